@@ -158,16 +158,21 @@ def load_image(path: str):
 
 def main():
     loaded_image = load_image(PATH)
-    map_dict = {}
+    tiles = [[None] * WIDTH for _ in range(HEIGHT)]
+    decor = []
     for tile in iter_generate_map(loaded_image):
         pos_x = tile["pos_x"]
         pos_y = tile["pos_y"]
-        map_dict.update({f"{pos_x}_{pos_y}": {
-            "sprite": tile["sprite"],
-            "decor": tile["decor"],
-        }})
-
-    print(json.dumps(map_dict, indent=2))
+        tiles[pos_y][pos_x] = tile["sprite"]
+        if tile["decor"] is not None:
+            decor.append({"position": [pos_x, pos_y], "sprite": tile["decor"]})
+
+    # only the terrain part of a level file - spawns, nexus and tower slots are added by hand
+    print(json.dumps({
+        "grid_size": [WIDTH, HEIGHT],
+        "tiles": tiles,
+        "decor": decor,
+    }, indent=2))
 
 
 if __name__ == '__main__':
@@ -0,0 +1,43 @@
+//! Packs the resources folder into a zip archive that the game embeds, so the binary runs
+//! without a resources folder next to it.
+
+use std::env;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use zip::write::FileOptions;
+use zip::CompressionMethod;
+
+const RESOURCES_DIR: &str = "src/resources";
+
+fn main() {
+    println!("cargo:rerun-if-changed={}", RESOURCES_DIR);
+
+    let zip_path = Path::new(&env::var("OUT_DIR").unwrap()).join("resources.zip");
+    let mut zip = zip::ZipWriter::new(File::create(&zip_path).unwrap());
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    for path in resource_files(Path::new(RESOURCES_DIR)) {
+        // ggez looks files up in the archive by their resource path, e.g. `/levels/01_meadow.json`
+        let relative = path.strip_prefix(RESOURCES_DIR).unwrap();
+        let name = format!("/{}", relative.to_string_lossy().replace('\\', "/"));
+        zip.start_file(name, options).unwrap();
+        zip.write_all(&fs::read(&path).unwrap()).unwrap();
+    }
+    zip.finish().unwrap();
+}
+
+/// Every file under `folder`, sorted so the archive is the same on every build.
+fn resource_files(folder: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = Vec::new();
+    for entry in fs::read_dir(folder).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            files.extend(resource_files(&path));
+        } else {
+            files.push(path);
+        }
+    }
+    files.sort();
+    files
+}
@@ -1,152 +1,208 @@
-use ggez::audio;
-use ggez::audio::SoundSource;
-use ggez::graphics::{self, Color};
-use ggez::{Context, ContextBuilder, GameResult};
+use ggez::graphics;
+use ggez::{Context, GameResult};
 use std::collections::HashMap;
-
+use std::io::Read;
+use crate::level::TileImage;
+use crate::manifest::{AssetCategory, AssetManifest};
+use crate::movement_helpers::GridPosition;
+use crate::sprite_sheet::{Animator, SpriteSheet};
+use crate::text::{TextRenderer, TextStyle};
+
+/// Every image and font listed in the asset manifest, see `rust_game::manifest`. Each image
+/// comes with a sprite sheet, which is a single frame unless the manifest points at a sheet.
 pub struct Assets {
-    towers: HashMap<String, graphics::Image>,
-    default_tower: graphics::Image,
-    decors: HashMap<String, graphics::Image>,
-    default_decor: graphics::Image,
-    tiles: HashMap<String, graphics::Image>,
-    default_tile: graphics::Image,
-    enemies: HashMap<String, graphics::Image>,
-    default_enemy: graphics::Image,
-    score_label: graphics::Image,
-    nexus_image: graphics::Image,
-    // font: graphics::Font,
-    // rocket_sound: audio::Source,
-    // nexus_hit_sound: audio::Source,
-    // enemy_hit_sound: audio::Source,
-    honey_image: graphics::Image,
-    heart_image: graphics::Image,
+    images: HashMap<AssetCategory, HashMap<String, graphics::Image>>,
+    sheets: HashMap<AssetCategory, HashMap<String, SpriteSheet>>,
+    default_images: HashMap<AssetCategory, graphics::Image>,
+    default_sheets: HashMap<AssetCategory, SpriteSheet>,
+    /// The images shipped with the level being played, looked up before the manifest's and
+    /// dropped when another level is loaded.
+    level_images: HashMap<AssetCategory, HashMap<String, graphics::Image>>,
+    level_sheets: HashMap<AssetCategory, HashMap<String, SpriteSheet>>,
+    text: TextRenderer,
 }
 
 impl Assets {
     pub(crate) fn new(ctx: &mut Context) -> GameResult<Assets> {
-        let mut tiles: HashMap<String, graphics::Image> = HashMap::new();
-        tiles.insert("6".to_string(), graphics::Image::new(ctx, "/06.png")?);
-        tiles.insert("14".to_string(), graphics::Image::new(ctx, "/14.png")?);
-        tiles.insert("19".to_string(), graphics::Image::new(ctx, "/19.png")?);
-        tiles.insert("33".to_string(), graphics::Image::new(ctx, "/33.png")?);
-        tiles.insert("44".to_string(), graphics::Image::new(ctx, "/44.png")?);
-        tiles.insert("48".to_string(), graphics::Image::new(ctx, "/48.png")?);
-        tiles.insert("57".to_string(), graphics::Image::new(ctx, "/57.png")?);
-        tiles.insert("62".to_string(), graphics::Image::new(ctx, "/62.png")?);
-        tiles.insert("70".to_string(), graphics::Image::new(ctx, "/70.png")?);
-        tiles.insert("71".to_string(), graphics::Image::new(ctx, "/71.png")?);
-
-        let mut decors: HashMap<String, graphics::Image> = HashMap::new();
-        decors.insert("101".to_string(), graphics::Image::new(ctx, "/101.png")?);
-        decors.insert("102".to_string(), graphics::Image::new(ctx, "/102.png")?);
-        decors.insert("103".to_string(), graphics::Image::new(ctx, "/103.png")?);
-        decors.insert("104".to_string(), graphics::Image::new(ctx, "/104.png")?);
-        decors.insert("105".to_string(), graphics::Image::new(ctx, "/105.png")?);
-
-        let mut enemies: HashMap<String, graphics::Image> = HashMap::new();
-        enemies.insert("slime_blue".to_string(), graphics::Image::new(ctx, "/slime_blue.png")?);
-        enemies.insert("slime_green".to_string(), graphics::Image::new(ctx, "/slime_blue.png")?);
-        enemies.insert("slime_orange".to_string(), graphics::Image::new(ctx, "/slime_blue.png")?);
-
-        let mut towers: HashMap<String, graphics::Image> = HashMap::new();
-        towers.insert("tower_1".to_string(), graphics::Image::new(ctx, "/tower_1.png")?);
-        towers.insert("tower_3".to_string(), graphics::Image::new(ctx, "/tower_3.png")?);
-        towers.insert("tower_5".to_string(), graphics::Image::new(ctx, "/tower_5.png")?);
-
-        let default_tile = graphics::Image::new(ctx, "/default_tile.png")?;
-        let default_decor = graphics::Image::new(ctx, "/101.png")?;
-        let default_enemy = graphics::Image::new(ctx, "/default_enemy.png")?;
-        let default_tower = graphics::Image::new(ctx, "/tower_disabled.png")?;
-        let score_label = graphics::Image::new(ctx, "/score_label.png")?;
-        let honey_image = graphics::Image::new(ctx, "/honey.png")?;
-        let heart_image = graphics::Image::new(ctx, "/heart.png")?;
-        let nexus_image = graphics::Image::new(ctx, "/nexus.png")?;
-
-        // let font = graphics::Font::new(ctx, "/font.ttf")?;
-        // let rocket_sound = audio::Source::new(ctx, "/pew.ogg")?;
-        // let nexus_hit_sound = audio::Source::new(ctx, "/pew.ogg")?;
-        // let enemy_hit_sound = audio::Source::new(ctx, "/pew.ogg")?;
+        let manifest = AssetManifest::load(ctx)?;
+
+        // sounds are only checked by the manifest, the audio manager loads them
+        let mut images: HashMap<AssetCategory, HashMap<String, graphics::Image>> = HashMap::new();
+        let mut sheets: HashMap<AssetCategory, HashMap<String, SpriteSheet>> = HashMap::new();
+        let mut default_images: HashMap<AssetCategory, graphics::Image> = HashMap::new();
+        let mut default_sheets: HashMap<AssetCategory, SpriteSheet> = HashMap::new();
+        for category in AssetCategory::ALL.iter().filter(|category| category.is_image()) {
+            let mut category_images: HashMap<String, graphics::Image> = HashMap::new();
+            let mut category_sheets: HashMap<String, SpriteSheet> = HashMap::new();
+            for (id, path) in manifest.entries(*category) {
+                let (image, sheet) = load_sprite(ctx, path)?;
+                category_images.insert(id.to_string(), image);
+                category_sheets.insert(id.to_string(), sheet);
+            }
+            images.insert(*category, category_images);
+            sheets.insert(*category, category_sheets);
+
+            let (default_image, default_sheet) = load_sprite(ctx, manifest.default_path(*category).unwrap())?;
+            default_images.insert(*category, default_image);
+            default_sheets.insert(*category, default_sheet);
+        }
+
+        let text = TextRenderer::new(ctx, &manifest)?;
 
         Ok(Assets {
-            towers,
-            default_tower,
-            tiles,
-            default_tile,
-            decors,
-            default_decor,
-            enemies,
-            default_enemy,
-            score_label,
-            nexus_image,
-            honey_image,
-            heart_image,
-            // font,
-            // rocket_sound,
-            // nexus_hit_sound,
-            // enemy_hit_sound,
+            images,
+            sheets,
+            default_images,
+            default_sheets,
+            level_images: HashMap::new(),
+            level_sheets: HashMap::new(),
+            text,
         })
     }
 
-    pub(crate) fn get_enemy_image(&mut self, mut sprite_name: String) -> &graphics::Image {
-        return if let Some(x) = self.enemies.get_mut(&sprite_name) {
-            x
-        } else {
-            &mut self.default_enemy
-            // panic!("The sprite {} was not found in the assets list", sprite_name);
-        };
+    /// Replaces the images of the previous level with the ones shipped with `tile_images`' level.
+    pub(crate) fn set_level_images(&mut self, ctx: &mut Context, tile_images: &[TileImage]) -> GameResult {
+        self.level_images.clear();
+        self.level_sheets.clear();
+        for tile_image in tile_images.iter() {
+            self.register_tile_image(ctx, tile_image)?;
+        }
+        Ok(())
     }
 
-    pub(crate) fn get_nexus_image(&mut self) -> &graphics::Image {
-        &self.nexus_image
+    /// Makes an image shipped with a level available as a tile or decor.
+    fn register_tile_image(&mut self, ctx: &mut Context, tile_image: &TileImage) -> GameResult {
+        let mut image = graphics::Image::new(ctx, &tile_image.path)?;
+
+        if let Some([x, y, width, height]) = tile_image.source_rect {
+            let image_width = image.width() as u32;
+            let fits = |start: u32, size: u32, limit: u32| size > 0 && start.checked_add(size).is_some_and(|end| end <= limit);
+            if !fits(x, width, image_width) || !fits(y, height, image.height() as u32) {
+                return Err(ggez::GameError::ResourceLoadError(format!(
+                    "sprite {} lies outside of {}", tile_image.id, tile_image.path
+                )));
+            }
+
+            let pixels = image.to_rgba8(ctx)?;
+            let mut cropped: Vec<u8> = Vec::with_capacity((width * height * 4) as usize);
+            for row in y..y + height {
+                let start = ((row * image_width + x) * 4) as usize;
+                cropped.extend_from_slice(&pixels[start..start + (width * 4) as usize]);
+            }
+            image = graphics::Image::from_rgba8(ctx, width as u16, height as u16, &cropped)?;
+        }
+
+        let category = if tile_image.decor { AssetCategory::Decor } else { AssetCategory::Tiles };
+        let sheet = SpriteSheet::single((image.width() as u32, image.height() as u32));
+        self.level_images.entry(category).or_default().insert(tile_image.id.clone(), image);
+        self.level_sheets.entry(category).or_default().insert(tile_image.id.clone(), sheet);
+        Ok(())
     }
 
-    pub(crate) fn get_score_label_image(&mut self) -> &graphics::Image {
-        &self.score_label
+    /// The image `id` of `category`, or the category's default if there is none.
+    pub(crate) fn get_image(&self, category: AssetCategory, id: &str) -> &graphics::Image {
+        let level_image = self.level_images.get(&category).and_then(|images| images.get(id));
+        match level_image.or_else(|| self.images.get(&category).and_then(|images| images.get(id))) {
+            Some(image) => image,
+            None => &self.default_images[&category],
+        }
     }
 
-    pub(crate) fn get_honey_image(&mut self) -> &graphics::Image {
-        &self.honey_image
+    pub(crate) fn get_sheet(&self, category: AssetCategory, id: &str) -> &SpriteSheet {
+        let level_sheet = self.level_sheets.get(&category).and_then(|sheets| sheets.get(id));
+        match level_sheet.or_else(|| self.sheets.get(&category).and_then(|sheets| sheets.get(id))) {
+            Some(sheet) => sheet,
+            None => &self.default_sheets[&category],
+        }
     }
 
-    pub(crate) fn get_heart_image(&mut self) -> &graphics::Image {
-        &self.heart_image
+    /// Draws the current frame of `animator` with the sheet's origin at `position`.
+    pub(crate) fn draw_sprite(&self, ctx: &mut Context, category: AssetCategory, id: &str, animator: &Animator, position: GridPosition) -> GameResult {
+        let image = self.get_image(category, id);
+        let sheet = self.get_sheet(category, id);
+        let frame = animator.frame(sheet);
+
+        let [x, y, width, height] = frame.rect;
+        let (image_width, image_height) = (image.width() as f32, image.height() as f32);
+        let source = graphics::Rect::new(x as f32 / image_width, y as f32 / image_height, width as f32 / image_width, height as f32 / image_height);
+
+        let mut dest: ggez::mint::Point2<f32> = position.into();
+        dest.x -= sheet.origin.0 * sheet.scale;
+        dest.y -= sheet.origin.1 * sheet.scale;
+        let mut scale_x = sheet.scale;
+        if frame.flip_x {
+            // mirroring happens around the left edge, so move the frame back in place
+            dest.x += width as f32 * sheet.scale;
+            scale_x = -scale_x;
+        }
+
+        let draw_params = graphics::DrawParam::new().src(source).dest(dest).scale([scale_x, sheet.scale]);
+        graphics::draw(ctx, image, draw_params)
     }
 
-    pub(crate) fn get_tower_image(&mut self, level: i32) -> &graphics::Image {
-        let sprite_name = if level >= 5 {
-            "tower_5"
-        } else if level >= 3 {
-            "tower_3"
-        } else if level >= 1 {
-            "tower_1"
-        } else {
-            "tower_disabled"
-        };
+    /// Draws `text` with its top left corner at `dest`, in screen or world coordinates
+    /// like any other drawing.
+    pub(crate) fn draw_text(&mut self, ctx: &mut Context, text: &str, dest: ggez::mint::Point2<f32>, style: &TextStyle) -> GameResult {
+        self.text.draw(ctx, text, [dest.x, dest.y], style)
+    }
 
-        return if let Some(x) = self.towers.get_mut(&sprite_name.to_string()) {
-            x
-        } else {
-            &mut self.default_tower
-            // panic!("The sprite {} was not found in the assets list", sprite_name);
-        };
+    /// The size of `text` in pixels.
+    pub(crate) fn text_dimensions(&mut self, ctx: &mut Context, text: &str, style: &TextStyle) -> (f32, f32) {
+        self.text.dimensions(ctx, text, style)
     }
 
-    pub(crate) fn get_tile_image(&mut self, mut sprite_name: String) -> &graphics::Image {
-        return if let Some(x) = self.tiles.get_mut(&sprite_name) {
-            x
-        } else {
-            &mut self.default_tile
-            // panic!("The sprite {} was not found in the assets list", sprite_name);
-        };
+    pub(crate) fn set_text_scale(&mut self, scale: f32) {
+        self.text.set_scale(scale);
     }
 
-    pub(crate) fn get_decor_image(&mut self, mut decor_name: String) -> &graphics::Image {
-        return if let Some(x) = self.decors.get_mut(&decor_name) {
-            x
-        } else {
-            &mut self.default_decor
-            // panic!("The sprite {} was not found in the assets list", sprite_name);
+    /// Lets go of the texts that weren't drawn this frame.
+    pub(crate) fn end_frame(&mut self) {
+        self.text.end_frame();
+    }
+
+    /// Sprites of the extra level layers can be decor or tiles, decor is looked up first.
+    pub(crate) fn get_overlay_image(&self, sprite_name: &str) -> &graphics::Image {
+        let is_decor = |images: &HashMap<AssetCategory, HashMap<String, graphics::Image>>| {
+            images.get(&AssetCategory::Decor).is_some_and(|decor| decor.contains_key(sprite_name))
         };
+        if is_decor(&self.level_images) || is_decor(&self.images) {
+            self.get_image(AssetCategory::Decor, sprite_name)
+        } else {
+            self.get_image(AssetCategory::Tiles, sprite_name)
+        }
     }
+
+    /// The ids of every image in `category`, including the current level's, in the order
+    /// the editor palette shows them.
+    pub(crate) fn get_ids(&self, category: AssetCategory) -> Vec<String> {
+        let images = &self.images[&category];
+        let mut ids: Vec<String> = images.keys().cloned().collect();
+        if let Some(level_images) = self.level_images.get(&category) {
+            ids.extend(level_images.keys().filter(|id| !images.contains_key(*id)).cloned());
+        }
+        sorted_ids(ids)
+    }
+}
+
+/// Loads an image, or a sprite sheet and its image when `path` is a sheet's JSON file.
+fn load_sprite(ctx: &mut Context, path: &str) -> GameResult<(graphics::Image, SpriteSheet)> {
+    if !path.ends_with(".json") {
+        let image = graphics::Image::new(ctx, path)?;
+        let sheet = SpriteSheet::single((image.width() as u32, image.height() as u32));
+        return Ok((image, sheet));
+    }
+
+    let mut contents = String::new();
+    ggez::filesystem::open(ctx, path)?.read_to_string(&mut contents)?;
+    let json: serde_json::Value = serde_json::from_str(&contents)
+        .map_err(|e| ggez::GameError::ResourceLoadError(format!("sprite sheet {}: invalid JSON: {}", path, e)))?;
+    let image = graphics::Image::new(ctx, SpriteSheet::image_path(&json, path)?)?;
+    let sheet = SpriteSheet::from_json(&json, path, (image.width() as u32, image.height() as u32))?;
+    Ok((image, sheet))
+}
+
+/// Numeric ids first, in numeric order, then the named ones from Tiled tilesets.
+fn sorted_ids(mut ids: Vec<String>) -> Vec<String> {
+    ids.sort_by_key(|id| (id.parse::<u32>().unwrap_or(u32::MAX), id.clone()));
+    ids
 }
@@ -0,0 +1,160 @@
+use ggez::audio::{self, SoundSource};
+use ggez::{Context, GameResult};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use crate::game_event::GameEvent;
+use crate::manifest::{AssetCategory, AssetManifest};
+use crate::settings::Settings;
+
+/// How long one music track takes to fade into the next.
+const CROSSFADE: Duration = Duration::from_millis(1500);
+/// The same sound effect isn't played again sooner than this, so a dozen towers shooting
+/// at once don't add up to noise.
+const SFX_COOLDOWN: Duration = Duration::from_millis(80);
+
+pub const MENU_MUSIC: &str = "music_menu";
+pub const LEVEL_MUSIC: &str = "music_level";
+
+fn event_sound(event: GameEvent) -> &'static str {
+    match event {
+        GameEvent::Shot => "shot",
+        GameEvent::Hit { .. } => "hit",
+        GameEvent::Kill { .. } => "kill",
+        GameEvent::NexusHit => "nexus_hit",
+        GameEvent::Upgrade => "upgrade",
+        GameEvent::GameOver => "game_over",
+    }
+}
+
+struct MusicTrack {
+    id: String,
+    source: audio::Source,
+    /// From 0, silent, to 1, full volume.
+    fade: f32,
+}
+
+/// Plays sound effects for game events and loops background music, fading from one track
+/// to the next. Without an audio device the game runs silently: everything here is a no-op.
+pub struct AudioManager {
+    enabled: bool,
+    settings: Settings,
+    sounds: HashMap<String, audio::SoundData>,
+    /// When each sound effect was last played, by id.
+    last_played: HashMap<&'static str, Instant>,
+    music: Option<MusicTrack>,
+    fading_out: Vec<MusicTrack>,
+}
+
+impl AudioManager {
+    /// `enabled` is whether the context was built with the audio module.
+    pub fn new(ctx: &mut Context, enabled: bool, settings: Settings) -> GameResult<Self> {
+        let mut audio_manager = AudioManager {
+            enabled,
+            settings,
+            sounds: HashMap::new(),
+            last_played: HashMap::new(),
+            music: None,
+            fading_out: Vec::new(),
+        };
+        audio_manager.reload(ctx)?;
+        Ok(audio_manager)
+    }
+
+    /// Reads the sounds of the asset manifest again. Music that is playing keeps playing.
+    pub fn reload(&mut self, ctx: &mut Context) -> GameResult {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        let manifest = AssetManifest::load(ctx)?;
+        let mut sounds: HashMap<String, audio::SoundData> = HashMap::new();
+        for (id, path) in manifest.entries(AssetCategory::Sounds) {
+            sounds.insert(id.to_string(), audio::SoundData::new(ctx, path)?);
+        }
+        self.sounds = sounds;
+        Ok(())
+    }
+
+    pub fn set_settings(&mut self, settings: Settings) {
+        self.settings = settings;
+        self.apply_music_volume();
+    }
+
+    pub fn play(&mut self, ctx: &mut Context, event: GameEvent) {
+        if !self.enabled {
+            return;
+        }
+        let sound = event_sound(event);
+        let now = Instant::now();
+        if self.last_played.get(sound).is_some_and(|last| now - *last < SFX_COOLDOWN) {
+            return;
+        }
+        self.last_played.insert(sound, now);
+
+        let volume = self.settings.master_volume * self.settings.sfx_volume;
+        if let Err(e) = self.play_sound(ctx, sound, volume) {
+            println!("failed to play the {} sound: {}", sound, e);
+        }
+    }
+
+    fn play_sound(&self, ctx: &mut Context, id: &str, volume: f32) -> GameResult {
+        let data = match self.sounds.get(id) {
+            Some(data) => data.clone(),
+            None => return Ok(()),
+        };
+        let mut source = audio::Source::from_data(ctx, data)?;
+        source.set_volume(volume);
+        source.play_detached(ctx)
+    }
+
+    /// Fades over to the track `id`, unless it's playing already.
+    pub fn play_music(&mut self, ctx: &mut Context, id: &str) {
+        if !self.enabled || self.music.as_ref().is_some_and(|music| music.id == id) {
+            return;
+        }
+        let data = match self.sounds.get(id) {
+            Some(data) => data.clone(),
+            None => return,
+        };
+
+        let source = audio::Source::from_data(ctx, data).and_then(|mut source| {
+            source.set_repeat(true);
+            source.set_volume(0.0);
+            source.play(ctx)?;
+            Ok(source)
+        });
+        match source {
+            Ok(source) => {
+                if let Some(music) = self.music.take() {
+                    self.fading_out.push(music);
+                }
+                self.music = Some(MusicTrack { id: id.to_string(), source, fade: 0.0 });
+            }
+            Err(e) => println!("failed to play the music {}: {}", id, e),
+        }
+    }
+
+    /// Advances the crossfade by `delta`, the time since the last call.
+    pub fn update(&mut self, ctx: &mut Context, delta: Duration) {
+        let step = delta.as_secs_f32() / CROSSFADE.as_secs_f32();
+        if let Some(music) = self.music.as_mut() {
+            music.fade = (music.fade + step).min(1.0);
+        }
+        for music in self.fading_out.iter_mut() {
+            music.fade = (music.fade - step).max(0.0);
+            if music.fade == 0.0 {
+                // a failing stop leaves the track silent anyway
+                let _ = music.source.stop(ctx);
+            }
+        }
+        self.fading_out.retain(|music| music.fade > 0.0);
+        self.apply_music_volume();
+    }
+
+    fn apply_music_volume(&mut self) {
+        let volume = self.settings.master_volume * self.settings.music_volume;
+        for music in self.music.iter_mut().chain(self.fading_out.iter_mut()) {
+            music.source.set_volume(volume * music.fade);
+        }
+    }
+}
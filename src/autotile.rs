@@ -0,0 +1,66 @@
+//! Picks terrain sprites for a cell from its neighbours, so level tools only have to know
+//! where the road, water and tower pads are. Same rules as `bin/map_generator.py`.
+
+pub const GRASS: &str = "70";
+pub const WATER: &str = "80";
+/// The second frame of animated water.
+pub const WATER_RIPPLE: &str = "81";
+pub const ROAD: &str = "71";
+pub const ROAD_EDGE_UP: &str = "6";
+pub const ROAD_EDGE_DOWN: &str = "44";
+pub const ROAD_EDGE_LEFT: &str = "14";
+pub const ROAD_EDGE_RIGHT: &str = "48";
+pub const ROAD_CORNER_UP_LEFT: &str = "19";
+pub const ROAD_CORNER_UP_RIGHT: &str = "33";
+pub const ROAD_CORNER_DOWN_LEFT: &str = "57";
+pub const ROAD_CORNER_DOWN_RIGHT: &str = "62";
+
+pub const DECOR: [&str; 5] = ["101", "102", "103", "104", "105"];
+
+/// Offsets from a tower slot and the sprite drawn there. A tower centered at `(x, y)`
+/// stands on the 2x2 pad spanning `(x - 1, y - 1)` to `(x, y)`.
+pub const TOWER_PAD: [(i16, i16, &str); 4] = [
+    (-1, -1, ROAD_CORNER_UP_LEFT),
+    (0, -1, ROAD_CORNER_UP_RIGHT),
+    (-1, 0, ROAD_CORNER_DOWN_LEFT),
+    (0, 0, ROAD_CORNER_DOWN_RIGHT),
+];
+
+/// The sprite of a road cell at `(x, y)`: edges and corners face the neighbours that are
+/// not road. Cells outside the grid count as road so roads can run off the map.
+pub fn road_sprite<F: Fn(i16, i16) -> bool>(is_road: F, x: i16, y: i16) -> &'static str {
+    let up = !is_road(x, y - 1);
+    let down = !is_road(x, y + 1);
+    let left = !is_road(x - 1, y);
+    let right = !is_road(x + 1, y);
+
+    if up {
+        if right {
+            ROAD_CORNER_UP_RIGHT
+        } else if left {
+            ROAD_CORNER_UP_LEFT
+        } else {
+            ROAD_EDGE_UP
+        }
+    } else if down {
+        if right {
+            ROAD_CORNER_DOWN_RIGHT
+        } else if left {
+            ROAD_CORNER_DOWN_LEFT
+        } else {
+            ROAD_EDGE_DOWN
+        }
+    } else if left {
+        ROAD_EDGE_LEFT
+    } else if right {
+        ROAD_EDGE_RIGHT
+    } else {
+        ROAD
+    }
+}
+
+/// A decor sprite that only depends on the position, so regenerating a level keeps its decor.
+pub fn decor_sprite(x: i16, y: i16) -> &'static str {
+    let hash = (x as i64 * 73_856_093) ^ (y as i64 * 19_349_663);
+    DECOR[hash.rem_euclid(DECOR.len() as i64) as usize]
+}
@@ -0,0 +1,37 @@
+//! Converts a color-coded schema image into a level file:
+//!
+//!     cargo run --bin schema-to-level -- src/resources/my_level.png src/resources/levels/05_my_level.json
+//!
+//! Without an output path the level is printed to stdout. See `rust_game::schema` for the colors.
+
+use rust_game::schema;
+use std::{env, fs, process};
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.is_empty() || args.len() > 2 {
+        eprintln!("usage: schema-to-level <schema.png> [level.json]");
+        process::exit(2);
+    }
+
+    let bytes = fs::read(&args[0]).unwrap_or_else(|e| {
+        eprintln!("failed to read {}: {}", args[0], e);
+        process::exit(1);
+    });
+    let level = schema::load_schema(&bytes, &args[0]).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1);
+    });
+
+    let level_json = level.to_json_string();
+    match args.get(1) {
+        Some(output) => {
+            if let Err(e) = fs::write(output, level_json) {
+                eprintln!("failed to write {}: {}", output, e);
+                process::exit(1);
+            }
+            println!("{}: {} spawns, {} tower slots", output, level.spawns.len(), level.tower_slots.len());
+        }
+        None => print!("{}", level_json),
+    }
+}
@@ -0,0 +1,83 @@
+//! Checks level files for problems, so level changes can be gated on it:
+//!
+//!     cargo run --bin validate-level -- src/resources/levels/*
+//!
+//! Exits with 1 if any level fails to load or has problems. See `rust_game::validation`.
+//! The known sprites come from the asset manifest of the resources folder the level is in.
+
+use rust_game::manifest::AssetManifest;
+use rust_game::{campaign, validation};
+use std::{env, fs, path, process};
+
+/// Reads the `assets.json` in the closest folder above the level.
+fn load_manifest(level_path: &str) -> Result<AssetManifest, String> {
+    let level_path = fs::canonicalize(level_path).map_err(|e| e.to_string())?;
+    let manifest_path = level_path.ancestors()
+        .map(|folder| folder.join("assets.json"))
+        .find(|manifest_path| manifest_path.is_file())
+        .ok_or_else(|| "no assets.json found in the folders above the level".to_string())?;
+
+    let contents = fs::read_to_string(&manifest_path).map_err(|e| e.to_string())?;
+    let json: serde_json::Value = serde_json::from_str(&contents)
+        .map_err(|e| format!("{}: invalid JSON: {}", manifest_path.display(), e))?;
+    AssetManifest::from_json(&json, &manifest_path.to_string_lossy()).map_err(|e| e.to_string())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.is_empty() {
+        eprintln!("usage: validate-level <level>...");
+        process::exit(2);
+    }
+
+    let mut failed = false;
+    for level_path in args.iter() {
+        let bytes = match fs::read(level_path) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                println!("{}: failed to read: {}", level_path, e);
+                failed = true;
+                continue;
+            }
+        };
+
+        // files referenced by the level resolve like resource paths, starting with a '/'
+        let absolute = path::Path::new(level_path).is_absolute();
+        let mut open = |file_path: &str| {
+            let file_path = if absolute { file_path } else { file_path.trim_start_matches('/') };
+            Ok(fs::read_to_string(file_path)?)
+        };
+
+        let level = match campaign::parse_level_file(level_path, &bytes, &mut open) {
+            Ok(level) => level,
+            Err(e) => {
+                println!("{}: {}", level_path, e);
+                failed = true;
+                continue;
+            }
+        };
+
+        let manifest = match load_manifest(level_path) {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                println!("{}: {}", level_path, e);
+                failed = true;
+                continue;
+            }
+        };
+
+        let problems = validation::validate_level(&level, &manifest);
+        if problems.is_empty() {
+            println!("{}: ok", level_path);
+        } else {
+            failed = true;
+            for problem in problems.iter() {
+                println!("{}: {}", level_path, problem);
+            }
+        }
+    }
+
+    if failed {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,99 @@
+//! Bitmap fonts: an image with a grid of glyphs and a JSON descriptor next to it, listed
+//! among the fonts of the asset manifest in place of a TrueType file:
+//!
+//! ```json
+//! {
+//!   "image": "/fonts/pixel.png",
+//!   "grid": [6, 8],
+//!   "chars": " !\"#$%&'()*+,-./0123456789:;<=>?@ABCDEFGHIJKLMNOPQRSTUVWXYZ",
+//!   "spacing": 1
+//! }
+//! ```
+//!
+//! The glyphs are in the order of `chars`, row by row. `spacing` is the gap between glyphs
+//! in pixels of the image.
+
+use ggez::{GameError, GameResult};
+use serde_json::Value;
+use std::collections::HashMap;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BitmapFont {
+    pub glyph_size: (u32, u32),
+    pub spacing: f32,
+    glyphs: HashMap<char, [u32; 4]>,
+}
+
+/// One glyph of laid out text: the part of the image to draw and where, in pixels of the
+/// image from the top left of the text.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PlacedGlyph {
+    pub rect: [u32; 4],
+    pub x: f32,
+    pub y: f32,
+}
+
+impl BitmapFont {
+    /// The path of the font's image, to be loaded before `from_json` can be called.
+    pub fn image_path<'a>(json: &'a Value, source: &str) -> GameResult<&'a str> {
+        json["image"].as_str().ok_or_else(|| font_error(source, "image must be a path"))
+    }
+
+    pub fn from_json(json: &Value, source: &str, image_size: (u32, u32)) -> GameResult<BitmapFont> {
+        let glyph_size = match (json["grid"][0].as_u64(), json["grid"][1].as_u64()) {
+            (Some(width), Some(height)) if width > 0 && height > 0 => (width as u32, height as u32),
+            _ => return Err(font_error(source, "grid must be [glyph_width, glyph_height]")),
+        };
+        let chars = json["chars"].as_str()
+            .ok_or_else(|| font_error(source, "chars must list the glyphs of the image"))?;
+
+        let columns = image_size.0 / glyph_size.0;
+        let rows = image_size.1 / glyph_size.1;
+        if chars.chars().count() as u32 > columns * rows {
+            return Err(font_error(source, &format!("chars lists {} glyphs but the image holds {}", chars.chars().count(), columns * rows)));
+        }
+
+        let glyphs: HashMap<char, [u32; 4]> = chars.chars().enumerate()
+            .map(|(index, c)| {
+                let (column, row) = (index as u32 % columns, index as u32 / columns);
+                (c, [column * glyph_size.0, row * glyph_size.1, glyph_size.0, glyph_size.1])
+            })
+            .collect();
+
+        Ok(BitmapFont {
+            glyph_size,
+            spacing: json["spacing"].as_f64().unwrap_or(0.0) as f32,
+            glyphs,
+        })
+    }
+
+    /// Places the glyphs of `text`, which may have several lines. Characters the font
+    /// doesn't have are left out, but take up their room.
+    pub fn layout(&self, text: &str) -> Vec<PlacedGlyph> {
+        let advance = self.glyph_size.0 as f32 + self.spacing;
+        let line_height = self.glyph_size.1 as f32 + self.spacing;
+
+        let mut placed: Vec<PlacedGlyph> = Vec::new();
+        for (line_index, line) in text.lines().enumerate() {
+            for (index, c) in line.chars().enumerate() {
+                if let Some(rect) = self.glyphs.get(&c) {
+                    placed.push(PlacedGlyph { rect: *rect, x: index as f32 * advance, y: line_index as f32 * line_height });
+                }
+            }
+        }
+        placed
+    }
+
+    /// The width and height of `text` in pixels of the image.
+    pub fn measure(&self, text: &str) -> (f32, f32) {
+        let longest = text.lines().map(|line| line.chars().count()).max().unwrap_or(0) as f32;
+        let lines = text.lines().count() as f32;
+        let width = (longest * (self.glyph_size.0 as f32 + self.spacing) - self.spacing).max(0.0);
+        let height = (lines * (self.glyph_size.1 as f32 + self.spacing) - self.spacing).max(0.0);
+        (width, height)
+    }
+}
+
+fn font_error(source: &str, message: &str) -> GameError {
+    GameError::ResourceLoadError(format!("bitmap font {}: {}", source, message))
+}
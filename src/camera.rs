@@ -0,0 +1,124 @@
+//! The part of the map that is on screen. `GridPosition`s convert to world pixels with a
+//! fixed `GRID_CELL_SIZE`; the camera maps those world pixels to the window, so maps of any
+//! size can be panned and zoomed. UI is drawn in screen pixels, see `screen_rect`.
+
+use ggez::graphics::Rect;
+use crate::config::GRID_CELL_SIZE;
+use crate::movement_helpers::GridPosition;
+
+pub const MIN_ZOOM: f32 = 0.25;
+pub const MAX_ZOOM: f32 = 4.0;
+
+pub struct Camera {
+    /// The world pixel at the top left corner of the window.
+    offset: (f32, f32),
+    zoom: f32,
+    viewport: (f32, f32),
+    world_size: (f32, f32),
+}
+
+impl Camera {
+    pub fn new(viewport: (f32, f32), grid_size: (i16, i16)) -> Self {
+        let mut camera = Camera {
+            offset: (0.0, 0.0),
+            zoom: 1.0,
+            viewport,
+            world_size: (0.0, 0.0),
+        };
+        camera.set_grid_size(grid_size);
+        camera
+    }
+
+    /// Points the camera at the top left corner of a new map at the default zoom.
+    pub fn set_grid_size(&mut self, grid_size: (i16, i16)) {
+        self.world_size = (
+            grid_size.0 as f32 * GRID_CELL_SIZE.0 as f32,
+            grid_size.1 as f32 * GRID_CELL_SIZE.1 as f32,
+        );
+        self.offset = (0.0, 0.0);
+        self.zoom = 1.0;
+        self.clamp();
+    }
+
+    pub fn resize(&mut self, viewport: (f32, f32)) {
+        self.viewport = viewport;
+        self.clamp();
+    }
+
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    /// Moves the view by a distance in screen pixels.
+    pub fn pan(&mut self, dx: f32, dy: f32) {
+        self.offset.0 += dx / self.zoom;
+        self.offset.1 += dy / self.zoom;
+        self.clamp();
+    }
+
+    /// Zooms by `factor`, keeping the world under the screen point `anchor` in place.
+    pub fn zoom_at(&mut self, factor: f32, anchor: (f32, f32)) {
+        let anchored = self.screen_to_world(anchor.0, anchor.1);
+        self.zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+        self.offset = (anchored.0 - anchor.0 / self.zoom, anchored.1 - anchor.1 / self.zoom);
+        self.clamp();
+    }
+
+    /// Centers the view on a grid position, as far as the edges of the map allow.
+    pub fn look_at(&mut self, position: GridPosition) {
+        let (x, y): (f32, f32) = position.into();
+        let world = (x * GRID_CELL_SIZE.0 as f32, y * GRID_CELL_SIZE.1 as f32);
+        self.offset = (world.0 - self.viewport.0 / self.zoom / 2.0, world.1 - self.viewport.1 / self.zoom / 2.0);
+        self.clamp();
+    }
+
+    /// The world rectangle on screen, for `graphics::set_screen_coordinates` before drawing the map.
+    pub fn view_rect(&self) -> Rect {
+        Rect::new(self.offset.0, self.offset.1, self.viewport.0 / self.zoom, self.viewport.1 / self.zoom)
+    }
+
+    /// The whole window in screen pixels, for drawing UI on top of the map.
+    pub fn screen_rect(&self) -> Rect {
+        Rect::new(0.0, 0.0, self.viewport.0, self.viewport.1)
+    }
+
+    pub fn screen_to_world(&self, x: f32, y: f32) -> (f32, f32) {
+        (self.offset.0 + x / self.zoom, self.offset.1 + y / self.zoom)
+    }
+
+    pub fn world_to_screen(&self, x: f32, y: f32) -> (f32, f32) {
+        ((x - self.offset.0) * self.zoom, (y - self.offset.1) * self.zoom)
+    }
+
+    pub fn screen_to_grid(&self, x: f32, y: f32) -> GridPosition {
+        let (world_x, world_y) = self.screen_to_world(x, y);
+        GridPosition::new(world_x / GRID_CELL_SIZE.0 as f32, world_y / GRID_CELL_SIZE.1 as f32)
+    }
+
+    /// The first and one past the last visible cell on both axes, within `grid_size`.
+    pub fn visible_cells(&self, grid_size: (i16, i16)) -> ((i16, i16), (i16, i16)) {
+        let view = self.view_rect();
+        let cell = (GRID_CELL_SIZE.0 as f32, GRID_CELL_SIZE.1 as f32);
+        let first = ((view.x / cell.0).floor().max(0.0) as i16, (view.y / cell.1).floor().max(0.0) as i16);
+        let last = (
+            (((view.x + view.w) / cell.0).ceil().max(0.0) as i16).min(grid_size.0),
+            (((view.y + view.h) / cell.1).ceil().max(0.0) as i16).min(grid_size.1),
+        );
+        (first, last)
+    }
+
+    /// Keeps the view on the map. A map smaller than the window is centered instead.
+    fn clamp(&mut self) {
+        let visible = (self.viewport.0 / self.zoom, self.viewport.1 / self.zoom);
+        self.offset.0 = clamp_axis(self.offset.0, visible.0, self.world_size.0);
+        self.offset.1 = clamp_axis(self.offset.1, visible.1, self.world_size.1);
+    }
+}
+
+fn clamp_axis(offset: f32, visible: f32, world: f32) -> f32 {
+    if world <= visible {
+        (world - visible) / 2.0
+    } else {
+        offset.max(0.0).min(world - visible)
+    }
+}
@@ -0,0 +1,181 @@
+use ggez::{filesystem, Context, GameError, GameResult};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use crate::level::Level;
+use crate::{schema, tiled};
+use crate::tiled::FileOpener;
+
+const LEVELS_DIR: &str = "/levels";
+const PROFILE_PATH: &str = "/profile.json";
+const LEVEL_EXTENSIONS: [&str; 4] = ["json", "tmj", "tmx", "png"];
+
+pub struct LevelEntry {
+    pub path: String,
+    pub name: String,
+}
+
+/// The ordered list of levels found in the levels directory. Levels are played in
+/// file name order, so `01_meadow.json` comes before `02_switchback.json`. Besides the
+/// native JSON format, maps saved by Tiled (`.tmj`, `.tmx`) and schema images (`.png`)
+/// are picked up as well.
+pub struct Campaign {
+    pub levels: Vec<LevelEntry>,
+}
+
+impl Campaign {
+    pub fn discover(ctx: &mut Context) -> GameResult<Campaign> {
+        let paths: Vec<String> = filesystem::read_dir(ctx, LEVELS_DIR)?
+            .filter(|path| path.extension().is_some_and(|ext| LEVEL_EXTENSIONS.iter().any(|level_ext| ext == *level_ext)))
+            .map(|path| path.to_string_lossy().replace('\\', "/"))
+            .collect();
+        let paths = level_paths(paths);
+
+        // one broken file, or an image that only looks like a schema, shouldn't keep the
+        // rest of the campaign from loading
+        let mut levels: Vec<LevelEntry> = Vec::new();
+        for path in paths {
+            match load_level_file(ctx, &path) {
+                Ok(level) => levels.push(LevelEntry { path, name: level.name }),
+                Err(e) => println!("skipping {}: {}", path, e),
+            }
+        }
+
+        if levels.is_empty() {
+            return Err(GameError::ResourceLoadError(format!("no level in {} could be loaded", LEVELS_DIR)));
+        }
+
+        Ok(Campaign { levels })
+    }
+
+    pub fn load_level(&self, ctx: &mut Context, index: usize) -> GameResult<Level> {
+        load_level_file(ctx, &self.levels[index].path)
+    }
+}
+
+/// Sorts the level files into playing order. The editor saves levels made in other formats
+/// as a level file next to the original, so where both exist only the level file is kept.
+pub fn level_paths(mut paths: Vec<String>) -> Vec<String> {
+    paths.sort();
+    paths.dedup();
+    let stem = |path: &str| path.rsplit_once('.').map_or(path, |(stem, _)| stem).to_string();
+    let saved_stems: Vec<String> = paths.iter().filter(|path| path.ends_with(".json")).map(|path| stem(path)).collect();
+    paths.retain(|path| path.ends_with(".json") || !saved_stems.contains(&stem(path)));
+    paths
+}
+
+fn read_file(ctx: &mut Context, path: &str) -> GameResult<String> {
+    let mut contents = String::new();
+    filesystem::open(ctx, path)?.read_to_string(&mut contents)?;
+    Ok(contents)
+}
+
+fn load_level_file(ctx: &mut Context, path: &str) -> GameResult<Level> {
+    let mut bytes: Vec<u8> = Vec::new();
+    filesystem::open(ctx, path)?.read_to_end(&mut bytes)?;
+    let mut open = |tileset_path: &str| read_file(ctx, tileset_path);
+    parse_level_file(path, &bytes, &mut open)
+}
+
+/// Builds a level from the contents of a level file in any of the supported formats,
+/// picked by the extension of `path`. `open` reads the files the level refers to.
+pub fn parse_level_file(path: &str, bytes: &[u8], open: &mut FileOpener) -> GameResult<Level> {
+    if path.ends_with(".png") {
+        return schema::load_schema(bytes, path);
+    }
+
+    let contents = std::str::from_utf8(bytes)
+        .map_err(|e| GameError::ResourceLoadError(format!("level {}: {}", path, e)))?;
+
+    if path.ends_with(".tmx") {
+        return tiled::load_tmx(contents, path, open);
+    }
+
+    let json: Value = serde_json::from_str(contents)
+        .map_err(|e| GameError::ResourceLoadError(format!("level {}: invalid JSON: {}", path, e)))?;
+    if path.ends_with(".tmj") || tiled::is_tiled_json(&json) {
+        tiled::load_tmj(contents, path, open)
+    } else {
+        Level::from_json(&json, path)
+    }
+}
+
+/// Campaign progress that survives restarts: how far the player got and the
+/// best score on every level, keyed by the level's file path.
+pub struct Profile {
+    unlocked_levels: usize,
+    best_scores: HashMap<String, i32>,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Profile::new()
+    }
+}
+
+impl Profile {
+    pub fn new() -> Self {
+        Profile {
+            unlocked_levels: 1,
+            best_scores: HashMap::new(),
+        }
+    }
+
+    /// Loads the saved profile, starting a fresh one if there is none yet or it can't be read.
+    pub fn load(ctx: &mut Context) -> Self {
+        let profile_file = match filesystem::open(ctx, PROFILE_PATH) {
+            Ok(profile_file) => profile_file,
+            Err(_) => return Profile::new(),
+        };
+
+        let json: Value = match serde_json::from_reader(profile_file) {
+            Ok(json) => json,
+            Err(e) => {
+                println!("profile is corrupted, starting a new one: {}", e);
+                return Profile::new();
+            }
+        };
+
+        let mut profile = Profile::new();
+        if let Some(unlocked_levels) = json["unlocked_levels"].as_u64() {
+            profile.unlocked_levels = (unlocked_levels as usize).max(1);
+        }
+        if let Some(best_scores) = json["best_scores"].as_object() {
+            for (level, score) in best_scores {
+                if let Some(score) = score.as_i64() {
+                    profile.best_scores.insert(level.clone(), score as i32);
+                }
+            }
+        }
+        profile
+    }
+
+    pub fn save(&self, ctx: &mut Context) -> GameResult {
+        let json = json!({
+            "unlocked_levels": self.unlocked_levels,
+            "best_scores": self.best_scores,
+        });
+
+        let mut profile_file = filesystem::create(ctx, PROFILE_PATH)?;
+        profile_file.write_all(json.to_string().as_bytes())?;
+        Ok(())
+    }
+
+    pub fn is_unlocked(&self, level_index: usize) -> bool {
+        level_index < self.unlocked_levels
+    }
+
+    pub fn best_score(&self, level_path: &str) -> Option<i32> {
+        self.best_scores.get(level_path).copied()
+    }
+
+    /// Keeps the best score for the level and unlocks the next one if it was completed.
+    pub fn record_result(&mut self, level_index: usize, level_path: &str, score: i32, completed: bool) {
+        let best_score = self.best_scores.entry(level_path.to_string()).or_insert(score);
+        *best_score = (*best_score).max(score);
+
+        if completed {
+            self.unlocked_levels = self.unlocked_levels.max(level_index + 2);
+        }
+    }
+}
@@ -1,9 +1,11 @@
-pub const GRID_SIZE: (i16, i16) = (64, 32);
+/// Levels bring their own size, this is the size of generated levels and of the window
+/// in cells at the default zoom.
+pub const DEFAULT_GRID_SIZE: (i16, i16) = (64, 32);
 pub const GRID_CELL_SIZE: (i16, i16) = (16, 16);
 
 pub const SCREEN_SIZE: (u32, u32) = (
-    GRID_SIZE.0 as u32 * GRID_CELL_SIZE.0 as u32,
-    GRID_SIZE.1 as u32 * GRID_CELL_SIZE.1 as u32,
+    DEFAULT_GRID_SIZE.0 as u32 * GRID_CELL_SIZE.0 as u32,
+    DEFAULT_GRID_SIZE.1 as u32 * GRID_CELL_SIZE.1 as u32,
 );
 
 pub const UPDATES_PER_SECOND: f32 = 128.0;
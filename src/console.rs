@@ -0,0 +1,194 @@
+//! The developer console: a line of input, what it printed so far and the commands it
+//! understands. The game carries the commands out.
+
+use std::collections::VecDeque;
+
+/// How many lines of output the console keeps.
+const LOG_SIZE: usize = 200;
+/// The most `tick` runs at once, the game stands still until they're done.
+pub const MAX_TICKS: u32 = 10_000;
+/// The most enemies `spawn` brings at once.
+pub const MAX_SPAWN: u32 = 500;
+/// The most honey `honey` sets, low enough that rewards can't overflow it.
+pub const MAX_HONEY: i32 = 1_000_000;
+/// The most lives `lives` sets.
+pub const MAX_LIVES: i32 = 1_000;
+/// The highest `hardness`, enemy health and rewards grow with it.
+pub const MAX_HARDNESS: i32 = 10_000;
+
+pub const HELP: [&str; 12] = [
+    "honey <amount>          set the honey",
+    "lives <amount>          set the lives",
+    "spawn <sprite> [count]  spawn enemies right away",
+    "hardness <level>        set the hardness of new enemies",
+    "upgrade all             upgrade every tower for free",
+    "tick <count>            run the game ahead",
+    "seed                    print the seed of an endless level",
+    "save                    quick save",
+    "load                    quick load",
+    "godmode                 toggle losing lives",
+    "clear                   clear the console",
+    "help                    list the commands",
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    Honey(i32),
+    Lives(i32),
+    Spawn { sprite: String, count: u32 },
+    Hardness(i32),
+    UpgradeAll,
+    Tick(u32),
+    Seed,
+    Save,
+    Load,
+    GodMode,
+    Clear,
+    Help,
+}
+
+fn number<T: std::str::FromStr>(argument: Option<&str>, usage: &str) -> Result<T, String> {
+    argument.and_then(|argument| argument.parse().ok()).ok_or_else(|| format!("usage: {}", usage))
+}
+
+impl Command {
+    pub fn parse(line: &str) -> Result<Command, String> {
+        let mut words = line.split_whitespace();
+        let name = words.next().ok_or_else(|| "type help for the commands".to_string())?;
+        let argument = words.next();
+        let command = match name {
+            "honey" => Command::Honey(number(argument, "honey <amount>")?),
+            "lives" => Command::Lives(number(argument, "lives <amount>")?),
+            "spawn" => {
+                let sprite = argument.ok_or_else(|| "usage: spawn <sprite> [count]".to_string())?;
+                let count = match words.next() {
+                    Some(count) => number(Some(count), "spawn <sprite> [count]")?,
+                    None => 1,
+                };
+                Command::Spawn { sprite: sprite.to_string(), count }
+            }
+            "hardness" => Command::Hardness(number(argument, "hardness <level>")?),
+            "upgrade" if argument == Some("all") => Command::UpgradeAll,
+            "upgrade" => return Err("usage: upgrade all".to_string()),
+            "tick" => Command::Tick(number(argument, "tick <count>")?),
+            "seed" => Command::Seed,
+            "save" => Command::Save,
+            "load" => Command::Load,
+            "godmode" => Command::GodMode,
+            "clear" => Command::Clear,
+            "help" => Command::Help,
+            _ => return Err(format!("unknown command {}, type help for the commands", name)),
+        };
+        Ok(command)
+    }
+}
+
+pub struct Console {
+    pub input: String,
+    open: bool,
+    /// Set when the console opens and cleared at the next update, so the character typed
+    /// by the key that opened it doesn't end up in the input.
+    just_opened: bool,
+    log: VecDeque<String>,
+    /// The lines entered before, oldest first, to bring back with the arrow keys.
+    history: Vec<String>,
+    history_index: Option<usize>,
+}
+
+impl Default for Console {
+    fn default() -> Self {
+        Console::new()
+    }
+}
+
+impl Console {
+    pub fn new() -> Self {
+        Console { input: String::new(), open: false, just_opened: false, log: VecDeque::new(), history: Vec::new(), history_index: None }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn open(&mut self) {
+        self.open = true;
+        self.just_opened = true;
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+    }
+
+    /// Call once every update, after the input events of the frame.
+    pub fn update(&mut self) {
+        self.just_opened = false;
+    }
+
+    /// The output, oldest first.
+    pub fn log(&self) -> &VecDeque<String> {
+        &self.log
+    }
+
+    pub fn print(&mut self, line: String) {
+        if self.log.len() == LOG_SIZE {
+            self.log.pop_front();
+        }
+        self.log.push_back(line);
+    }
+
+    pub fn clear(&mut self) {
+        self.log.clear();
+    }
+
+    /// Types a character into the open console. Whatever the key that opened it typed is
+    /// left out.
+    pub fn type_char(&mut self, character: char) {
+        if self.open && !self.just_opened && !character.is_control() {
+            self.input.push(character);
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        self.input.pop();
+    }
+
+    /// Takes the line typed in, echoing it to the log.
+    pub fn submit(&mut self) -> Option<String> {
+        let line = std::mem::take(&mut self.input);
+        self.history_index = None;
+        if line.trim().is_empty() {
+            return None;
+        }
+        self.print(format!("> {}", line));
+        if self.history.last() != Some(&line) {
+            self.history.push(line.clone());
+        }
+        Some(line)
+    }
+
+    /// Brings back the line entered before the one shown.
+    pub fn history_back(&mut self) {
+        let index = match self.history_index {
+            Some(index) => index.saturating_sub(1),
+            None if !self.history.is_empty() => self.history.len() - 1,
+            None => return,
+        };
+        self.history_index = Some(index);
+        self.input = self.history[index].clone();
+    }
+
+    /// Goes the other way through the history, back to an empty line.
+    pub fn history_forward(&mut self) {
+        let index = match self.history_index {
+            Some(index) => index + 1,
+            None => return,
+        };
+        if index < self.history.len() {
+            self.history_index = Some(index);
+            self.input = self.history[index].clone();
+        } else {
+            self.history_index = None;
+            self.input.clear();
+        }
+    }
+}
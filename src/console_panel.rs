@@ -0,0 +1,42 @@
+use ggez::{Context, GameResult};
+use ggez::graphics::{Color, Rect};
+use crate::assets::Assets;
+use crate::console::Console;
+use crate::text::TextStyle;
+use crate::ui::Ui;
+
+/// The part of the screen the console drops down over.
+const HEIGHT_FRACTION: f32 = 0.4;
+const PADDING: f32 = 8.0;
+const LINE_SPACING: f32 = 2.0;
+
+/// Draws the console over the top of the screen, the input at the bottom with the output
+/// above it, newest last.
+pub struct ConsolePanel;
+
+impl ConsolePanel {
+    pub fn new() -> Self {
+        ConsolePanel
+    }
+
+    pub fn draw(&self, ctx: &mut Context, ui: &mut Ui, assets: &mut Assets, screen: Rect, console: &Console) -> GameResult {
+        let rect = Rect::new(0.0, 0.0, screen.w, (screen.h * HEIGHT_FRACTION).round());
+        ui.panel(ctx, rect)?;
+
+        let style = TextStyle::new(Color::WHITE).size(14.0);
+        let input_str = format!("> {}_", console.input);
+        let (_, line_height) = assets.text_dimensions(ctx, &input_str, &style);
+        let mut y = rect.h - PADDING - line_height;
+        ui.label(ctx, assets, &input_str, [PADDING, y], &style)?;
+
+        let log_style = TextStyle::new(Color::new(0.8, 0.8, 0.8, 1.0)).size(14.0);
+        for line in console.log().iter().rev() {
+            y -= line_height + LINE_SPACING;
+            if y < PADDING {
+                break;
+            }
+            ui.label(ctx, assets, line, [PADDING, y], &log_style)?;
+        }
+        Ok(())
+    }
+}
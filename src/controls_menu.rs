@@ -0,0 +1,132 @@
+use ggez::{Context, GameResult};
+use ggez::event::{KeyCode, KeyMods};
+use ggez::graphics::Color;
+use crate::input::{self, Action, Bindings};
+use crate::scene::{Scene, Shared, Transition};
+use crate::text::TextStyle;
+use crate::ui_layout::{self, Anchor};
+
+pub const NAME: &str = "controls";
+const LABEL_WIDTH: f32 = 160.0;
+const KEY_WIDTH: f32 = 150.0;
+const ROW_HEIGHT: f32 = 28.0;
+const SPACING: f32 = 8.0;
+const PADDING: f32 = 24.0;
+
+/// What was clicked in the last frame, carried out at the next update.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Clicked {
+    Rebind(Action),
+    Reset,
+    Back,
+}
+
+/// Lists the key of every action. Clicking one waits for the key to bind to it instead.
+pub struct ControlsMenu {
+    clicked: Option<Clicked>,
+    /// The action the next key press goes to.
+    waiting: Option<Action>,
+}
+
+impl ControlsMenu {
+    pub fn new() -> Self {
+        ControlsMenu { clicked: None, waiting: None }
+    }
+}
+
+impl Scene for ControlsMenu {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn update(&mut self, ctx: &mut Context, shared: &mut Shared) -> GameResult<Transition> {
+        let transition = match self.clicked.take() {
+            // clicking the action again stops waiting for its key
+            Some(Clicked::Rebind(action)) if self.waiting == Some(action) => {
+                self.waiting = None;
+                Transition::None
+            }
+            Some(Clicked::Rebind(action)) => {
+                self.waiting = Some(action);
+                Transition::None
+            }
+            Some(Clicked::Reset) => {
+                self.waiting = None;
+                shared.set_bindings(ctx, Bindings::default());
+                Transition::None
+            }
+            Some(Clicked::Back) => Transition::Pop,
+            None => Transition::None,
+        };
+        Ok(transition)
+    }
+
+    fn draw(&mut self, ctx: &mut Context, shared: &mut Shared) -> GameResult {
+        let ui = &mut shared.ui;
+        let assets = &mut shared.assets;
+        ui.shade(ctx, shared.screen)?;
+
+        let title = "Controls";
+        let title_style = TextStyle::new(Color::WHITE).size(24.0).outline(Color::BLACK);
+        let title_size = assets.text_dimensions(ctx, title, &title_style);
+        let row_count = Action::ALL.len() + 2;
+        let row_width = LABEL_WIDTH + KEY_WIDTH;
+        let height = title_size.1 + SPACING + row_count as f32 * (ROW_HEIGHT + SPACING);
+        let panel = Anchor::Center.place(shared.screen, (row_width + PADDING * 2.0, height + PADDING * 2.0), 0.0);
+        ui.panel(ctx, panel)?;
+        ui.label(ctx, assets, title, [panel.x + (panel.w - title_size.0) / 2.0, panel.y + PADDING], &title_style)?;
+
+        let sizes = vec![(row_width, ROW_HEIGHT); row_count];
+        let rows = ui_layout::column([panel.x + PADDING, panel.y + PADDING + title_size.1 + SPACING * 2.0], &sizes, SPACING);
+        let label_style = TextStyle::new(Color::WHITE).outline(Color::BLACK);
+        for (action, row) in Action::ALL.iter().zip(rows.iter()) {
+            let (_, label_height) = assets.text_dimensions(ctx, action.name(), &label_style);
+            ui.label(ctx, assets, action.name(), [row.x, row.y + (ROW_HEIGHT - label_height) / 2.0], &label_style)?;
+
+            let key_str = if self.waiting == Some(*action) {
+                "Press a key...".to_string()
+            } else {
+                let key = shared.bindings.key_for(*action);
+                input::key_name(key).map_or_else(|| format!("{:?}", key), |name| name.to_string())
+            };
+            let key_rect = ggez::graphics::Rect::new(row.x + LABEL_WIDTH, row.y, KEY_WIDTH, ROW_HEIGHT);
+            if ui.button(ctx, assets, key_rect, &key_str, true)? {
+                self.clicked = Some(Clicked::Rebind(*action));
+            }
+        }
+
+        let buttons = &rows[Action::ALL.len()..];
+        if ui.button(ctx, assets, buttons[0], "Reset to defaults", true)? {
+            self.clicked = Some(Clicked::Reset);
+        }
+        if ui.button(ctx, assets, buttons[1], "Back", true)? {
+            self.clicked = Some(Clicked::Back);
+        }
+        Ok(())
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+
+    fn key_down(&mut self, ctx: &mut Context, shared: &mut Shared, keycode: KeyCode, _keymods: KeyMods, repeat: bool) -> Transition {
+        if repeat {
+            return Transition::None;
+        }
+        let action = match self.waiting {
+            Some(action) => action,
+            None if keycode == KeyCode::Escape => return Transition::Pop,
+            None => return Transition::None,
+        };
+        if input::key_name(keycode).is_none() {
+            println!("{:?} can't be bound", keycode);
+            return Transition::None;
+        }
+
+        let mut bindings = shared.bindings.clone();
+        bindings.bind(action, keycode);
+        shared.set_bindings(ctx, bindings);
+        self.waiting = None;
+        Transition::None
+    }
+}
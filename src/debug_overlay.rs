@@ -0,0 +1,130 @@
+use ggez::{graphics, Context, GameResult};
+use ggez::graphics::{Color, DrawMode, DrawParam, Rect};
+use std::collections::VecDeque;
+use std::time::Duration;
+use crate::assets::Assets;
+use crate::camera::Camera;
+use crate::config::GRID_CELL_SIZE;
+use crate::enemy::Enemy;
+use crate::info_panel::InfoPanel;
+use crate::level::Level;
+use crate::movement_helpers::{GridPosition, RectangleBorder};
+use crate::nexus::Nexus;
+use crate::tower::Tower;
+use crate::ui::Ui;
+
+const GRID_COLOR: Color = Color::new(1.0, 1.0, 1.0, 0.12);
+const ROUTE_COLOR: Color = Color::new(0.3, 1.0, 0.4, 0.7);
+const BORDER_COLOR: Color = Color::new(1.0, 0.3, 0.9, 0.9);
+const RANGE_COLOR: Color = Color::new(1.0, 0.85, 0.3, 0.5);
+/// The points entities are drawn from and tested against.
+const ORIGIN_COLOR: Color = Color::new(0.3, 0.8, 1.0, 1.0);
+const ORIGIN_SIZE: f32 = 3.0;
+/// How many of the last ticks the update timing is taken over.
+const TICK_SAMPLES: usize = 128;
+
+/// What the game is up to, for the debug overlay's panel.
+pub struct DebugStats {
+    pub ticks: i32,
+    pub hardness: i32,
+    pub spawned_enemies: i32,
+    pub enemy_count: Option<i32>,
+    pub cursor: GridPosition,
+}
+
+/// Shows what's normally hidden, to check the layout of levels and the game running:
+/// the grid, the route every enemy has left, the borders entities are hit tested with,
+/// tower ranges and how long frames and updates take.
+pub struct DebugOverlay {
+    tick_times: VecDeque<Duration>,
+}
+
+fn point(position: GridPosition) -> [f32; 2] {
+    let (x, y): (f32, f32) = position.into();
+    [x * GRID_CELL_SIZE.0 as f32, y * GRID_CELL_SIZE.1 as f32]
+}
+
+fn border_rect(borders: &RectangleBorder) -> Rect {
+    let top_left = point(borders.top_left_corner());
+    let bot_right = point(borders.bot_right_corner());
+    Rect::new(top_left[0], top_left[1], bot_right[0] - top_left[0], bot_right[1] - top_left[1])
+}
+
+impl DebugOverlay {
+    pub fn new() -> Self {
+        DebugOverlay { tick_times: VecDeque::with_capacity(TICK_SAMPLES) }
+    }
+
+    /// Call with how long every update tick took.
+    pub fn record_tick(&mut self, tick_time: Duration) {
+        if self.tick_times.len() == TICK_SAMPLES {
+            self.tick_times.pop_front();
+        }
+        self.tick_times.push_back(tick_time);
+    }
+
+    /// Draws over the map, in world coordinates.
+    pub fn draw_world(&self, ctx: &mut Context, camera: &Camera, level: &Level, enemies: &VecDeque<Enemy>, towers: &[Tower], nexus: &Nexus) -> GameResult {
+        // lines stay a pixel wide at any zoom
+        let width = 1.0 / camera.zoom();
+        let mut mesh = graphics::MeshBuilder::new();
+
+        let cell = (GRID_CELL_SIZE.0 as f32, GRID_CELL_SIZE.1 as f32);
+        let (first, last) = camera.visible_cells(level.grid_size);
+        for x in first.0..=last.0 {
+            mesh.line(&[[x as f32 * cell.0, first.1 as f32 * cell.1], [x as f32 * cell.0, last.1 as f32 * cell.1]], width, GRID_COLOR)?;
+        }
+        for y in first.1..=last.1 {
+            mesh.line(&[[first.0 as f32 * cell.0, y as f32 * cell.1], [last.0 as f32 * cell.0, y as f32 * cell.1]], width, GRID_COLOR)?;
+        }
+
+        for enemy in enemies.iter().filter(|enemy| enemy.is_alive()) {
+            let route: Vec<[f32; 2]> = enemy.remaining_route(level).into_iter().map(point).collect();
+            if route.len() >= 2 {
+                mesh.line(&route, width * 2.0, ROUTE_COLOR)?;
+            }
+            mesh.circle(DrawMode::fill(), point(enemy.get_position()), ORIGIN_SIZE * width, 0.5, ORIGIN_COLOR)?;
+        }
+
+        for tower in towers.iter() {
+            let center = point(tower.get_position());
+            mesh.circle(DrawMode::stroke(width), center, tower.range() * cell.0, 0.5, RANGE_COLOR)?;
+            mesh.rectangle(DrawMode::stroke(width), border_rect(&tower.borders()), BORDER_COLOR)?;
+            mesh.circle(DrawMode::fill(), point(tower.render_position()), ORIGIN_SIZE * width, 0.5, ORIGIN_COLOR)?;
+        }
+
+        mesh.rectangle(DrawMode::stroke(width), border_rect(&nexus.borders), BORDER_COLOR)?;
+        mesh.circle(DrawMode::fill(), point(nexus.render_position()), ORIGIN_SIZE * width, 0.5, ORIGIN_COLOR)?;
+
+        let mesh = mesh.build(ctx)?;
+        graphics::draw(ctx, &mesh, DrawParam::new())
+    }
+
+    /// Draws the numbers in the top right corner of `screen`, in screen coordinates.
+    pub fn draw_stats(&self, ctx: &mut Context, ui: &mut Ui, assets: &mut Assets, screen: Rect, stats: &DebugStats, enemies: &VecDeque<Enemy>) -> GameResult {
+        let frame_time = ggez::timer::average_delta(ctx);
+        let (tick_total, tick_max) = self.tick_times.iter()
+            .fold((Duration::from_secs(0), Duration::from_secs(0)), |(total, max), time| (total + *time, max.max(*time)));
+        let tick_average = tick_total / self.tick_times.len().max(1) as u32;
+        let millis = |duration: Duration| duration.as_secs_f64() * 1000.0;
+
+        let alive = enemies.iter().filter(|enemy| enemy.is_alive()).count();
+        let spawned = match stats.enemy_count {
+            Some(enemy_count) => format!("{} of {}", stats.spawned_enemies, enemy_count),
+            None => stats.spawned_enemies.to_string(),
+        };
+        let (cursor_x, cursor_y): (f32, f32) = stats.cursor.into();
+        let lines = vec![
+            format!("FPS: {:.0} ({:.2} ms per frame)", ggez::timer::fps(ctx), millis(frame_time)),
+            format!("Update: {:.3} ms average, {:.3} ms max", millis(tick_average), millis(tick_max)),
+            format!("Tick: {}", stats.ticks),
+            format!("Hardness: {}", stats.hardness),
+            format!("Enemies: {} alive, {} spawned", alive, spawned),
+            format!("Cursor: ({:.1}, {:.1})", cursor_x, cursor_y),
+        ];
+        let panel = InfoPanel::new("Debug".to_string(), lines);
+        let (width, _) = panel.size(ctx, assets);
+        panel.draw(ctx, ui, assets, [screen.w - width - 8.0, 8.0])?;
+        Ok(())
+    }
+}
@@ -0,0 +1,269 @@
+use ggez::{graphics, Context, GameResult};
+use ggez::event::KeyCode;
+use ggez::graphics::Color;
+use ggez::input::mouse::MouseButton;
+use crate::assets::Assets;
+use crate::autotile;
+use crate::config::GRID_CELL_SIZE;
+use crate::level::{Level, Spawn};
+use crate::manifest::AssetCategory;
+use crate::movement_helpers::{GridPosition, RectangleBorder};
+use crate::text::TextStyle;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum EditorTool {
+    Tile,
+    Decor,
+    Spawn,
+    Path,
+    Nexus,
+    TowerSlot,
+}
+
+impl EditorTool {
+    fn name(&self) -> &'static str {
+        match self {
+            EditorTool::Tile => "Tile",
+            EditorTool::Decor => "Decor",
+            EditorTool::Spawn => "Spawn",
+            EditorTool::Path => "Path",
+            EditorTool::Nexus => "Nexus",
+            EditorTool::TowerSlot => "Tower slot",
+        }
+    }
+}
+
+/// Edits the loaded level in place. Left click paints or places with the current tool,
+/// right click erases. Keys: 1-6 pick the tool, Q/E cycle the palette.
+pub struct Editor {
+    tool: EditorTool,
+    tile_palette: Vec<String>,
+    decor_palette: Vec<String>,
+    tile_index: usize,
+    decor_index: usize,
+    selected_spawn: usize,
+    status_render_position: GridPosition,
+}
+
+impl Editor {
+    pub fn new(assets: &Assets) -> Self {
+        Editor {
+            tool: EditorTool::Tile,
+            tile_palette: assets.get_ids(AssetCategory::Tiles),
+            decor_palette: assets.get_ids(AssetCategory::Decor),
+            tile_index: 0,
+            decor_index: 0,
+            selected_spawn: 0,
+            status_render_position: (1.0, 0.5).into(),
+        }
+    }
+
+    pub fn key_down(&mut self, keycode: KeyCode) {
+        match keycode {
+            KeyCode::Key1 => self.tool = EditorTool::Tile,
+            KeyCode::Key2 => self.tool = EditorTool::Decor,
+            KeyCode::Key3 => self.tool = EditorTool::Spawn,
+            KeyCode::Key4 => self.tool = EditorTool::Path,
+            KeyCode::Key5 => self.tool = EditorTool::Nexus,
+            KeyCode::Key6 => self.tool = EditorTool::TowerSlot,
+            KeyCode::Q => self.cycle_palette(-1),
+            KeyCode::E => self.cycle_palette(1),
+            _ => {}
+        }
+    }
+
+    pub fn cycle_palette(&mut self, step: i32) {
+        let (index, len) = match self.tool {
+            EditorTool::Tile => (&mut self.tile_index, self.tile_palette.len()),
+            EditorTool::Decor => (&mut self.decor_index, self.decor_palette.len()),
+            _ => return,
+        };
+        if len > 0 {
+            *index = (*index as i32 + step).rem_euclid(len as i32) as usize;
+        }
+    }
+
+    /// Applies the current tool at the clicked cell.
+    pub fn click(&mut self, level: &mut Level, cell: (i16, i16), button: MouseButton) {
+        let erase = button == MouseButton::Right;
+        let position: GridPosition = cell.into();
+
+        match self.tool {
+            EditorTool::Tile | EditorTool::Decor => self.paint(level, cell, erase),
+            EditorTool::Spawn => {
+                let existing = level.spawns.iter().position(|spawn| spawn.position == position);
+                match (existing, erase) {
+                    (Some(index), false) => self.selected_spawn = index,
+                    // a level needs at least one spawn
+                    (Some(index), true) if level.spawns.len() > 1 => {
+                        level.spawns.remove(index);
+                        self.selected_spawn = 0;
+                    }
+                    (None, false) => {
+                        level.spawns.push(Spawn { position, path: Vec::new() });
+                        self.selected_spawn = level.spawns.len() - 1;
+                    }
+                    _ => {}
+                }
+            }
+            EditorTool::Path => {
+                let spawn = match level.spawns.get_mut(self.selected_spawn) {
+                    Some(spawn) => spawn,
+                    None => return,
+                };
+                if erase {
+                    spawn.path.pop();
+                    return;
+                }
+
+                // enemies only walk in straight lines, so snap the node in line with the previous one
+                let previous: (f32, f32) = spawn.path.last().copied().unwrap_or(spawn.position).into();
+                let (x, y): (f32, f32) = position.into();
+                let snapped = if (x - previous.0).abs() >= (y - previous.1).abs() {
+                    GridPosition::new(x, previous.1)
+                } else {
+                    GridPosition::new(previous.0, y)
+                };
+                spawn.path.push(snapped);
+            }
+            EditorTool::Nexus => {
+                if erase {
+                    return;
+                }
+                let (left, top): (f32, f32) = level.nexus.borders.top_left_corner().into();
+                let offset = (cell.0 as f32 - left, cell.1 as f32 - top);
+                let shift = |pos: GridPosition| {
+                    let (x, y): (f32, f32) = pos.into();
+                    GridPosition::new(x + offset.0, y + offset.1)
+                };
+                level.nexus.position = shift(level.nexus.position);
+                level.nexus.render_position = shift(level.nexus.render_position);
+                level.nexus.borders = RectangleBorder::new(
+                    shift(level.nexus.borders.top_left_corner()),
+                    shift(level.nexus.borders.bot_right_corner()),
+                );
+            }
+            EditorTool::TowerSlot => {
+                let existing = level.tower_slots.iter().position(|slot| tower_pad(*slot).is_it_in(position));
+                match (existing, erase) {
+                    (Some(index), true) => {
+                        level.tower_slots.remove(index);
+                    }
+                    (None, false) if !level.tower_terrain(position).buildable => {
+                        println!("can't build a tower at ({}, {})", cell.0, cell.1);
+                    }
+                    (None, false) => {
+                        level.tower_slots.push(position);
+                        for (dx, dy, sprite) in autotile::TOWER_PAD.iter() {
+                            if let Some(tile) = level.tile_mut(cell.0 + dx, cell.1 + dy) {
+                                tile.sprite = sprite.to_string();
+                                tile.decor = None;
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// Painting tools keep painting while the mouse is dragged.
+    pub fn drag(&mut self, level: &mut Level, cell: (i16, i16), button: MouseButton) {
+        if self.tool == EditorTool::Tile || self.tool == EditorTool::Decor {
+            self.paint(level, cell, button == MouseButton::Right);
+        }
+    }
+
+    fn paint(&self, level: &mut Level, cell: (i16, i16), erase: bool) {
+        let tile = match level.tile_mut(cell.0, cell.1) {
+            Some(tile) => tile,
+            None => return,
+        };
+
+        match self.tool {
+            EditorTool::Tile if !erase => {
+                if let Some(sprite) = self.tile_palette.get(self.tile_index) {
+                    tile.sprite = sprite.clone();
+                }
+            }
+            EditorTool::Tile => tile.sprite = autotile::GRASS.to_string(),
+            EditorTool::Decor if !erase => tile.decor = self.decor_palette.get(self.decor_index).cloned(),
+            EditorTool::Decor => tile.decor = None,
+            _ => {}
+        }
+    }
+
+    /// Draws the routes, nexus and tower slots over the map, in world coordinates.
+    pub fn draw(&self, ctx: &mut Context, level: &Level) -> GameResult {
+        // a rectangle covering `size` cells from the top left corner `pos`
+        let marker = |pos: GridPosition, size: (f32, f32)| {
+            let top_left: ggez::mint::Point2<f32> = pos.into();
+            graphics::Rect::new(top_left.x, top_left.y, size.0 * GRID_CELL_SIZE.0 as f32, size.1 * GRID_CELL_SIZE.1 as f32)
+        };
+
+        for (index, spawn) in level.spawns.iter().enumerate() {
+            let color = if index == self.selected_spawn { Color::from_rgb(80, 255, 80) } else { Color::from_rgb(30, 140, 30) };
+
+            // enemies are two cells wide, so draw the route through the middle of them
+            let mut points: Vec<ggez::mint::Point2<f32>> = Vec::new();
+            for checkpoint in std::iter::once(&spawn.position).chain(spawn.path.iter()) {
+                let (x, y): (f32, f32) = (*checkpoint).into();
+                points.push(GridPosition::new(x + 1.0, y + 1.0).into());
+            }
+            if points.len() >= 2 {
+                let route = graphics::Mesh::new_line(ctx, &points, 2.0, color)?;
+                graphics::draw(ctx, &route, graphics::DrawParam::new())?;
+            }
+
+            let spawn_mesh = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), marker(spawn.position, (2.0, 2.0)), color)?;
+            graphics::draw(ctx, &spawn_mesh, graphics::DrawParam::new())?;
+        }
+
+        {
+            let top_left = level.nexus.borders.top_left_corner();
+            let (left, top): (f32, f32) = top_left.into();
+            let (right, bottom): (f32, f32) = level.nexus.borders.bot_right_corner().into();
+            let nexus_rect = marker(top_left, (right - left + 1.0, bottom - top + 1.0));
+            let nexus_mesh = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(2.0), nexus_rect, Color::from_rgb(255, 80, 255))?;
+            graphics::draw(ctx, &nexus_mesh, graphics::DrawParam::new())?;
+        }
+
+        for slot in level.tower_slots.iter() {
+            let pad = tower_pad(*slot).top_left_corner();
+            let slot_mesh = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(2.0), marker(pad, (2.0, 2.0)), Color::from_rgb(80, 160, 255))?;
+            graphics::draw(ctx, &slot_mesh, graphics::DrawParam::new())?;
+        }
+
+        Ok(())
+    }
+
+    /// Draws the current tool and palette entry, in screen coordinates.
+    pub fn draw_status(&self, ctx: &mut Context, assets: &mut Assets) -> GameResult {
+        let selection = match self.tool {
+            EditorTool::Tile => self.tile_palette.get(self.tile_index).map(|sprite| (sprite.clone(), false)),
+            EditorTool::Decor => self.decor_palette.get(self.decor_index).map(|sprite| (sprite.clone(), true)),
+            _ => None,
+        };
+        let status_str = match &selection {
+            Some((sprite, _)) => format!("EDITOR  [{}] {}    1-6 tool, Q/E palette, Ctrl+S save, F2 play", self.tool.name(), sprite),
+            None => format!("EDITOR  [{}]    1-6 tool, Q/E palette, Ctrl+S save, F2 play", self.tool.name()),
+        };
+        let status_dest: ggez::mint::Point2<f32> = self.status_render_position.into();
+        assets.draw_text(ctx, &status_str, status_dest, &TextStyle::new(Color::WHITE).shadow(Color::BLACK))?;
+
+        if let Some((sprite, decor)) = selection {
+            let preview_dest: ggez::mint::Point2<f32> = GridPosition::new(0.0, 0.0).into();
+            let category = if decor { AssetCategory::Decor } else { AssetCategory::Tiles };
+            let preview = assets.get_image(category, &sprite);
+            graphics::draw(ctx, preview, graphics::DrawParam::new().dest(preview_dest))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The 2x2 cells a tower centered at `slot` stands on, see `autotile::TOWER_PAD`.
+fn tower_pad(slot: GridPosition) -> RectangleBorder {
+    let (x, y): (f32, f32) = slot.into();
+    RectangleBorder::new((x - 1.0, y - 1.0).into(), (x, y).into())
+}
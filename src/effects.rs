@@ -0,0 +1,100 @@
+use ggez::{graphics, Context, GameResult};
+use ggez::graphics::{Color, DrawMode, DrawParam, Rect};
+use std::time::Duration;
+use crate::assets::Assets;
+use crate::camera::Camera;
+use crate::config::GRID_CELL_SIZE;
+use crate::game_event::GameEvent;
+use crate::particles::Particles;
+use crate::text::TextStyle;
+
+const DAMAGE_COLOR: Color = Color::new(1.0, 1.0, 1.0, 1.0);
+const HONEY_COLOR: Color = Color::new(1.0, 0.8, 0.2, 1.0);
+const FLASH_COLOR: Color = Color::new(0.8, 0.0, 0.0, 0.35);
+const SPLAT_PARTICLES: usize = 14;
+/// In screen pixels and seconds.
+const NEXUS_SHAKE: (f32, f32) = (6.0, 0.35);
+const NEXUS_FLASH: f32 = 0.3;
+
+/// The feedback for what happens in a level: damage numbers, honey earned, slime splats
+/// and the screen shaking and flashing red when the nexus is hit.
+pub struct Effects {
+    particles: Particles,
+}
+
+/// The world pixel of a position in cells.
+fn to_world(position: (f32, f32)) -> (f32, f32) {
+    (position.0 * GRID_CELL_SIZE.0 as f32, position.1 * GRID_CELL_SIZE.1 as f32)
+}
+
+fn faded(color: Color, fade: f32) -> Color {
+    Color::new(color.r, color.g, color.b, color.a * fade)
+}
+
+impl Effects {
+    pub fn new() -> Self {
+        Effects { particles: Particles::new() }
+    }
+
+    pub fn handle(&mut self, event: GameEvent) {
+        match event {
+            GameEvent::Hit { position, damage } => self.particles.float_number(damage, to_world(position), DAMAGE_COLOR),
+            GameEvent::Kill { position, damage, honey, color } => {
+                let position = to_world(position);
+                self.particles.float_number(damage, position, DAMAGE_COLOR);
+                self.particles.float_text(format!("+{} honey", honey), (position.0, position.1 - 12.0), HONEY_COLOR);
+                self.particles.burst(&mut rand::thread_rng(), position, color, SPLAT_PARTICLES);
+            }
+            GameEvent::NexusHit => {
+                self.particles.shake(NEXUS_SHAKE.0, NEXUS_SHAKE.1);
+                self.particles.flash(NEXUS_FLASH);
+            }
+            _ => {}
+        }
+    }
+
+    pub fn update(&mut self, delta: Duration) {
+        self.particles.update(delta.as_secs_f32());
+    }
+
+    pub fn clear(&mut self) {
+        self.particles.clear();
+    }
+
+    /// How far to move the view this frame, in screen pixels.
+    pub fn shake_offset(&self) -> (f32, f32) {
+        self.particles.shake_offset()
+    }
+
+    /// Draws the particles in world coordinates.
+    pub fn draw_particles(&self, ctx: &mut Context) -> GameResult {
+        if self.particles.particles().is_empty() {
+            return Ok(());
+        }
+        let mut mesh = graphics::MeshBuilder::new();
+        for particle in self.particles.particles() {
+            mesh.circle(DrawMode::fill(), [particle.position.0, particle.position.1], particle.size, 0.5, faded(particle.color, particle.fade()))?;
+        }
+        let mesh = mesh.build(ctx)?;
+        graphics::draw(ctx, &mesh, DrawParam::new())
+    }
+
+    /// Draws the floating text and the flash in screen coordinates, so the text stays
+    /// readable at any zoom.
+    pub fn draw_overlay(&self, ctx: &mut Context, assets: &mut Assets, camera: &Camera, screen: Rect) -> GameResult {
+        for text in self.particles.texts() {
+            let fade = text.fade();
+            let style = TextStyle::new(faded(text.color, fade)).size(14.0).outline(faded(Color::BLACK, fade));
+            let (width, height) = assets.text_dimensions(ctx, &text.text, &style);
+            let (x, y) = camera.world_to_screen(text.position.0, text.position.1);
+            assets.draw_text(ctx, &text.text, [x - width / 2.0, y - height / 2.0].into(), &style)?;
+        }
+
+        let flash = self.particles.flash_alpha();
+        if flash > 0.0 {
+            let mesh = graphics::Mesh::new_rectangle(ctx, DrawMode::fill(), screen, faded(FLASH_COLOR, flash))?;
+            graphics::draw(ctx, &mesh, DrawParam::new())?;
+        }
+        Ok(())
+    }
+}
@@ -1,98 +1,263 @@
 use ggez::{graphics, Context, GameResult};
 use crate::movement_helpers::{GridPosition, Direction};
 use crate::assets::Assets;
+use crate::manifest::AssetCategory;
 use ggez::graphics::Color;
+use crate::level::Level;
+use crate::config::MILLIS_PER_UPDATE;
+use crate::sprite_sheet::{Action, Animator};
+use crate::health::{self, Health};
+use crate::config::{GRID_CELL_SIZE, UPDATES_PER_SECOND};
+use crate::info_panel::InfoPanel;
+use crate::save_game::SavedEnemy;
 use rand::Rng;
 
-const STARTING_POINT: (i16, i16) = (0, 4);
-const CHECKPOINTS: [(i16, i16); 7] = [
-    (55, 4),
-    (55, 27),
-    (7, 27),
-    (7, 10),
-    (47, 10),
-    (47, 18),
-    (14, 18),
-];
+/// The health bar spans the enemy's 2 cells, this many pixels above its sprite.
+const HEALTH_BAR_RISE: f32 = 18.0;
+const HEALTH_BAR_HEIGHT: f32 = 4.0;
+const SHIELD_COLOR: Color = Color::new(0.35, 0.7, 1.0, 1.0);
+const ARMOR_COLOR: Color = Color::new(0.75, 0.78, 0.82, 1.0);
+/// A kill is worth the enemy's hardness times a number from this range in honey.
+const REWARD_NOISE: std::ops::Range<i32> = 70..130;
+
+/// The sprites enemies are drawn with, from the enemies of the asset manifest.
+pub const ENEMY_SPRITES: [&str; 3] = ["slime_blue", "slime_green", "slime_orange"];
 
 pub struct Enemy {
+    sprite: String,
+    animator: Animator,
+    spawn_index: usize,
     checkpoint_index: usize,
     position: GridPosition,
     speed: f32,
-    health: i32,
+    health: Health,
     hardness: i32,
 }
 
 impl Enemy {
-    pub fn new(hardness: i32, health: i32) -> Self {
+    pub fn new(sprite: &str, hardness: i32, health: Health, spawn_index: usize, level: &Level) -> Self {
         Enemy {
+            sprite: sprite.to_string(),
+            animator: Animator::new(Action::Walk),
+            spawn_index,
             checkpoint_index: 0,
-            position: STARTING_POINT.into(),
+            position: level.spawns[spawn_index].position,
             health,
             speed: 1.0,
             hardness,
         }
     }
 
-    fn get_direction(&self) -> Option<Direction> {
+    /// An enemy as it was saved, or none if its spawn isn't in the level anymore.
+    pub fn from_saved(saved: &SavedEnemy, level: &Level) -> Option<Self> {
+        if saved.spawn_index >= level.spawns.len() {
+            return None;
+        }
+        let mut enemy = Enemy::new(&saved.sprite, saved.hardness, saved.health, saved.spawn_index, level);
+        enemy.checkpoint_index = saved.checkpoint_index.min(level.spawns[saved.spawn_index].path.len());
+        enemy.position = saved.position.into();
+        Some(enemy)
+    }
+
+    pub fn to_saved(&self) -> SavedEnemy {
+        SavedEnemy {
+            sprite: self.sprite.clone(),
+            hardness: self.hardness,
+            health: self.health,
+            spawn_index: self.spawn_index,
+            checkpoint_index: self.checkpoint_index,
+            position: self.position.into(),
+        }
+    }
+
+    fn get_direction(&self, level: &Level) -> Option<Direction> {
         let current_position: (f32, f32) = self.position.into();
-        let checkpoint: (i16, i16) = CHECKPOINTS[self.checkpoint_index];
+        let checkpoint: (i16, i16) = match level.spawns[self.spawn_index].path.get(self.checkpoint_index) {
+            Some(checkpoint) => (*checkpoint).into(),
+            // the route is over - stay put until the nexus picks the enemy up
+            None => return None,
+        };
         let div: (f32, f32) = (current_position.0 - checkpoint.0 as f32, current_position.1 - checkpoint.1 as f32);
 
         return if div.0 < 0.0 { Some(Direction::Right) } else if div.0 > 0.0 { Some(Direction::Left) } else if div.1 < 0.0 { Some(Direction::Down) } else if div.1 > 0.0 { Some(Direction::Up) } else { None };
     }
 
-    fn step(&mut self) {
-        let new_direction: Option<Direction> = self.get_direction();
+    fn step(&mut self, level: &Level) {
+        let new_direction: Option<Direction> = self.get_direction(level);
 
         if new_direction.is_none() {
-            self.checkpoint_index += 1;
+            if self.checkpoint_index < level.spawns[self.spawn_index].path.len() {
+                self.checkpoint_index += 1;
+            } else {
+                self.animator.play(Action::Idle);
+            }
+            return;
+        }
+        self.animator.face(new_direction.unwrap());
+        self.animator.play(Action::Walk);
+
+        // slowed down enemies move in fractions of a cell, stop them right at the checkpoint
+        let checkpoint: (f32, f32) = level.spawns[self.spawn_index].path[self.checkpoint_index].into();
+        let current_position: (f32, f32) = self.position.into();
+        let distance = (checkpoint.0 - current_position.0).abs() + (checkpoint.1 - current_position.1).abs();
+        let speed = self.speed * self.terrain_speed(level);
+        if speed >= distance {
+            self.position = checkpoint.into();
             return;
         }
 
-        let new_position: GridPosition = GridPosition::new_from_move(self.position, new_direction.unwrap(), self.speed);
+        let new_position: GridPosition = GridPosition::new_from_move(self.position, new_direction.unwrap(), speed);
         self.position = new_position;
     }
 
-    pub fn update(&mut self) {
-        self.step()
+    /// The slowest terrain under the 2x2 cells the enemy covers.
+    fn terrain_speed(&self, level: &Level) -> f32 {
+        let (x, y): (f32, f32) = self.position.into();
+        let (x, y) = (x.floor() as i16, y.floor() as i16);
+        [(x, y), (x + 1, y), (x, y + 1), (x + 1, y + 1)].iter()
+            .map(|(cx, cy)| level.terrain_at(*cx, *cy).speed)
+            .fold(f32::INFINITY, f32::min)
+    }
+
+    pub fn update(&mut self, level: &Level, assets: &Assets) {
+        if self.is_alive() {
+            self.step(level);
+        }
+        self.animator.update(assets.get_sheet(AssetCategory::Enemies, &self.sprite), MILLIS_PER_UPDATE);
+    }
+
+    /// Deals up to `damage` and returns how much of it was used up, see `Health::take_damage`.
+    pub fn take_damage(&mut self, damage: i32) -> i32 {
+        let used = self.health.take_damage(damage);
+        self.animator.play(if self.is_alive() { Action::Hit } else { Action::Death });
+        used
+    }
+
+    /// Whether the enemy died and its death animation is over, so it can be removed.
+    pub fn is_gone(&self, assets: &Assets) -> bool {
+        !self.is_alive() && self.animator.is_finished(assets.get_sheet(AssetCategory::Enemies, &self.sprite))
+    }
+
+    /// Where the enemy is and the checkpoints it has left to walk to.
+    pub fn remaining_route(&self, level: &Level) -> Vec<GridPosition> {
+        let path = &level.spawns[self.spawn_index].path;
+        let mut route = vec![self.position];
+        route.extend(path.iter().skip(self.checkpoint_index).copied());
+        route
+    }
+
+    /// The middle of the 2x2 cells the enemy covers, in cells.
+    pub fn center(&self) -> (f32, f32) {
+        let (x, y): (f32, f32) = self.position.into();
+        (x + 1.0, y + 1.0)
     }
 
-    pub fn reduce_health(&mut self, reduce_by: i32) {
-        self.health -= reduce_by;
+    /// The color of the slime the enemy splats into when it dies.
+    pub fn splat_color(&self) -> Color {
+        match self.sprite.as_str() {
+            "slime_blue" => Color::from_rgb(80, 150, 240),
+            "slime_green" => Color::from_rgb(110, 200, 80),
+            "slime_orange" => Color::from_rgb(240, 150, 50),
+            _ => Color::from_rgb(200, 200, 200),
+        }
     }
 
-    pub fn get_health(&self) -> i32 {
-        self.health
+    pub fn get_health(&self) -> &Health {
+        &self.health
     }
 
     pub fn get_honey_reward(&self) -> i32 {
         let mut rng = rand::thread_rng();
-        let reward_noise: i32 = rng.gen_range(70..130);
+        let reward_noise: i32 = rng.gen_range(REWARD_NOISE);
         self.hardness * reward_noise
     }
 
+    /// The least and the most honey `get_honey_reward` can give.
+    fn honey_reward_range(&self) -> (i32, i32) {
+        (self.hardness * REWARD_NOISE.start, self.hardness * (REWARD_NOISE.end - 1))
+    }
+
+    pub fn info(&self, level: &Level) -> InfoPanel {
+        let mut lines = vec![format!("Health: {} / {}", self.health.current, self.health.max)];
+        if self.health.max_shield > 0 {
+            lines.push(format!("Shield: {} / {}", self.health.shield, self.health.max_shield));
+        }
+        if self.health.armor > 0 {
+            lines.push(format!("Armor: blocks {}%", self.health.armor));
+        }
+        let speed = self.speed * self.terrain_speed(level) * UPDATES_PER_SECOND;
+        lines.push(format!("Speed: {:.0} cells/s", speed));
+        let (min_reward, max_reward) = self.honey_reward_range();
+        lines.push(format!("Bounty: {}-{} honey", min_reward, max_reward));
+        InfoPanel::new(enemy_kind(&self.sprite), lines)
+    }
+
     pub fn is_alive(&self) -> bool {
-        return self.health > 0;
+        return self.health.is_alive();
     }
 
     pub fn get_position(&self) -> GridPosition {
         self.position
     }
 
-    pub fn draw(&self, ctx: &mut Context, assets: &mut Assets) -> GameResult {
-        let current_position: (f32, f32) = self.position.into();
-        let enemy_sprite = assets.get_enemy_image("default".to_string());
-        let enemy_sprite_dest: ggez::mint::Point2<f32> = self.position.into();
-        let enemy_draw_params = graphics::DrawParam::new().dest(enemy_sprite_dest);
-
-        let health_position: GridPosition = (current_position.0 - 1 as f32, current_position.1 + 3 as f32).into();
-        let health_str = format!("Health: {}", self.health);
-        let health_display = graphics::Text::new((health_str));
-        let health_dest: ggez::mint::Point2<f32> = health_position.into();
-
-        graphics::draw(ctx, enemy_sprite, enemy_draw_params);
-        graphics::draw(ctx, &health_display, (health_dest, 0.0, Color::WHITE))?;
-        Ok(())
+    /// Whether `cursor` is over the enemy's 2x2 cells.
+    pub fn is_under(&self, cursor: GridPosition) -> bool {
+        let (x, y): (f32, f32) = self.position.into();
+        let (cursor_x, cursor_y): (f32, f32) = cursor.into();
+        cursor_x >= x && cursor_x < x + 2.0 && cursor_y >= y && cursor_y < y + 2.0
+    }
+
+    pub fn draw(&self, ctx: &mut Context, assets: &mut Assets, show_health_bar: bool) -> GameResult {
+        assets.draw_sprite(ctx, AssetCategory::Enemies, &self.sprite, &self.animator, self.position)?;
+        if !self.is_alive() {
+            return Ok(());
+        }
+
+        let position: ggez::mint::Point2<f32> = self.position.into();
+        let width = GRID_CELL_SIZE.0 as f32 * 2.0;
+        let mut mesh = graphics::MeshBuilder::new();
+
+        if self.health.shield > 0 {
+            // a bubble around the slime that fades as the shield wears down
+            let center = [position.x + width / 2.0, position.y + GRID_CELL_SIZE.1 as f32 * 0.75];
+            let alpha = 0.15 + 0.25 * self.health.shield_fraction();
+            mesh.circle(graphics::DrawMode::fill(), center, width * 0.7, 0.5, Color { a: alpha, ..SHIELD_COLOR })?;
+            mesh.circle(graphics::DrawMode::stroke(1.0), center, width * 0.7, 0.5, SHIELD_COLOR)?;
+        }
+
+        if show_health_bar {
+            let bar = graphics::Rect::new(position.x, position.y - HEALTH_BAR_RISE, width, HEALTH_BAR_HEIGHT);
+            // armored enemies get a thick steel frame around their bar
+            let (frame_color, frame_width) = if self.health.armor > 0 { (ARMOR_COLOR, 2.0) } else { (Color::BLACK, 1.0) };
+            let frame = graphics::Rect::new(bar.x - frame_width, bar.y - frame_width, bar.w + frame_width * 2.0, bar.h + frame_width * 2.0);
+            mesh.rectangle(graphics::DrawMode::fill(), frame, frame_color)?;
+            mesh.rectangle(graphics::DrawMode::fill(), bar, Color::new(0.15, 0.15, 0.15, 1.0))?;
+
+            let fraction = self.health.fraction();
+            if fraction > 0.0 {
+                mesh.rectangle(graphics::DrawMode::fill(), graphics::Rect::new(bar.x, bar.y, bar.w * fraction, bar.h), health::bar_color(fraction))?;
+            }
+            let shield_fraction = self.health.shield_fraction();
+            if shield_fraction > 0.0 {
+                let shield_bar = graphics::Rect::new(bar.x, bar.y, bar.w * shield_fraction, bar.h / 2.0);
+                mesh.rectangle(graphics::DrawMode::fill(), shield_bar, SHIELD_COLOR)?;
+            }
+        }
+
+        if self.health.shield <= 0 && !show_health_bar {
+            return Ok(());
+        }
+        let mesh = mesh.build(ctx)?;
+        graphics::draw(ctx, &mesh, graphics::DrawParam::new())
+    }
+}
+
+/// A readable name for the sprite, `slime_blue` becomes "Blue slime".
+fn enemy_kind(sprite: &str) -> String {
+    let name = sprite.rsplit('_').collect::<Vec<&str>>().join(" ");
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => name,
     }
 }
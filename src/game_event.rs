@@ -0,0 +1,15 @@
+use ggez::graphics::Color;
+
+/// Something that happened in the level, for sounds and effects to react to. Positions
+/// are in cells.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GameEvent {
+    Shot,
+    /// An enemy took `damage` and survived.
+    Hit { position: (f32, f32), damage: i32 },
+    /// An enemy died to `damage`, and `honey` was earned for it.
+    Kill { position: (f32, f32), damage: i32, honey: i32, color: Color },
+    NexusHit,
+    Upgrade,
+    GameOver,
+}
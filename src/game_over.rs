@@ -0,0 +1,52 @@
+use ggez::{Context, GameResult};
+use ggez::event::{KeyCode, KeyMods};
+use crate::level_select;
+use crate::scene::{Scene, Shared, Transition};
+
+pub const NAME: &str = "game over";
+const ITEMS: [(&str, bool); 2] = [("Play again", true), ("Level select", true)];
+
+/// The result of a level, over the level that was played. Playing again goes back to it.
+pub struct GameOver {
+    result: String,
+    clicked: Option<usize>,
+}
+
+impl GameOver {
+    pub fn new(result: String) -> Self {
+        GameOver { result, clicked: None }
+    }
+}
+
+impl Scene for GameOver {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn update(&mut self, _ctx: &mut Context, _shared: &mut Shared) -> GameResult<Transition> {
+        let transition = match self.clicked.take() {
+            Some(0) => Transition::Pop,
+            Some(_) => Transition::Unwind { to: level_select::NAME, push: None },
+            None => Transition::None,
+        };
+        Ok(transition)
+    }
+
+    fn draw(&mut self, ctx: &mut Context, shared: &mut Shared) -> GameResult {
+        if let Some(clicked) = shared.ui.menu(ctx, &mut shared.assets, shared.screen, &self.result, &ITEMS)? {
+            self.clicked = Some(clicked);
+        }
+        Ok(())
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+
+    fn key_down(&mut self, _ctx: &mut Context, _shared: &mut Shared, keycode: KeyCode, _keymods: KeyMods, repeat: bool) -> Transition {
+        match keycode {
+            KeyCode::Escape if !repeat => Transition::Unwind { to: level_select::NAME, push: None },
+            _ => Transition::None,
+        }
+    }
+}
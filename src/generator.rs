@@ -0,0 +1,258 @@
+//! Generates playable levels from a seed for the endless mode. The road winds from the
+//! left edge to a nexus near the right edge, tower slots are put next to it until most of
+//! the route is in reach of a tower, and the rest of the map gets ponds and decor.
+//! The same seed always gives the same level.
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use crate::autotile;
+use crate::config::DEFAULT_GRID_SIZE;
+use crate::level::{Animation, AnimationFrame, Level, NexusLayout, Spawn};
+use crate::movement_helpers::{GridPosition, RectangleBorder};
+
+/// The key endless runs are stored under in the profile's best scores.
+pub const ENDLESS_PROFILE_KEY: &str = "endless";
+
+/// How far from a tower slot, in cells, the route counts as covered.
+pub const COVERAGE_RADIUS: f32 = 6.0;
+/// The share of the route that has to be covered by tower slots.
+pub const MIN_COVERAGE: f32 = 0.8;
+
+const NEXUS_SIZE: i16 = 5;
+const MIN_STRAIGHT: i16 = 4;
+const MAX_STRAIGHT: i16 = 12;
+const MIN_TURN: i16 = 6;
+const DECOR_CHANCE: f64 = 0.06;
+const WATER_ANIMATION: &str = "water";
+const WATER_FRAMES: [&str; 2] = [autotile::WATER, autotile::WATER_RIPPLE];
+const WATER_FRAME_DURATION: u32 = 600;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Cell {
+    Grass,
+    Road,
+    Nexus,
+    TowerPad,
+    Water,
+}
+
+struct Layout {
+    width: i16,
+    height: i16,
+    cells: Vec<Cell>,
+}
+
+impl Layout {
+    fn get(&self, x: i16, y: i16) -> Option<Cell> {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return None;
+        }
+        Some(self.cells[y as usize * self.width as usize + x as usize])
+    }
+
+    fn set(&mut self, x: i16, y: i16, cell: Cell) {
+        if self.get(x, y).is_some() {
+            self.cells[y as usize * self.width as usize + x as usize] = cell;
+        }
+    }
+
+    fn is_road(&self, x: i16, y: i16) -> bool {
+        match self.get(x, y) {
+            Some(cell) => cell == Cell::Road || cell == Cell::Nexus,
+            None => true,
+        }
+    }
+
+    /// Whether the `width` x `height` block at `(x, y)` and a one cell ring around it are
+    /// all grass inside the grid.
+    fn is_free(&self, x: i16, y: i16, width: i16, height: i16) -> bool {
+        if x < 1 || y < 1 || x + width >= self.width || y + height >= self.height {
+            return false;
+        }
+        (y - 1..=y + height).all(|cy| (x - 1..=x + width).all(|cx| self.get(cx, cy) == Some(Cell::Grass)))
+    }
+
+    fn fill(&mut self, x: i16, y: i16, width: i16, height: i16, cell: Cell) {
+        for cy in y..y + height {
+            for cx in x..x + width {
+                self.set(cx, cy, cell);
+            }
+        }
+    }
+}
+
+/// Creates the level for `seed`. Endless levels have no enemy limit.
+pub fn generate_level(seed: u64) -> Level {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let (width, height) = DEFAULT_GRID_SIZE;
+
+    loop {
+        let mut layout = Layout { width, height, cells: vec![Cell::Grass; width as usize * height as usize] };
+        let (start, path, nexus) = wind_road(&mut rng, &mut layout);
+        let route = route_cells(start, &path);
+
+        let tower_slots = place_tower_slots(&mut rng, &mut layout, &route);
+        if coverage(&route, &tower_slots) < MIN_COVERAGE {
+            continue;
+        }
+
+        dig_ponds(&mut rng, &mut layout);
+
+        let mut level = Level::new(format!("Endless #{}", seed), (width, height), nexus);
+        level.spawns.push(Spawn { position: start.into(), path });
+        level.tower_slots = tower_slots.iter().map(|slot| (*slot).into()).collect();
+        if layout.cells.contains(&Cell::Water) {
+            level.animations.push(Animation {
+                id: WATER_ANIMATION.to_string(),
+                frames: WATER_FRAMES.iter()
+                    .map(|sprite| AnimationFrame { sprite: sprite.to_string(), duration: WATER_FRAME_DURATION })
+                    .collect(),
+            });
+        }
+        paint_tiles(&mut rng, &layout, &mut level);
+        return level;
+    }
+}
+
+/// Lays out the road as straights to the right with turns up and down in between and
+/// returns the spawn, the checkpoints and the nexus the road ends in.
+fn wind_road(rng: &mut StdRng, layout: &mut Layout) -> ((i16, i16), Vec<GridPosition>, NexusLayout) {
+    let (width, height) = (layout.width, layout.height);
+    // keep the nexus, which is centered on the road, inside the grid
+    let lowest_lane = 3;
+    let highest_lane = height - 5;
+    let nexus_x = width - NEXUS_SIZE - 2;
+
+    let start = (0, rng.gen_range(lowest_lane..=highest_lane));
+    let (mut x, mut y) = start;
+    let mut path: Vec<GridPosition> = Vec::new();
+    loop {
+        let straight = rng.gen_range(MIN_STRAIGHT..=MAX_STRAIGHT);
+        if x + straight + MIN_TURN >= nexus_x {
+            break;
+        }
+        x += straight;
+        path.push((x, y).into());
+
+        let turn_to = loop {
+            let lane = rng.gen_range(lowest_lane..=highest_lane);
+            if (lane - y).abs() >= MIN_TURN {
+                break lane;
+            }
+        };
+        y = turn_to;
+        path.push((x, y).into());
+    }
+
+    let nexus_y = y - 2;
+    path.push((nexus_x, y).into());
+
+    let route = route_cells(start, &path);
+    for (cx, cy) in route.iter() {
+        layout.fill(*cx, *cy, 2, 2, Cell::Road);
+    }
+    layout.fill(nexus_x, nexus_y, NEXUS_SIZE, NEXUS_SIZE, Cell::Nexus);
+
+    let nexus = NexusLayout {
+        position: GridPosition::new((nexus_x * 2 + NEXUS_SIZE - 1) as f32 / 2.0, (nexus_y * 2 + NEXUS_SIZE - 1) as f32 / 2.0),
+        render_position: GridPosition::new(nexus_x as f32 + 0.5, nexus_y as f32 + 0.5),
+        borders: RectangleBorder::new(
+            (nexus_x, nexus_y).into(),
+            (nexus_x + NEXUS_SIZE - 1, nexus_y + NEXUS_SIZE - 1).into(),
+        ),
+    };
+    (start, path, nexus)
+}
+
+/// Every position an enemy passes on its way from `start` through the checkpoints.
+fn route_cells(start: (i16, i16), path: &[GridPosition]) -> Vec<(i16, i16)> {
+    let mut cells: Vec<(i16, i16)> = vec![start];
+    let mut cell = start;
+    for checkpoint in path.iter() {
+        let to: (i16, i16) = (*checkpoint).into();
+        let step = ((to.0 - cell.0).signum(), (to.1 - cell.1).signum());
+        while cell != to {
+            cell = (cell.0 + step.0, cell.1 + step.1);
+            cells.push(cell);
+        }
+    }
+    cells
+}
+
+fn is_covered(cell: (i16, i16), slots: &[(i16, i16)]) -> bool {
+    // enemies and tower pads are both 2x2, so compare their centers
+    slots.iter().any(|(x, y)| {
+        let dx = (*x as f32 - 0.5) - (cell.0 as f32 + 0.5);
+        let dy = (*y as f32 - 0.5) - (cell.1 as f32 + 0.5);
+        (dx * dx + dy * dy).sqrt() <= COVERAGE_RADIUS
+    })
+}
+
+fn coverage(route: &[(i16, i16)], slots: &[(i16, i16)]) -> f32 {
+    let covered = route.iter().filter(|cell| is_covered(**cell, slots)).count();
+    covered as f32 / route.len() as f32
+}
+
+/// Walks the route and puts a tower pad next to the road wherever the route is not yet
+/// in reach of a tower. Returns the slots, each at the bottom right cell of its pad.
+fn place_tower_slots(rng: &mut StdRng, layout: &mut Layout, route: &[(i16, i16)]) -> Vec<(i16, i16)> {
+    let mut slots: Vec<(i16, i16)> = Vec::new();
+    for (x, y) in route.iter().copied() {
+        if is_covered((x, y), &slots) {
+            continue;
+        }
+
+        // one grass cell between the road and the pad
+        let mut pads = [(x, y - 3), (x, y + 3), (x - 3, y), (x + 3, y)];
+        pads.shuffle(rng);
+        if let Some((pad_x, pad_y)) = pads.iter().copied().find(|(pad_x, pad_y)| layout.is_free(*pad_x, *pad_y, 2, 2)) {
+            layout.fill(pad_x, pad_y, 2, 2, Cell::TowerPad);
+            slots.push((pad_x + 1, pad_y + 1));
+        }
+    }
+    slots
+}
+
+fn dig_ponds(rng: &mut StdRng, layout: &mut Layout) {
+    let ponds = rng.gen_range(0..=3);
+    for _ in 0..ponds {
+        // ponds only go where there is room for them, so give up after a few tries
+        for _ in 0..20 {
+            let pond_width = rng.gen_range(3..=6);
+            let pond_height = rng.gen_range(2..=4);
+            let x = rng.gen_range(0..layout.width - pond_width);
+            let y = rng.gen_range(0..layout.height - pond_height);
+            if layout.is_free(x, y, pond_width, pond_height) {
+                layout.fill(x, y, pond_width, pond_height, Cell::Water);
+                break;
+            }
+        }
+    }
+}
+
+fn paint_tiles(rng: &mut StdRng, layout: &Layout, level: &mut Level) {
+    for y in 0..layout.height {
+        for x in 0..layout.width {
+            let tile = level.tile_mut(x, y).unwrap();
+            match layout.get(x, y).unwrap() {
+                Cell::Grass => {
+                    tile.sprite = autotile::GRASS.to_string();
+                    if rng.gen_bool(DECOR_CHANCE) {
+                        tile.decor = autotile::DECOR.choose(rng).map(|decor| decor.to_string());
+                    }
+                }
+                Cell::Water => tile.sprite = WATER_ANIMATION.to_string(),
+                Cell::Road | Cell::Nexus => tile.sprite = autotile::road_sprite(|x, y| layout.is_road(x, y), x, y).to_string(),
+                Cell::TowerPad => tile.sprite = autotile::GRASS.to_string(),
+            }
+        }
+    }
+
+    for slot in level.tower_slots.clone() {
+        let (x, y): (i16, i16) = slot.into();
+        for (dx, dy, sprite) in autotile::TOWER_PAD.iter() {
+            level.tile_mut(x + dx, y + dy).unwrap().sprite = sprite.to_string();
+        }
+    }
+}
@@ -0,0 +1,100 @@
+//! Hit points of enemies and what protects them: a shield soaks up damage until it breaks,
+//! armor blocks part of every hit that gets through.
+
+use ggez::graphics::Color;
+use std::cmp::min;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Health {
+    pub current: i32,
+    pub max: i32,
+    pub shield: i32,
+    pub max_shield: i32,
+    /// The percentage of damage blocked, from 0 to 100.
+    pub armor: i32,
+}
+
+impl Health {
+    pub fn new(max: i32) -> Self {
+        Health { current: max, max, shield: 0, max_shield: 0, armor: 0 }
+    }
+
+    pub fn with_shield(mut self, shield: i32) -> Self {
+        self.shield = shield;
+        self.max_shield = shield;
+        self
+    }
+
+    pub fn with_armor(mut self, armor: i32) -> Self {
+        self.armor = armor.clamp(0, 100);
+        self
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.current > 0
+    }
+
+    /// Whether anything has been taken off the health or the shield.
+    pub fn is_damaged(&self) -> bool {
+        self.current < self.max || self.shield < self.max_shield
+    }
+
+    /// Deals up to `damage` and returns how much of it was used up, the rest can go to the
+    /// next enemy. The shield goes first, then armor blocks its part of what's left.
+    pub fn take_damage(&mut self, damage: i32) -> i32 {
+        if !self.is_alive() || damage <= 0 {
+            return 0;
+        }
+
+        let shield_hit = min(self.shield, damage);
+        self.shield -= shield_hit;
+        let left = damage - shield_hit;
+
+        let health_hit = left - left * self.armor / 100;
+        if health_hit < self.current {
+            self.current -= health_hit;
+            return damage;
+        }
+
+        // the hit kills, only what it took to get through the armor is used up. Armor
+        // rounds the blocked part down, so the least that kills can be below the share
+        // needed without rounding.
+        let mut needed = if self.armor >= 100 {
+            left
+        } else {
+            let unblocked = 100 - self.armor;
+            min(left, (self.current * 100 + unblocked - 1) / unblocked)
+        };
+        while needed > 1 && (needed - 1) - (needed - 1) * self.armor / 100 >= self.current {
+            needed -= 1;
+        }
+        self.current = 0;
+        shield_hit + needed
+    }
+
+    /// The health left, from 0 to 1.
+    pub fn fraction(&self) -> f32 {
+        if self.max <= 0 {
+            return 0.0;
+        }
+        (self.current as f32 / self.max as f32).clamp(0.0, 1.0)
+    }
+
+    /// The shield left, from 0 to 1, 0 without a shield.
+    pub fn shield_fraction(&self) -> f32 {
+        if self.max_shield <= 0 {
+            return 0.0;
+        }
+        (self.shield as f32 / self.max_shield as f32).clamp(0.0, 1.0)
+    }
+}
+
+/// The color of a health bar `fraction` full: green when full, yellow at half, red near empty.
+pub fn bar_color(fraction: f32) -> Color {
+    let fraction = fraction.clamp(0.0, 1.0);
+    if fraction >= 0.5 {
+        Color::new((1.0 - fraction) * 2.0, 0.8, 0.1, 1.0)
+    } else {
+        Color::new(1.0, 0.8 * fraction * 2.0, 0.1, 1.0)
+    }
+}
@@ -0,0 +1,84 @@
+//! Notices changes to the files in the resources folder while the game is running, so
+//! images, sprite sheets, the asset manifest and levels can be reloaded without a restart.
+//! There is no file system notification crate in our dependencies, so the folder is polled.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often the resources folder is scanned.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// What the watcher remembers about a file to tell whether it changed.
+#[derive(Clone, Copy, PartialEq)]
+struct FileStamp {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+pub struct ResourceWatcher {
+    root: PathBuf,
+    files: HashMap<String, FileStamp>,
+    last_poll: Instant,
+}
+
+impl ResourceWatcher {
+    /// Starts watching the folder `root`, which doesn't have to exist.
+    pub fn new<P: AsRef<Path>>(root: P) -> Self {
+        let mut watcher = ResourceWatcher {
+            root: root.as_ref().to_path_buf(),
+            files: HashMap::new(),
+            last_poll: Instant::now(),
+        };
+        watcher.files = watcher.stamps();
+        watcher
+    }
+
+    /// `scan`, at most once per `POLL_INTERVAL`. Meant to be called every update.
+    pub fn poll(&mut self) -> Vec<String> {
+        if self.last_poll.elapsed() < POLL_INTERVAL {
+            return Vec::new();
+        }
+        self.last_poll = Instant::now();
+        self.scan()
+    }
+
+    /// The files that were added, changed or removed since the last scan, as resource
+    /// paths like `/levels/01_meadow.json`, sorted.
+    pub fn scan(&mut self) -> Vec<String> {
+        let files = self.stamps();
+        let mut changed: Vec<String> = files.iter()
+            .filter(|(path, stamp)| self.files.get(*path) != Some(stamp))
+            .map(|(path, _)| path.clone())
+            .chain(self.files.keys().filter(|path| !files.contains_key(*path)).cloned())
+            .collect();
+        changed.sort();
+        self.files = files;
+        changed
+    }
+
+    fn stamps(&self) -> HashMap<String, FileStamp> {
+        let mut files: HashMap<String, FileStamp> = HashMap::new();
+        let mut folders: Vec<PathBuf> = vec![self.root.clone()];
+        while let Some(folder) = folders.pop() {
+            let entries = match fs::read_dir(&folder) {
+                Ok(entries) => entries,
+                Err(_) => continue,
+            };
+            for entry in entries.filter_map(|entry| entry.ok()) {
+                let metadata = match entry.metadata() {
+                    Ok(metadata) => metadata,
+                    Err(_) => continue,
+                };
+                if metadata.is_dir() {
+                    folders.push(entry.path());
+                } else if let Ok(relative) = entry.path().strip_prefix(&self.root) {
+                    let resource_path = format!("/{}", relative.to_string_lossy().replace('\\', "/"));
+                    files.insert(resource_path, FileStamp { modified: metadata.modified().ok(), len: metadata.len() });
+                }
+            }
+        }
+        files
+    }
+}
@@ -0,0 +1,77 @@
+use ggez::graphics::{Color, Rect};
+use ggez::{Context, GameResult};
+use crate::assets::Assets;
+use crate::text::TextStyle;
+use crate::ui::{Ui, ACCENT_COLOR};
+
+const PADDING: f32 = 8.0;
+const LINE_SPACING: f32 = 2.0;
+/// How far from the mouse tooltips are drawn, so the cursor doesn't cover them.
+const CURSOR_OFFSET: f32 = 16.0;
+
+/// A box of text about a tower or an enemy, shown on hover or for the selected tower.
+pub struct InfoPanel {
+    pub title: String,
+    pub lines: Vec<String>,
+}
+
+impl InfoPanel {
+    pub fn new(title: String, lines: Vec<String>) -> Self {
+        InfoPanel { title, lines }
+    }
+
+    /// Draws the panel next to the mouse at `cursor`, flipped to the other side of the
+    /// cursor where it would leave `screen`. Both are in screen coordinates.
+    pub fn draw_at_cursor(&self, ctx: &mut Context, ui: &mut Ui, assets: &mut Assets, cursor: [f32; 2], screen: Rect) -> GameResult<Rect> {
+        let (width, height) = self.size(ctx, assets);
+        let mut x = cursor[0] + CURSOR_OFFSET;
+        let mut y = cursor[1] + CURSOR_OFFSET;
+        if x + width > screen.w {
+            x = cursor[0] - CURSOR_OFFSET - width;
+        }
+        if y + height > screen.h {
+            y = cursor[1] - CURSOR_OFFSET - height;
+        }
+        self.draw(ctx, ui, assets, [x.max(0.0), y.max(0.0)])
+    }
+
+    /// Draws the panel with its top left corner at `dest`, in screen coordinates, and
+    /// returns where it ended up.
+    pub fn draw(&self, ctx: &mut Context, ui: &mut Ui, assets: &mut Assets, dest: [f32; 2]) -> GameResult<Rect> {
+        let (width, height) = self.size(ctx, assets);
+        let rect = Rect::new(dest[0], dest[1], width, height);
+        ui.panel(ctx, rect)?;
+
+        let mut y = dest[1] + PADDING;
+        let title_style = title_style();
+        ui.label(ctx, assets, &self.title, [dest[0] + PADDING, y], &title_style)?;
+        y += assets.text_dimensions(ctx, &self.title, &title_style).1 + LINE_SPACING * 2.0;
+
+        let line_style = line_style();
+        for line in self.lines.iter() {
+            ui.label(ctx, assets, line, [dest[0] + PADDING, y], &line_style)?;
+            y += assets.text_dimensions(ctx, line, &line_style).1 + LINE_SPACING;
+        }
+        Ok(rect)
+    }
+
+    pub fn size(&self, ctx: &mut Context, assets: &mut Assets) -> (f32, f32) {
+        let (title_width, title_height) = assets.text_dimensions(ctx, &self.title, &title_style());
+        let mut width = title_width;
+        let mut height = title_height + LINE_SPACING * 2.0;
+        for line in self.lines.iter() {
+            let (line_width, line_height) = assets.text_dimensions(ctx, line, &line_style());
+            width = width.max(line_width);
+            height += line_height + LINE_SPACING;
+        }
+        (width + PADDING * 2.0, height + PADDING * 2.0 - LINE_SPACING)
+    }
+}
+
+fn title_style() -> TextStyle {
+    TextStyle::new(ACCENT_COLOR).size(17.0)
+}
+
+fn line_style() -> TextStyle {
+    TextStyle::new(Color::WHITE).size(14.0)
+}
@@ -0,0 +1,199 @@
+//! Keys are bound to actions rather than read directly, so the player can rebind them. The
+//! bindings are stored next to the settings in the user's config folder.
+
+use ggez::event::KeyCode;
+use ggez::{filesystem, Context, GameResult};
+use serde_json::{Map, Value};
+use std::io::Write;
+
+const BINDINGS_PATH: &str = "/bindings.json";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Pause,
+    /// Toggles playing at double speed.
+    SpeedUp,
+    UpgradeSelected,
+    SellSelected,
+    /// Selects the next tower and moves the camera to it.
+    CycleTowers,
+    QuickSave,
+    QuickLoad,
+    ToggleDebug,
+    ToggleConsole,
+}
+
+impl Action {
+    pub const ALL: [Action; 9] = [
+        Action::Pause,
+        Action::SpeedUp,
+        Action::UpgradeSelected,
+        Action::SellSelected,
+        Action::CycleTowers,
+        Action::QuickSave,
+        Action::QuickLoad,
+        Action::ToggleDebug,
+        Action::ToggleConsole,
+    ];
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            Action::Pause => "pause",
+            Action::SpeedUp => "speed_up",
+            Action::UpgradeSelected => "upgrade_selected",
+            Action::SellSelected => "sell_selected",
+            Action::CycleTowers => "cycle_towers",
+            Action::QuickSave => "quick_save",
+            Action::QuickLoad => "quick_load",
+            Action::ToggleDebug => "toggle_debug",
+            Action::ToggleConsole => "toggle_console",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Pause => "Pause",
+            Action::SpeedUp => "Speed up",
+            Action::UpgradeSelected => "Upgrade tower",
+            Action::SellSelected => "Sell tower",
+            Action::CycleTowers => "Next tower",
+            Action::QuickSave => "Quick save",
+            Action::QuickLoad => "Quick load",
+            Action::ToggleDebug => "Debug info",
+            Action::ToggleConsole => "Console",
+        }
+    }
+
+    fn default_key(&self) -> KeyCode {
+        match self {
+            Action::Pause => KeyCode::Escape,
+            Action::SpeedUp => KeyCode::Space,
+            Action::UpgradeSelected => KeyCode::U,
+            Action::SellSelected => KeyCode::X,
+            Action::CycleTowers => KeyCode::Tab,
+            Action::QuickSave => KeyCode::F5,
+            Action::QuickLoad => KeyCode::F9,
+            Action::ToggleDebug => KeyCode::F3,
+            Action::ToggleConsole => KeyCode::Grave,
+        }
+    }
+}
+
+/// Keys the game reads directly, so they can't be bound: WASD pans the map, H switches the
+/// health bars, F2 opens the editor, F7 and F8 change the volume, = and - zoom and Enter and
+/// Backspace edit the console's input.
+pub const RESERVED_KEYS: [KeyCode; 12] = [
+    KeyCode::W, KeyCode::A, KeyCode::S, KeyCode::D, KeyCode::H,
+    KeyCode::F2, KeyCode::F7, KeyCode::F8, KeyCode::Equals, KeyCode::Minus,
+    KeyCode::Return, KeyCode::Back,
+];
+
+/// The keys that can be bound, with the names they are saved and shown with. None of the
+/// `RESERVED_KEYS` are among them.
+const KEY_NAMES: [(KeyCode, &str); 52] = [
+    (KeyCode::B, "B"), (KeyCode::C, "C"),
+    (KeyCode::E, "E"), (KeyCode::F, "F"), (KeyCode::G, "G"),
+    (KeyCode::I, "I"), (KeyCode::J, "J"), (KeyCode::K, "K"), (KeyCode::L, "L"),
+    (KeyCode::M, "M"), (KeyCode::N, "N"), (KeyCode::O, "O"), (KeyCode::P, "P"),
+    (KeyCode::Q, "Q"), (KeyCode::R, "R"), (KeyCode::T, "T"),
+    (KeyCode::U, "U"), (KeyCode::V, "V"), (KeyCode::X, "X"),
+    (KeyCode::Y, "Y"), (KeyCode::Z, "Z"),
+    (KeyCode::Key0, "0"), (KeyCode::Key1, "1"), (KeyCode::Key2, "2"), (KeyCode::Key3, "3"),
+    (KeyCode::Key4, "4"), (KeyCode::Key5, "5"), (KeyCode::Key6, "6"), (KeyCode::Key7, "7"),
+    (KeyCode::Key8, "8"), (KeyCode::Key9, "9"),
+    (KeyCode::F1, "F1"), (KeyCode::F3, "F3"), (KeyCode::F4, "F4"),
+    (KeyCode::F5, "F5"), (KeyCode::F6, "F6"),
+    (KeyCode::F9, "F9"), (KeyCode::F10, "F10"), (KeyCode::F11, "F11"), (KeyCode::F12, "F12"),
+    (KeyCode::Escape, "Escape"), (KeyCode::Space, "Space"), (KeyCode::Tab, "Tab"),
+    (KeyCode::Delete, "Delete"),
+    (KeyCode::Insert, "Insert"), (KeyCode::Home, "Home"), (KeyCode::End, "End"),
+    (KeyCode::PageUp, "PageUp"), (KeyCode::PageDown, "PageDown"),
+    (KeyCode::Comma, ","), (KeyCode::Period, "."), (KeyCode::Grave, "`"),
+];
+
+pub fn key_name(key: KeyCode) -> Option<&'static str> {
+    KEY_NAMES.iter().find(|(known, _)| *known == key).map(|(_, name)| *name)
+}
+
+pub fn parse_key(name: &str) -> Option<KeyCode> {
+    KEY_NAMES.iter().find(|(_, known)| *known == name).map(|(key, _)| *key)
+}
+
+/// One key for every action.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bindings {
+    keys: Vec<(Action, KeyCode)>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Bindings { keys: Action::ALL.iter().map(|action| (*action, action.default_key())).collect() }
+    }
+}
+
+impl Bindings {
+    /// Loads the saved bindings, falling back to the defaults for anything missing or unreadable.
+    pub fn load(ctx: &mut Context) -> Self {
+        let bindings_file = match filesystem::open(ctx, BINDINGS_PATH) {
+            Ok(bindings_file) => bindings_file,
+            Err(_) => return Bindings::default(),
+        };
+
+        match serde_json::from_reader(bindings_file) {
+            Ok(json) => Bindings::from_json(&json),
+            Err(e) => {
+                println!("key bindings are corrupted, using the defaults: {}", e);
+                Bindings::default()
+            }
+        }
+    }
+
+    pub fn save(&self, ctx: &mut Context) -> GameResult {
+        let mut bindings_file = filesystem::create(ctx, BINDINGS_PATH)?;
+        bindings_file.write_all(self.to_json().to_string().as_bytes())?;
+        Ok(())
+    }
+
+    pub fn from_json(json: &Value) -> Self {
+        let mut bindings = Bindings::default();
+        for action in Action::ALL.iter() {
+            match json[action.key()].as_str().map(|name| (name, parse_key(name))) {
+                Some((_, Some(key))) => bindings.bind(*action, key),
+                Some((name, None)) => println!("unknown key {} for {}, keeping the default", name, action.key()),
+                None => {}
+            }
+        }
+        bindings
+    }
+
+    pub fn to_json(&self) -> Value {
+        let mut json = Map::new();
+        for (action, key) in self.keys.iter() {
+            if let Some(name) = key_name(*key) {
+                json.insert(action.key().to_string(), Value::from(name));
+            }
+        }
+        Value::Object(json)
+    }
+
+    pub fn key_for(&self, action: Action) -> KeyCode {
+        self.keys.iter().find(|(bound, _)| *bound == action).map_or(action.default_key(), |(_, key)| *key)
+    }
+
+    pub fn action_for(&self, key: KeyCode) -> Option<Action> {
+        self.keys.iter().find(|(_, bound)| *bound == key).map(|(action, _)| *action)
+    }
+
+    /// Binds `key` to `action`. An action that had the key before gets the action's old
+    /// key instead, so no two actions share a key.
+    pub fn bind(&mut self, action: Action, key: KeyCode) {
+        let old_key = self.key_for(action);
+        for (bound, bound_key) in self.keys.iter_mut() {
+            if *bound == action {
+                *bound_key = key;
+            } else if *bound_key == key {
+                *bound_key = old_key;
+            }
+        }
+    }
+}
@@ -0,0 +1,517 @@
+use ggez::{GameError, GameResult};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use crate::autotile::TOWER_PAD;
+use crate::movement_helpers::{GridPosition, RectangleBorder};
+use crate::terrain::Terrain;
+
+const DEFAULT_STARTING_HONEY: i32 = 100;
+const DEFAULT_STARTING_LIVES: i32 = 3;
+
+pub struct Spawn {
+    pub position: GridPosition,
+    pub path: Vec<GridPosition>,
+}
+
+pub struct NexusLayout {
+    pub position: GridPosition,
+    pub render_position: GridPosition,
+    pub borders: RectangleBorder,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tile {
+    pub sprite: String,
+    pub decor: Option<String>,
+}
+
+/// An image the level brings along, e.g. from a Tiled tileset, registered in `Assets` under `id`
+/// before the level is played. `source_rect` is `[x, y, width, height]` when the sprite is
+/// one cell of a larger tileset image.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TileImage {
+    pub id: String,
+    pub path: String,
+    pub source_rect: Option<[u32; 4]>,
+    pub decor: bool,
+}
+
+/// A sparse layer of sprites drawn over the ground and decor, in the order the level lists
+/// them. Layers `above_entities` are drawn over enemies and towers, e.g. a tree canopy.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Layer {
+    pub name: String,
+    pub above_entities: bool,
+    pub sprites: BTreeMap<(i16, i16), String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnimationFrame {
+    pub sprite: String,
+    /// How long the frame is shown, in milliseconds.
+    pub duration: u32,
+}
+
+/// A sprite cycling through frames, e.g. water or swaying flowers. Its id can be used
+/// in place of a sprite id in any layer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Animation {
+    pub id: String,
+    pub frames: Vec<AnimationFrame>,
+}
+
+impl Animation {
+    /// The sprite shown `millis` milliseconds into the level.
+    pub fn sprite_at(&self, millis: u64) -> &str {
+        let cycle: u64 = self.frames.iter().map(|frame| frame.duration as u64).sum();
+        if cycle == 0 {
+            return &self.frames[0].sprite;
+        }
+
+        let mut elapsed = millis % cycle;
+        for frame in self.frames.iter() {
+            if elapsed < frame.duration as u64 {
+                return &frame.sprite;
+            }
+            elapsed -= frame.duration as u64;
+        }
+        &self.frames[0].sprite
+    }
+}
+
+/// Everything needed to play a map: terrain, enemy routes, the nexus, tower slots
+/// and the starting resources. Loaded from a single JSON file under `levels/`.
+pub struct Level {
+    pub name: String,
+    pub grid_size: (i16, i16),
+    pub starting_honey: i32,
+    pub starting_lives: i32,
+    /// How many enemies have to be beaten to complete the level, `None` for endless play.
+    pub enemy_count: Option<i32>,
+    pub spawns: Vec<Spawn>,
+    pub nexus: NexusLayout,
+    pub tower_slots: Vec<GridPosition>,
+    pub tile_images: Vec<TileImage>,
+    /// Extra layers, drawn after the ground and decor of `tiles`.
+    pub layers: Vec<Layer>,
+    pub animations: Vec<Animation>,
+    /// Terrain properties by sprite or animation id, on top of `Terrain::of_sprite`.
+    pub terrain: BTreeMap<String, Terrain>,
+    tiles: Vec<Tile>,
+}
+
+impl Level {
+    /// An empty level with default starting resources, no spawns or tower slots and
+    /// blank tiles, for loaders to fill in.
+    pub fn new(name: String, grid_size: (i16, i16), nexus: NexusLayout) -> Self {
+        let blank_tile = Tile { sprite: String::new(), decor: None };
+        Level {
+            name,
+            grid_size,
+            starting_honey: DEFAULT_STARTING_HONEY,
+            starting_lives: DEFAULT_STARTING_LIVES,
+            enemy_count: None,
+            spawns: Vec::new(),
+            nexus,
+            tower_slots: Vec::new(),
+            tile_images: Vec::new(),
+            layers: Vec::new(),
+            animations: Vec::new(),
+            terrain: BTreeMap::new(),
+            tiles: vec![blank_tile; grid_size.0 as usize * grid_size.1 as usize],
+        }
+    }
+
+    pub fn from_json(json: &Value, source: &str) -> GameResult<Level> {
+        let grid_size = parse_size(&json["grid_size"], source)?;
+        let name = json["name"].as_str().unwrap_or(source).to_string();
+
+        let nexus_json = &json["nexus"];
+        let borders = parse_array(&nexus_json["borders"], "nexus.borders", source)?;
+        if borders.len() != 2 {
+            return Err(load_error(source, "nexus.borders must be [top_left, bottom_right]"));
+        }
+        let nexus = NexusLayout {
+            position: parse_position(&nexus_json["position"], "nexus.position", source)?,
+            render_position: parse_position(&nexus_json["render_position"], "nexus.render_position", source)?,
+            borders: RectangleBorder::new(
+                parse_position(&borders[0], "nexus.borders", source)?,
+                parse_position(&borders[1], "nexus.borders", source)?,
+            ),
+        };
+
+        // the file has to hold every tile before the grid is made that big
+        let rows = parse_array(&json["tiles"], "tiles", source)?;
+        if rows.len() != grid_size.1 as usize {
+            return Err(load_error(source, &format!("tiles has {} rows, expected {}", rows.len(), grid_size.1)));
+        }
+        let rows: Vec<&Vec<Value>> = rows.iter()
+            .map(|row| parse_array(row, "tiles", source))
+            .collect::<GameResult<_>>()?;
+        if let Some((y, row)) = rows.iter().enumerate().find(|(_, row)| row.len() != grid_size.0 as usize) {
+            return Err(load_error(source, &format!("tiles row {} has {} cells, expected {}", y, row.len(), grid_size.0)));
+        }
+
+        let mut level = Level::new(name, grid_size, nexus);
+        if !json["starting_honey"].is_null() {
+            level.starting_honey = parse_i32(&json["starting_honey"], "starting_honey", source)?;
+        }
+        if !json["starting_lives"].is_null() {
+            level.starting_lives = parse_i32(&json["starting_lives"], "starting_lives", source)?;
+        }
+        if !json["enemy_count"].is_null() {
+            level.enemy_count = Some(parse_i32(&json["enemy_count"], "enemy_count", source)?);
+        }
+
+        for spawn in parse_array(&json["spawns"], "spawns", source)? {
+            let position = parse_position(&spawn["position"], "spawns.position", source)?;
+            let mut path: Vec<GridPosition> = Vec::new();
+            for checkpoint in parse_array(&spawn["path"], "spawns.path", source)? {
+                path.push(parse_position(checkpoint, "spawns.path", source)?);
+            }
+            level.spawns.push(Spawn { position, path });
+        }
+        if level.spawns.is_empty() {
+            return Err(load_error(source, "at least one spawn is required"));
+        }
+
+        for slot in parse_array(&json["tower_slots"], "tower_slots", source)? {
+            level.tower_slots.push(parse_position(slot, "tower_slots", source)?);
+        }
+
+        for (y, row) in rows.iter().enumerate() {
+            for (x, sprite) in row.iter().enumerate() {
+                let sprite = parse_sprite_id(sprite)
+                    .ok_or_else(|| load_error(source, &format!("tiles row {} contains an invalid sprite id", y)))?;
+                level.tile_mut(x as i16, y as i16).unwrap().sprite = sprite;
+            }
+        }
+
+        if !json["tile_images"].is_null() {
+            for tile_image in parse_array(&json["tile_images"], "tile_images", source)? {
+                let id = parse_sprite_id(&tile_image["id"])
+                    .ok_or_else(|| load_error(source, "tile_images.id must be a sprite id"))?;
+                let path = tile_image["path"].as_str()
+                    .ok_or_else(|| load_error(source, "tile_images.path must be a string"))?
+                    .to_string();
+                let source_rect = match tile_image["source_rect"].as_array() {
+                    Some(rect) if rect.len() == 4 => Some([
+                        rect[0].as_u64().unwrap_or(0) as u32,
+                        rect[1].as_u64().unwrap_or(0) as u32,
+                        rect[2].as_u64().unwrap_or(0) as u32,
+                        rect[3].as_u64().unwrap_or(0) as u32,
+                    ]),
+                    _ => None,
+                };
+                let decor = tile_image["decor"].as_bool().unwrap_or(false);
+                level.tile_images.push(TileImage { id, path, source_rect, decor });
+            }
+        }
+
+        if !json["decor"].is_null() {
+            for decor in parse_array(&json["decor"], "decor", source)? {
+                let (x, y): (i16, i16) = parse_position(&decor["position"], "decor.position", source)?.into();
+                let sprite = parse_sprite_id(&decor["sprite"])
+                    .ok_or_else(|| load_error(source, "decor.sprite must be a sprite id"))?;
+                match level.tile_mut(x, y) {
+                    Some(tile) => tile.decor = Some(sprite),
+                    None => return Err(load_error(source, &format!("decor at ({}, {}) is outside the grid", x, y))),
+                }
+            }
+        }
+
+        if !json["layers"].is_null() {
+            for layer_json in parse_array(&json["layers"], "layers", source)? {
+                let mut layer = Layer {
+                    name: layer_json["name"].as_str().unwrap_or("").to_string(),
+                    above_entities: layer_json["above_entities"].as_bool().unwrap_or(false),
+                    sprites: BTreeMap::new(),
+                };
+                for sprite_json in parse_array(&layer_json["sprites"], "layers.sprites", source)? {
+                    let (x, y): (i16, i16) = parse_position(&sprite_json["position"], "layers.sprites.position", source)?.into();
+                    let sprite = parse_sprite_id(&sprite_json["sprite"])
+                        .ok_or_else(|| load_error(source, "layers.sprites.sprite must be a sprite id"))?;
+                    if level.tile(x, y).is_none() {
+                        return Err(load_error(source, &format!("layer {} has a sprite at ({}, {}) outside the grid", layer.name, x, y)));
+                    }
+                    layer.sprites.insert((x, y), sprite);
+                }
+                level.layers.push(layer);
+            }
+        }
+
+        if !json["animations"].is_null() {
+            for animation_json in parse_array(&json["animations"], "animations", source)? {
+                let id = parse_sprite_id(&animation_json["id"])
+                    .ok_or_else(|| load_error(source, "animations.id must be a sprite id"))?;
+                let mut frames: Vec<AnimationFrame> = Vec::new();
+                for frame in parse_array(&animation_json["frames"], "animations.frames", source)? {
+                    let sprite = parse_sprite_id(&frame["sprite"])
+                        .ok_or_else(|| load_error(source, "animations.frames.sprite must be a sprite id"))?;
+                    let duration = parse_i32(&frame["duration"], "animations.frames.duration", source)?.max(0) as u32;
+                    frames.push(AnimationFrame { sprite, duration });
+                }
+                if frames.is_empty() {
+                    return Err(load_error(source, &format!("animation {} has no frames", id)));
+                }
+                level.animations.push(Animation { id, frames });
+            }
+        }
+
+        if !json["terrain"].is_null() {
+            let terrain = json["terrain"].as_object()
+                .ok_or_else(|| load_error(source, "terrain must map sprite ids to terrain"))?;
+            for (sprite, terrain_json) in terrain.iter() {
+                let terrain = Terrain::from_json(terrain_json)
+                    .ok_or_else(|| load_error(source, &format!("terrain of sprite {} is invalid, speed must be above 0 and range_bonus at least 0", sprite)))?;
+                level.terrain.insert(sprite.clone(), terrain);
+            }
+        }
+
+        Ok(level)
+    }
+
+    /// The level in the format `from_json` reads.
+    pub fn to_json(&self) -> Value {
+        let position = |pos: &GridPosition| {
+            let (x, y): (f32, f32) = (*pos).into();
+            json!([coordinate_to_json(x), coordinate_to_json(y)])
+        };
+
+        let mut tiles: Vec<Value> = Vec::new();
+        let mut decor: Vec<Value> = Vec::new();
+        for y in 0..self.grid_size.1 {
+            let mut row: Vec<Value> = Vec::new();
+            for x in 0..self.grid_size.0 {
+                let tile = self.tile(x, y).unwrap();
+                row.push(sprite_id_to_json(&tile.sprite));
+                if let Some(sprite) = &tile.decor {
+                    decor.push(json!({ "position": [x, y], "sprite": sprite_id_to_json(sprite) }));
+                }
+            }
+            tiles.push(Value::Array(row));
+        }
+
+        let mut level = json!({
+            "name": self.name,
+            "grid_size": [self.grid_size.0, self.grid_size.1],
+            "starting_honey": self.starting_honey,
+            "starting_lives": self.starting_lives,
+            "spawns": self.spawns.iter().map(|spawn| json!({
+                "position": position(&spawn.position),
+                "path": spawn.path.iter().map(position).collect::<Vec<Value>>(),
+            })).collect::<Vec<Value>>(),
+            "nexus": {
+                "position": position(&self.nexus.position),
+                "render_position": position(&self.nexus.render_position),
+                "borders": [
+                    position(&self.nexus.borders.top_left_corner()),
+                    position(&self.nexus.borders.bot_right_corner()),
+                ],
+            },
+            "tower_slots": self.tower_slots.iter().map(position).collect::<Vec<Value>>(),
+            "tiles": tiles,
+            "decor": decor,
+        });
+
+        if let Some(enemy_count) = self.enemy_count {
+            level["enemy_count"] = json!(enemy_count);
+        }
+        if !self.tile_images.is_empty() {
+            level["tile_images"] = self.tile_images.iter().map(|tile_image| json!({
+                "id": tile_image.id,
+                "path": tile_image.path,
+                "source_rect": tile_image.source_rect,
+                "decor": tile_image.decor,
+            })).collect();
+        }
+        if !self.layers.is_empty() {
+            level["layers"] = self.layers.iter().map(|layer| json!({
+                "name": layer.name,
+                "above_entities": layer.above_entities,
+                "sprites": layer.sprites.iter()
+                    .map(|((x, y), sprite)| json!({ "position": [x, y], "sprite": sprite_id_to_json(sprite) }))
+                    .collect::<Vec<Value>>(),
+            })).collect();
+        }
+        if !self.animations.is_empty() {
+            level["animations"] = self.animations.iter().map(|animation| json!({
+                "id": sprite_id_to_json(&animation.id),
+                "frames": animation.frames.iter()
+                    .map(|frame| json!({ "sprite": sprite_id_to_json(&frame.sprite), "duration": frame.duration }))
+                    .collect::<Vec<Value>>(),
+            })).collect();
+        }
+        if !self.terrain.is_empty() {
+            level["terrain"] = self.terrain.iter()
+                .map(|(sprite, terrain)| (sprite.clone(), terrain.to_json()))
+                .collect::<serde_json::Map<String, Value>>()
+                .into();
+        }
+        level
+    }
+
+    pub fn animation(&self, id: &str) -> Option<&Animation> {
+        self.animations.iter().find(|animation| animation.id == id)
+    }
+
+    /// The sprite to draw for `sprite` at `millis` into the level, resolving animations.
+    pub fn frame_sprite<'a>(&'a self, sprite: &'a str, millis: u64) -> &'a str {
+        match self.animation(sprite) {
+            Some(animation) => animation.sprite_at(millis),
+            None => sprite,
+        }
+    }
+
+    /// The terrain of a sprite or animation. Animations without terrain of their own
+    /// take it from their first frame.
+    pub fn terrain_of(&self, sprite: &str) -> Terrain {
+        let own_terrain = |sprite: &str| self.terrain.get(sprite).copied().or_else(|| Terrain::of_sprite(sprite));
+        own_terrain(sprite)
+            .or_else(|| self.animation(sprite).and_then(|animation| own_terrain(&animation.frames[0].sprite)))
+            .unwrap_or_default()
+    }
+
+    /// The terrain of everything drawn at a cell: the tile, its decor and the layers.
+    /// Cells outside the grid are plain terrain.
+    pub fn terrain_at(&self, x: i16, y: i16) -> Terrain {
+        let tile = match self.tile(x, y) {
+            Some(tile) => tile,
+            None => return Terrain::default(),
+        };
+
+        let mut terrain = self.terrain_of(&tile.sprite);
+        if let Some(decor) = &tile.decor {
+            terrain = terrain.combine(self.terrain_of(decor));
+        }
+        for layer in self.layers.iter() {
+            if let Some(sprite) = layer.sprites.get(&(x, y)) {
+                terrain = terrain.combine(self.terrain_of(sprite));
+            }
+        }
+        terrain
+    }
+
+    /// The terrain a tower at `slot` stands on: it gets the best range bonus of its pad's
+    /// cells and can only be built when all of them are buildable.
+    pub fn tower_terrain(&self, slot: GridPosition) -> Terrain {
+        let (x, y): (i16, i16) = slot.into();
+        TOWER_PAD.iter()
+            .map(|(dx, dy, _)| self.terrain_at(x + dx, y + dy))
+            .fold(Terrain::default(), |pad, cell| Terrain {
+                speed: 1.0,
+                range_bonus: pad.range_bonus.max(cell.range_bonus),
+                buildable: pad.buildable && cell.buildable,
+            })
+    }
+
+    /// Pretty prints `to_json`, keeping positions and tile rows on a single line so the
+    /// file stays readable.
+    pub fn to_json_string(&self) -> String {
+        let mut out = String::new();
+        write_compact_pretty(&self.to_json(), 0, &mut out);
+        out.push('\n');
+        out
+    }
+
+    pub fn tile(&self, x: i16, y: i16) -> Option<&Tile> {
+        self.tile_index(x, y).map(move |index| &self.tiles[index])
+    }
+
+    pub fn tile_mut(&mut self, x: i16, y: i16) -> Option<&mut Tile> {
+        self.tile_index(x, y).map(move |index| &mut self.tiles[index])
+    }
+
+    fn tile_index(&self, x: i16, y: i16) -> Option<usize> {
+        if x < 0 || y < 0 || x >= self.grid_size.0 || y >= self.grid_size.1 {
+            return None;
+        }
+        Some(y as usize * self.grid_size.0 as usize + x as usize)
+    }
+}
+
+fn load_error(source: &str, message: &str) -> GameError {
+    GameError::ResourceLoadError(format!("level {}: {}", source, message))
+}
+
+fn parse_array<'a>(value: &'a Value, field: &str, source: &str) -> GameResult<&'a Vec<Value>> {
+    value.as_array().ok_or_else(|| load_error(source, &format!("{} must be an array", field)))
+}
+
+fn parse_i32(value: &Value, field: &str, source: &str) -> GameResult<i32> {
+    value.as_i64()
+        .map(|v| v as i32)
+        .ok_or_else(|| load_error(source, &format!("{} must be an integer", field)))
+}
+
+fn parse_size(value: &Value, source: &str) -> GameResult<(i16, i16)> {
+    match (value[0].as_i64(), value[1].as_i64()) {
+        (Some(w), Some(h)) if w > i16::MAX as i64 || h > i16::MAX as i64 => {
+            Err(load_error(source, &format!("grid_size can be at most {} cells a side", i16::MAX)))
+        }
+        (Some(w), Some(h)) if w > 0 && h > 0 => Ok((w as i16, h as i16)),
+        _ => Err(load_error(source, "grid_size must be [width, height]")),
+    }
+}
+
+fn parse_position(value: &Value, field: &str, source: &str) -> GameResult<GridPosition> {
+    match (value[0].as_f64(), value[1].as_f64()) {
+        (Some(x), Some(y)) => Ok(GridPosition::new(x as f32, y as f32)),
+        _ => Err(load_error(source, &format!("{} must be an [x, y] position", field))),
+    }
+}
+
+fn coordinate_to_json(coordinate: f32) -> Value {
+    if coordinate.fract() == 0.0 {
+        json!(coordinate as i64)
+    } else {
+        json!(coordinate)
+    }
+}
+
+fn sprite_id_to_json(sprite: &str) -> Value {
+    match sprite.parse::<i64>() {
+        Ok(id) => json!(id),
+        Err(_) => json!(sprite),
+    }
+}
+
+fn write_compact_pretty(value: &Value, indent: usize, out: &mut String) {
+    let padding = "  ".repeat(indent + 1);
+    match value {
+        Value::Array(items) if items.iter().any(|item| item.is_array() || item.is_object()) => {
+            out.push_str("[\n");
+            for (index, item) in items.iter().enumerate() {
+                out.push_str(&padding);
+                write_compact_pretty(item, indent + 1, out);
+                out.push_str(if index + 1 < items.len() { ",\n" } else { "\n" });
+            }
+            out.push_str(&"  ".repeat(indent));
+            out.push(']');
+        }
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+            out.push_str(&format!("[{}]", items.join(", ")));
+        }
+        Value::Object(fields) if !fields.is_empty() => {
+            out.push_str("{\n");
+            for (index, (key, field)) in fields.iter().enumerate() {
+                out.push_str(&format!("{}{}: ", padding, Value::from(key.as_str())));
+                write_compact_pretty(field, indent + 1, out);
+                out.push_str(if index + 1 < fields.len() { ",\n" } else { "\n" });
+            }
+            out.push_str(&"  ".repeat(indent));
+            out.push('}');
+        }
+        _ => out.push_str(&value.to_string()),
+    }
+}
+
+/// Sprite ids are written as numbers in level files but looked up as strings in `Assets`.
+fn parse_sprite_id(value: &Value) -> Option<String> {
+    match value {
+        Value::Number(n) => Some(n.to_string()),
+        Value::String(s) => Some(s.clone()),
+        _ => None,
+    }
+}
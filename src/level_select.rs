@@ -0,0 +1,128 @@
+use ggez::{graphics, Context, GameResult};
+use ggez::event::{KeyCode, KeyMods};
+use ggez::graphics::Color;
+use crate::audio;
+use crate::campaign::{Campaign, Profile};
+use crate::generator::ENDLESS_PROFILE_KEY;
+use crate::assets::Assets;
+use crate::input::Action;
+use crate::playing::{self, Playing};
+use crate::scene::{Scene, Shared, Transition};
+use crate::text::TextStyle;
+use crate::ui::Ui;
+use crate::ui_layout::{self, Anchor};
+
+pub const NAME: &str = "level select";
+
+const ROW_SIZE: (f32, f32) = (384.0, 32.0);
+const ROW_SPACING: f32 = 16.0;
+const TITLE_SPACING: f32 = 40.0;
+
+/// What the player picked on the level select screen.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LevelChoice {
+    Campaign(usize),
+    /// A freshly generated level with no end, listed after the campaign.
+    Endless,
+}
+
+pub struct LevelSelect {
+    choice: Option<LevelChoice>,
+    back: bool,
+}
+
+impl LevelSelect {
+    pub fn new() -> Self {
+        LevelSelect { choice: None, back: false }
+    }
+
+    /// Draws a button for every level, centered on `screen`, and returns the one clicked.
+    /// Locked levels can't be clicked. The back button is under them.
+    fn draw_levels(&mut self, ctx: &mut Context, ui: &mut Ui, assets: &mut Assets, screen: graphics::Rect, campaign: &Campaign, profile: &Profile) -> GameResult<Option<LevelChoice>> {
+        let title = "Select a level";
+        let title_style = TextStyle::new(Color::WHITE).size(28.0).shadow(Color::from_rgb(120, 80, 10));
+        let title_size = assets.text_dimensions(ctx, title, &title_style);
+
+        let row_count = campaign.levels.len() + 2;
+        let height = title_size.1 + TITLE_SPACING + row_count as f32 * (ROW_SIZE.1 + ROW_SPACING) - ROW_SPACING;
+        let area = Anchor::Center.place(screen, (ROW_SIZE.0, height), 0.0);
+        ui.label(ctx, assets, title, [area.x + (area.w - title_size.0) / 2.0, area.y], &title_style)?;
+
+        let rows = ui_layout::column([area.x, area.y + title_size.1 + TITLE_SPACING], &vec![ROW_SIZE; row_count], ROW_SPACING);
+        let mut choice: Option<LevelChoice> = None;
+        for (index, level) in campaign.levels.iter().enumerate() {
+            let unlocked = profile.is_unlocked(index);
+            let status = if !unlocked {
+                "Locked".to_string()
+            } else if let Some(best_score) = profile.best_score(&level.path) {
+                format!("Best: {}", best_score)
+            } else {
+                "Not played".to_string()
+            };
+            let row_str = format!("{}. {}    {}", index + 1, level.name, status);
+            if ui.button(ctx, assets, rows[index], &row_str, unlocked)? {
+                choice = Some(LevelChoice::Campaign(index));
+            }
+        }
+
+        let endless_status = match profile.best_score(ENDLESS_PROFILE_KEY) {
+            Some(best_score) => format!("Best: {}", best_score),
+            None => "Not played".to_string(),
+        };
+        let endless_str = format!("Endless    {}", endless_status);
+        if ui.button(ctx, assets, rows[campaign.levels.len()], &endless_str, true)? {
+            choice = Some(LevelChoice::Endless);
+        }
+        let back = rows[campaign.levels.len() + 1];
+        let back_size = ui.button_size(ctx, assets, "Back");
+        let back = graphics::Rect::new(back.x + (back.w - back_size.0) / 2.0, back.y, back_size.0, back.h);
+        if ui.button(ctx, assets, back, "Back", true)? {
+            self.back = true;
+        }
+
+        Ok(choice)
+    }
+}
+
+impl Scene for LevelSelect {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn update(&mut self, ctx: &mut Context, shared: &mut Shared) -> GameResult<Transition> {
+        if std::mem::take(&mut self.back) {
+            return Ok(Transition::Pop);
+        }
+        let level_choice = match self.choice.take() {
+            Some(level_choice) => level_choice,
+            None => return Ok(Transition::None),
+        };
+        match Playing::new(ctx, shared, level_choice) {
+            Ok(playing) => Ok(Transition::Push(Box::new(playing))),
+            Err(e) => {
+                println!("failed to start {:?}: {}", level_choice, e);
+                Ok(Transition::None)
+            }
+        }
+    }
+
+    fn draw(&mut self, ctx: &mut Context, shared: &mut Shared) -> GameResult {
+        if let Some(level_choice) = self.draw_levels(ctx, &mut shared.ui, &mut shared.assets, shared.screen, &shared.campaign, &shared.profile)? {
+            self.choice = Some(level_choice);
+        }
+        Ok(())
+    }
+
+    fn music(&self) -> Option<&'static str> {
+        Some(audio::MENU_MUSIC)
+    }
+
+    fn key_down(&mut self, ctx: &mut Context, shared: &mut Shared, keycode: KeyCode, _keymods: KeyMods, repeat: bool) -> Transition {
+        match (keycode, shared.bindings.action_for(keycode)) {
+            _ if repeat => Transition::None,
+            (KeyCode::Escape, _) => Transition::Pop,
+            (_, Some(Action::QuickLoad)) => playing::quick_load(ctx, shared),
+            _ => Transition::None,
+        }
+    }
+}
@@ -0,0 +1,22 @@
+pub mod config;
+pub mod movement_helpers;
+pub mod level;
+pub mod tiled;
+pub mod schema;
+pub mod autotile;
+pub mod campaign;
+pub mod manifest;
+pub mod validation;
+pub mod generator;
+pub mod camera;
+pub mod terrain;
+pub mod sprite_sheet;
+pub mod hot_reload;
+pub mod settings;
+pub mod bitmap_font;
+pub mod health;
+pub mod ui_layout;
+pub mod input;
+pub mod save_game;
+pub mod particles;
+pub mod console;
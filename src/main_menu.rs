@@ -0,0 +1,54 @@
+use ggez::{Context, GameResult};
+use ggez::event::{KeyCode, KeyMods};
+use crate::audio;
+use crate::level_select::LevelSelect;
+use crate::scene::{Scene, Shared, Transition};
+use crate::settings_menu::SettingsMenu;
+
+pub const NAME: &str = "main menu";
+const ITEMS: [(&str, bool); 3] = [("Play", true), ("Settings", true), ("Quit", true)];
+
+/// The first screen, at the bottom of the scene stack.
+pub struct MainMenu {
+    clicked: Option<usize>,
+}
+
+impl MainMenu {
+    pub fn new() -> Self {
+        MainMenu { clicked: None }
+    }
+}
+
+impl Scene for MainMenu {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn update(&mut self, _ctx: &mut Context, _shared: &mut Shared) -> GameResult<Transition> {
+        let transition = match self.clicked.take() {
+            Some(0) => Transition::Push(Box::new(LevelSelect::new())),
+            Some(1) => Transition::Push(Box::new(SettingsMenu::new())),
+            Some(_) => Transition::Quit,
+            None => Transition::None,
+        };
+        Ok(transition)
+    }
+
+    fn draw(&mut self, ctx: &mut Context, shared: &mut Shared) -> GameResult {
+        if let Some(clicked) = shared.ui.menu(ctx, &mut shared.assets, shared.screen, "Game!", &ITEMS)? {
+            self.clicked = Some(clicked);
+        }
+        Ok(())
+    }
+
+    fn music(&self) -> Option<&'static str> {
+        Some(audio::MENU_MUSIC)
+    }
+
+    fn key_down(&mut self, _ctx: &mut Context, _shared: &mut Shared, keycode: KeyCode, _keymods: KeyMods, repeat: bool) -> Transition {
+        match keycode {
+            KeyCode::Escape if !repeat => Transition::Quit,
+            _ => Transition::None,
+        }
+    }
+}
@@ -0,0 +1,164 @@
+//! The asset manifest, `/assets.json` in the resources folder, lists every file the game
+//! loads by category and id:
+//!
+//! ```json
+//! {
+//!   "tiles": { "70": "/70.png" },
+//!   "enemies": { "slime_blue": "/slime_blue.png" },
+//!   "defaults": { "tiles": "/default_tile.png", "enemies": "/default_enemy.png" }
+//! }
+//! ```
+//!
+//! Every image category needs a default, which is drawn for ids the manifest doesn't know.
+
+use ggez::{filesystem, Context, GameError, GameResult};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::io::Read;
+
+pub const MANIFEST_PATH: &str = "/assets.json";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum AssetCategory {
+    Tiles,
+    Decor,
+    Towers,
+    Enemies,
+    Ui,
+    Sounds,
+    Fonts,
+}
+
+impl AssetCategory {
+    pub const ALL: [AssetCategory; 7] = [
+        AssetCategory::Tiles,
+        AssetCategory::Decor,
+        AssetCategory::Towers,
+        AssetCategory::Enemies,
+        AssetCategory::Ui,
+        AssetCategory::Sounds,
+        AssetCategory::Fonts,
+    ];
+
+    /// The category's key in the manifest.
+    pub fn key(&self) -> &'static str {
+        match self {
+            AssetCategory::Tiles => "tiles",
+            AssetCategory::Decor => "decor",
+            AssetCategory::Towers => "towers",
+            AssetCategory::Enemies => "enemies",
+            AssetCategory::Ui => "ui",
+            AssetCategory::Sounds => "sounds",
+            AssetCategory::Fonts => "fonts",
+        }
+    }
+
+    pub fn is_image(&self) -> bool {
+        !matches!(self, AssetCategory::Sounds | AssetCategory::Fonts)
+    }
+}
+
+pub struct AssetManifest {
+    assets: BTreeMap<AssetCategory, BTreeMap<String, String>>,
+    defaults: BTreeMap<AssetCategory, String>,
+}
+
+impl AssetManifest {
+    /// Reads the manifest from the resources and makes sure every file it lists exists.
+    pub fn load(ctx: &mut Context) -> GameResult<AssetManifest> {
+        let mut contents = String::new();
+        filesystem::open(ctx, MANIFEST_PATH)?.read_to_string(&mut contents)?;
+        let json: Value = serde_json::from_str(&contents)
+            .map_err(|e| manifest_error(MANIFEST_PATH, &format!("invalid JSON: {}", e)))?;
+        let manifest = AssetManifest::from_json(&json, MANIFEST_PATH)?;
+
+        let missing = manifest.missing_files(|path| filesystem::exists(ctx, path));
+        if !missing.is_empty() {
+            return Err(manifest_error(MANIFEST_PATH, &format!("missing files: {}", missing.join(", "))));
+        }
+        Ok(manifest)
+    }
+
+    pub fn from_json(json: &Value, source: &str) -> GameResult<AssetManifest> {
+        let fields = json.as_object()
+            .ok_or_else(|| manifest_error(source, "the manifest must be an object"))?;
+        for key in fields.keys() {
+            if key != "defaults" && !AssetCategory::ALL.iter().any(|category| category.key() == key) {
+                return Err(manifest_error(source, &format!("unknown category {}", key)));
+            }
+        }
+
+        let mut assets: BTreeMap<AssetCategory, BTreeMap<String, String>> = BTreeMap::new();
+        for category in AssetCategory::ALL.iter() {
+            assets.insert(*category, parse_paths(&json[category.key()], category.key(), source)?);
+        }
+
+        let mut defaults: BTreeMap<AssetCategory, String> = BTreeMap::new();
+        for (key, path) in parse_paths(&json["defaults"], "defaults", source)? {
+            match AssetCategory::ALL.iter().find(|category| category.key() == key && category.is_image()) {
+                Some(category) => defaults.insert(*category, path),
+                None => return Err(manifest_error(source, &format!("defaults.{} is not an image category", key))),
+            };
+        }
+        for category in AssetCategory::ALL.iter().filter(|category| category.is_image()) {
+            if !defaults.contains_key(category) {
+                return Err(manifest_error(source, &format!("defaults.{} is missing", category.key())));
+            }
+        }
+
+        Ok(AssetManifest { assets, defaults })
+    }
+
+    /// The ids and paths of a category, ordered by id.
+    pub fn entries(&self, category: AssetCategory) -> impl Iterator<Item = (&str, &str)> {
+        self.assets[&category].iter().map(|(id, path)| (id.as_str(), path.as_str()))
+    }
+
+    pub fn path(&self, category: AssetCategory, id: &str) -> Option<&str> {
+        self.assets[&category].get(id).map(|path| path.as_str())
+    }
+
+    /// The image drawn for ids of an image category that aren't in the manifest.
+    pub fn default_path(&self, category: AssetCategory) -> Option<&str> {
+        self.defaults.get(&category).map(|path| path.as_str())
+    }
+
+    /// Describes every listed file that `exists` says is missing, e.g. `tiles 70: /70.png`.
+    pub fn missing_files<F: FnMut(&str) -> bool>(&self, mut exists: F) -> Vec<String> {
+        let mut missing: Vec<String> = Vec::new();
+        for (category, paths) in self.assets.iter() {
+            for (id, path) in paths.iter() {
+                if !exists(path) {
+                    missing.push(format!("{} {}: {}", category.key(), id, path));
+                }
+            }
+        }
+        for (category, path) in self.defaults.iter() {
+            if !exists(path) {
+                missing.push(format!("defaults.{}: {}", category.key(), path));
+            }
+        }
+        missing
+    }
+}
+
+fn manifest_error(source: &str, message: &str) -> GameError {
+    GameError::ResourceLoadError(format!("asset manifest {}: {}", source, message))
+}
+
+/// An optional object of ids to resource paths.
+fn parse_paths(value: &Value, field: &str, source: &str) -> GameResult<BTreeMap<String, String>> {
+    if value.is_null() {
+        return Ok(BTreeMap::new());
+    }
+
+    let fields = value.as_object()
+        .ok_or_else(|| manifest_error(source, &format!("{} must map ids to paths", field)))?;
+    let mut paths: BTreeMap<String, String> = BTreeMap::new();
+    for (id, path) in fields.iter() {
+        let path = path.as_str()
+            .ok_or_else(|| manifest_error(source, &format!("{}.{} must be a path", field, id)))?;
+        paths.insert(id.clone(), path.to_string());
+    }
+    Ok(paths)
+}
@@ -0,0 +1,128 @@
+use ggez::{graphics, Context, GameResult};
+use ggez::graphics::spritebatch::SpriteBatch;
+use std::collections::HashMap;
+use crate::assets::Assets;
+use crate::level::Level;
+use crate::manifest::AssetCategory;
+use crate::movement_helpers::GridPosition;
+
+#[derive(Clone, Copy, PartialEq)]
+enum LayerKind {
+    Ground,
+    Decor,
+    Overlay,
+}
+
+/// One layer of the map, batched by sprite. Animated cells share the batch of their
+/// animation, which gets the current frame's image before it is drawn.
+struct LayerBatches {
+    kind: LayerKind,
+    batches: HashMap<String, SpriteBatch>,
+}
+
+impl LayerBatches {
+    fn new(kind: LayerKind) -> Self {
+        LayerBatches { kind, batches: HashMap::new() }
+    }
+
+    fn add(&mut self, assets: &mut Assets, level: &Level, sprite: &str, x: i16, y: i16) {
+        if !self.batches.contains_key(sprite) {
+            let image = image_for(assets, self.kind, level.frame_sprite(sprite, 0));
+            self.batches.insert(sprite.to_string(), SpriteBatch::new(image));
+        }
+
+        let dest: ggez::mint::Point2<f32> = GridPosition::new(x as f32, y as f32).into();
+        self.batches.get_mut(sprite).unwrap().add(graphics::DrawParam::new().dest(dest));
+    }
+
+    fn draw(&mut self, ctx: &mut Context, assets: &mut Assets, level: &Level, millis: u64) -> GameResult {
+        for (sprite, batch) in self.batches.iter_mut() {
+            if level.animation(sprite).is_some() {
+                batch.set_image(image_for(assets, self.kind, level.frame_sprite(sprite, millis)));
+            }
+            graphics::draw(ctx, batch, graphics::DrawParam::new())?;
+        }
+        Ok(())
+    }
+}
+
+fn image_for(assets: &mut Assets, kind: LayerKind, sprite: &str) -> graphics::Image {
+    match kind {
+        LayerKind::Ground => assets.get_image(AssetCategory::Tiles, sprite).clone(),
+        LayerKind::Decor => assets.get_image(AssetCategory::Decor, sprite).clone(),
+        LayerKind::Overlay => assets.get_overlay_image(sprite).clone(),
+    }
+}
+
+/// The layers of the level batched by sprite, so the whole map takes one draw call per
+/// sprite and layer instead of one per cell. The batches are only rebuilt after `invalidate`.
+pub struct MapRender {
+    below_entities: Vec<LayerBatches>,
+    above_entities: Vec<LayerBatches>,
+    dirty: bool,
+}
+
+impl MapRender {
+    pub fn new() -> Self {
+        MapRender {
+            below_entities: Vec::new(),
+            above_entities: Vec::new(),
+            dirty: true,
+        }
+    }
+
+    /// Call whenever the level's tiles changed or another level was loaded.
+    pub fn invalidate(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Draws the layers under enemies and towers. `millis` is the level's clock for animations.
+    pub fn draw(&mut self, ctx: &mut Context, assets: &mut Assets, level: &Level, millis: u64) -> GameResult {
+        if self.dirty {
+            self.rebuild(assets, level);
+            self.dirty = false;
+        }
+
+        for layer in self.below_entities.iter_mut() {
+            layer.draw(ctx, assets, level, millis)?;
+        }
+        Ok(())
+    }
+
+    /// Draws the layers over enemies and towers, call after `draw` and the entities.
+    pub fn draw_above_entities(&mut self, ctx: &mut Context, assets: &mut Assets, level: &Level, millis: u64) -> GameResult {
+        for layer in self.above_entities.iter_mut() {
+            layer.draw(ctx, assets, level, millis)?;
+        }
+        Ok(())
+    }
+
+    fn rebuild(&mut self, assets: &mut Assets, level: &Level) {
+        let mut ground = LayerBatches::new(LayerKind::Ground);
+        let mut decor = LayerBatches::new(LayerKind::Decor);
+        for x in 0..level.grid_size.0 {
+            for y in 0..level.grid_size.1 {
+                let tile = level.tile(x, y).unwrap();
+                ground.add(assets, level, &tile.sprite, x, y);
+                if let Some(decor_sprite) = &tile.decor {
+                    decor.add(assets, level, decor_sprite, x, y);
+                }
+            }
+        }
+
+        self.below_entities = vec![ground, decor];
+        self.above_entities = Vec::new();
+        for layer in level.layers.iter() {
+            let mut batches = LayerBatches::new(LayerKind::Overlay);
+            for ((x, y), sprite) in layer.sprites.iter() {
+                batches.add(assets, level, sprite, *x, *y);
+            }
+
+            if layer.above_entities {
+                self.above_entities.push(batches);
+            } else {
+                self.below_entities.push(batches);
+            }
+        }
+    }
+}
@@ -4,7 +4,7 @@ use rand::{
     distributions::{Distribution, Standard},
     Rng,
 };
-use crate::config::{GRID_CELL_SIZE, GRID_SIZE};
+use crate::config::GRID_CELL_SIZE;
 use ggez::graphics::mint;
 use ggez::mint::Point2;
 
@@ -106,6 +106,7 @@ impl Direction {
     }
 }
 
+#[derive(Clone, Copy, Debug)]
 pub struct RectangleBorder {
     top_left_corner: GridPosition,
     bot_right_corner: GridPosition,
@@ -116,6 +117,14 @@ impl RectangleBorder {
         RectangleBorder { top_left_corner, bot_right_corner }
     }
 
+    pub fn top_left_corner(&self) -> GridPosition {
+        self.top_left_corner
+    }
+
+    pub fn bot_right_corner(&self) -> GridPosition {
+        self.bot_right_corner
+    }
+
     pub fn is_it_in(&self, it: GridPosition) -> bool { // :)
         return !(it.x < self.top_left_corner.x
             || it.x > self.bot_right_corner.x
@@ -1,34 +1,48 @@
-use ggez::{graphics, Context, GameResult};
+use ggez::{Context, GameResult};
 use crate::movement_helpers::{GridPosition, RectangleBorder};
 use crate::assets::Assets;
+use crate::manifest::AssetCategory;
+use crate::level::NexusLayout;
+use crate::config::MILLIS_PER_UPDATE;
+use crate::sprite_sheet::{Action, Animator};
+
+const NEXUS_SPRITE: &str = "nexus";
 
 pub struct Nexus {
     position: GridPosition,
     render_position: GridPosition,
     pub borders: RectangleBorder,
+    animator: Animator,
 }
 
 impl Nexus {
-    pub fn new() -> Self {
+    pub fn new(layout: &NexusLayout) -> Self {
         Nexus {
-            position: (16, 19).into(),
-            render_position: (13.5, 16.5).into(),
-            borders: RectangleBorder::new((13, 15).into(), (17, 21).into()),
+            position: layout.position,
+            render_position: layout.render_position,
+            borders: layout.borders,
+            animator: Animator::new(Action::Idle),
         }
     }
 
+    pub fn render_position(&self) -> GridPosition {
+        self.render_position
+    }
+
     pub fn is_enemy_in(&self, enemy_pos: GridPosition) -> bool {
         self.borders.is_it_in(enemy_pos)
     }
 
-    pub fn update(&mut self) {}
+    /// Plays the hit animation, call when an enemy reached the nexus.
+    pub fn hit(&mut self) {
+        self.animator.play(Action::Hit);
+    }
 
-    pub fn draw(&self, ctx: &mut Context, assets: &mut Assets) -> GameResult {
-        let nexus_sprite = assets.get_nexus_image();
-        let nexus_sprite_dest: ggez::mint::Point2<f32> = self.render_position.into();
-        let nexus_draw_params = graphics::DrawParam::new().dest(nexus_sprite_dest);
+    pub fn update(&mut self, assets: &Assets) {
+        self.animator.update(assets.get_sheet(AssetCategory::Ui, NEXUS_SPRITE), MILLIS_PER_UPDATE);
+    }
 
-        graphics::draw(ctx, nexus_sprite, nexus_draw_params);
-        Ok(())
+    pub fn draw(&self, ctx: &mut Context, assets: &mut Assets) -> GameResult {
+        assets.draw_sprite(ctx, AssetCategory::Ui, NEXUS_SPRITE, &self.animator, self.render_position)
     }
 }
@@ -0,0 +1,191 @@
+//! Short lived effects: particles, numbers floating up from where damage landed, screen
+//! shake and flashes. They're only simulated here, in world pixels and seconds; the game
+//! draws them.
+
+use ggez::graphics::Color;
+use rand::Rng;
+use std::f32::consts::PI;
+
+/// Downwards, in pixels per second squared.
+const GRAVITY: f32 = 400.0;
+const TEXT_RISE_SPEED: f32 = 24.0;
+const TEXT_LIFETIME: f32 = 0.9;
+/// A number this close to a younger one of the same color is added to it, so the hits of
+/// every update don't each get their own number.
+const MERGE_DISTANCE: f32 = 24.0;
+const MERGE_AGE: f32 = 0.3;
+const MAX_PARTICLES: usize = 600;
+const MAX_TEXTS: usize = 120;
+
+pub struct Particle {
+    pub position: (f32, f32),
+    pub velocity: (f32, f32),
+    pub color: Color,
+    pub size: f32,
+    age: f32,
+    lifetime: f32,
+}
+
+impl Particle {
+    /// From 1 when the particle appears down to 0 when it's gone.
+    pub fn fade(&self) -> f32 {
+        (1.0 - self.age / self.lifetime).max(0.0)
+    }
+}
+
+pub struct FloatingText {
+    pub text: String,
+    pub position: (f32, f32),
+    pub color: Color,
+    /// The number shown, for numbers that add up.
+    amount: Option<i32>,
+    age: f32,
+}
+
+impl FloatingText {
+    pub fn fade(&self) -> f32 {
+        (1.0 - self.age / TEXT_LIFETIME).max(0.0)
+    }
+}
+
+pub struct Particles {
+    particles: Vec<Particle>,
+    texts: Vec<FloatingText>,
+    shake_strength: f32,
+    shake_duration: f32,
+    shake_left: f32,
+    flash_duration: f32,
+    flash_left: f32,
+    /// Seconds since the start, to move the shake along.
+    time: f32,
+}
+
+impl Default for Particles {
+    fn default() -> Self {
+        Particles::new()
+    }
+}
+
+impl Particles {
+    pub fn new() -> Self {
+        Particles {
+            particles: Vec::new(),
+            texts: Vec::new(),
+            shake_strength: 0.0,
+            shake_duration: 0.0,
+            shake_left: 0.0,
+            flash_duration: 0.0,
+            flash_left: 0.0,
+            time: 0.0,
+        }
+    }
+
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+
+    pub fn texts(&self) -> &[FloatingText] {
+        &self.texts
+    }
+
+    pub fn float_text(&mut self, text: String, position: (f32, f32), color: Color) {
+        if self.texts.len() < MAX_TEXTS {
+            self.texts.push(FloatingText { text, position, color, amount: None, age: 0.0 });
+        }
+    }
+
+    /// Floats `amount` up from `position`, or adds it to a number that just appeared there.
+    pub fn float_number(&mut self, amount: i32, position: (f32, f32), color: Color) {
+        let recent = self.texts.iter_mut().find(|text| {
+            let (dx, dy) = (text.position.0 - position.0, text.position.1 - position.1);
+            text.amount.is_some() && text.color == color && text.age < MERGE_AGE && dx * dx + dy * dy <= MERGE_DISTANCE * MERGE_DISTANCE
+        });
+        if let Some(text) = recent {
+            let total = text.amount.unwrap_or(0) + amount;
+            text.amount = Some(total);
+            text.text = total.to_string();
+            return;
+        }
+        if self.texts.len() < MAX_TEXTS {
+            self.texts.push(FloatingText { text: amount.to_string(), position, color, amount: Some(amount), age: 0.0 });
+        }
+    }
+
+    /// `count` particles flying out of `position` in every direction, falling as they go.
+    pub fn burst<R: Rng>(&mut self, rng: &mut R, position: (f32, f32), color: Color, count: usize) {
+        let room = MAX_PARTICLES.saturating_sub(self.particles.len());
+        for _ in 0..count.min(room) {
+            let angle = rng.gen_range(0.0..2.0 * PI);
+            let speed = rng.gen_range(40.0..140.0);
+            self.particles.push(Particle {
+                position,
+                velocity: (angle.cos() * speed, angle.sin() * speed - 60.0),
+                color,
+                size: rng.gen_range(1.5..3.5),
+                age: 0.0,
+                lifetime: rng.gen_range(0.4..0.8),
+            });
+        }
+    }
+
+    /// Shakes the view by up to `strength` pixels, calming down over `duration` seconds.
+    /// A stronger shake takes over a weaker one.
+    pub fn shake(&mut self, strength: f32, duration: f32) {
+        let current = self.current_shake();
+        if strength >= current {
+            self.shake_strength = strength;
+            self.shake_duration = duration;
+            self.shake_left = duration;
+        }
+    }
+
+    pub fn flash(&mut self, duration: f32) {
+        self.flash_duration = duration;
+        self.flash_left = duration;
+    }
+
+    pub fn update(&mut self, seconds: f32) {
+        self.time += seconds;
+        for particle in self.particles.iter_mut() {
+            particle.age += seconds;
+            particle.velocity.1 += GRAVITY * seconds;
+            particle.position.0 += particle.velocity.0 * seconds;
+            particle.position.1 += particle.velocity.1 * seconds;
+        }
+        self.particles.retain(|particle| particle.age < particle.lifetime);
+
+        for text in self.texts.iter_mut() {
+            text.age += seconds;
+            text.position.1 -= TEXT_RISE_SPEED * seconds;
+        }
+        self.texts.retain(|text| text.age < TEXT_LIFETIME);
+
+        self.shake_left = (self.shake_left - seconds).max(0.0);
+        self.flash_left = (self.flash_left - seconds).max(0.0);
+    }
+
+    /// How far to move the view this frame.
+    pub fn shake_offset(&self) -> (f32, f32) {
+        let strength = self.current_shake();
+        (strength * (self.time * 53.0).sin(), strength * (self.time * 41.0).cos())
+    }
+
+    fn current_shake(&self) -> f32 {
+        if self.shake_left <= 0.0 {
+            return 0.0;
+        }
+        self.shake_strength * self.shake_left / self.shake_duration
+    }
+
+    /// How opaque the flash is, from 0 to 1.
+    pub fn flash_alpha(&self) -> f32 {
+        if self.flash_left <= 0.0 {
+            return 0.0;
+        }
+        self.flash_left / self.flash_duration
+    }
+
+    pub fn clear(&mut self) {
+        *self = Particles { time: self.time, ..Particles::new() };
+    }
+}
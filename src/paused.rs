@@ -0,0 +1,60 @@
+use ggez::{Context, GameResult};
+use ggez::event::{KeyCode, KeyMods};
+use crate::input::Action;
+use crate::level_select;
+use crate::main_menu;
+use crate::scene::{Scene, Shared, Transition};
+use crate::settings_menu::SettingsMenu;
+
+pub const NAME: &str = "paused";
+const ITEMS: [(&str, bool); 4] = [("Resume", true), ("Settings", true), ("Level select", true), ("Main menu", true)];
+
+/// Stops the level below it until the player resumes it with the button or Escape.
+pub struct Paused {
+    clicked: Option<usize>,
+}
+
+impl Paused {
+    pub fn new() -> Self {
+        Paused { clicked: None }
+    }
+}
+
+impl Scene for Paused {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn update(&mut self, _ctx: &mut Context, _shared: &mut Shared) -> GameResult<Transition> {
+        let transition = match self.clicked.take() {
+            Some(0) => Transition::Pop,
+            Some(1) => Transition::Push(Box::new(SettingsMenu::new())),
+            Some(2) => Transition::Unwind { to: level_select::NAME, push: None },
+            Some(_) => Transition::Unwind { to: main_menu::NAME, push: None },
+            None => Transition::None,
+        };
+        Ok(transition)
+    }
+
+    fn draw(&mut self, ctx: &mut Context, shared: &mut Shared) -> GameResult {
+        shared.ui.shade(ctx, shared.screen)?;
+        if let Some(clicked) = shared.ui.menu(ctx, &mut shared.assets, shared.screen, "Paused", &ITEMS)? {
+            self.clicked = Some(clicked);
+        }
+        Ok(())
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+
+    fn key_down(&mut self, _ctx: &mut Context, shared: &mut Shared, keycode: KeyCode, _keymods: KeyMods, repeat: bool) -> Transition {
+        if repeat {
+            Transition::None
+        } else if keycode == KeyCode::Escape || shared.bindings.action_for(keycode) == Some(Action::Pause) {
+            Transition::Pop
+        } else {
+            Transition::None
+        }
+    }
+}
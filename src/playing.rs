@@ -0,0 +1,898 @@
+use ggez::{graphics, Context, GameResult};
+use ggez::event::{KeyCode, KeyMods};
+use ggez::graphics::Color;
+use ggez::input::mouse::MouseButton;
+use ggez::input::{keyboard, mouse};
+use rand::Rng;
+use std::cmp::max;
+use std::collections::VecDeque;
+use std::fs;
+use std::time::{Duration, Instant};
+use crate::audio;
+use crate::camera::Camera;
+use crate::config::MILLIS_PER_UPDATE;
+use crate::console::{self, Command, Console};
+use crate::console_panel::ConsolePanel;
+use crate::debug_overlay::{DebugOverlay, DebugStats};
+use crate::editor::Editor;
+use crate::effects::Effects;
+use crate::enemy::{self, Enemy};
+use crate::game_event::GameEvent;
+use crate::game_over::GameOver;
+use crate::generator;
+use crate::get_resources_dir;
+use crate::health::Health;
+use crate::input::Action;
+use crate::level::Level;
+use crate::level_select::{self, LevelChoice};
+use crate::map_render::MapRender;
+use crate::nexus::Nexus;
+use crate::paused::Paused;
+use crate::save_game::SaveGame;
+use crate::scene::{Scene, Shared, Transition};
+use crate::score_board::{Hud, ScoreBoard};
+use crate::settings::HealthBars;
+use crate::text::TextStyle;
+use crate::tower::Tower;
+use crate::ui_layout;
+
+pub const NAME: &str = "playing";
+/// Camera panning speed in screen pixels per update.
+const SCROLL_SPEED: f32 = 4.0;
+/// How close to the window's edge, in pixels, the mouse scrolls the map.
+const EDGE_SCROLL_MARGIN: f32 = 8.0;
+const ZOOM_STEP: f32 = 1.25;
+/// How many times faster the game runs while sped up.
+const FAST_FORWARD: u64 = 2;
+
+/// What the player clicked in the HUD, carried out at the next update.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum UiAction {
+    UpgradeTower(usize),
+    SellTower(usize),
+}
+
+/// A level being played, or edited with F2.
+pub struct Playing {
+    level_choice: LevelChoice,
+    endless_seed: u64,
+    level: Level,
+    nexus: Nexus,
+    enemies: VecDeque<Enemy>,
+    towers: Vec<Tower>,
+    score: i32,
+    honey: i32,
+    lives: i32,
+    ticks: i32,
+    gameover: bool,
+    completed: bool,
+    spawned_enemies: i32,
+    last_update: Instant,
+    score_board: ScoreBoard,
+    hardness: i32,
+    editor: Editor,
+    editing: bool,
+    camera: Camera,
+    map_render: MapRender,
+    debug: bool,
+    debug_overlay: DebugOverlay,
+    fast_forward: bool,
+    effects: Effects,
+    console: Console,
+    console_panel: ConsolePanel,
+    /// Enemies reaching the nexus don't cost lives, set from the console.
+    god_mode: bool,
+    /// The tower whose info panel stays open, by index into `towers`.
+    selected_tower: Option<usize>,
+    ui_actions: Vec<UiAction>,
+}
+
+impl Playing {
+    pub fn new(ctx: &mut Context, shared: &mut Shared, level_choice: LevelChoice) -> GameResult<Self> {
+        let endless_seed = match level_choice {
+            LevelChoice::Campaign(_) => 0,
+            LevelChoice::Endless => rand::random(),
+        };
+        Playing::start(ctx, shared, level_choice, endless_seed)
+    }
+
+    /// Picks up a saved game on a fresh copy of its level.
+    pub fn from_save(ctx: &mut Context, shared: &mut Shared, save_game: &SaveGame) -> GameResult<Self> {
+        let level_choice = if save_game.level_path == generator::ENDLESS_PROFILE_KEY {
+            LevelChoice::Endless
+        } else {
+            match shared.campaign.levels.iter().position(|level| level.path == save_game.level_path) {
+                Some(level_index) => LevelChoice::Campaign(level_index),
+                None => return Err(ggez::GameError::ResourceLoadError(format!("saved level {} is not in the campaign", save_game.level_path))),
+            }
+        };
+        let mut playing = Playing::start(ctx, shared, level_choice, save_game.endless_seed)?;
+
+        playing.score = save_game.score;
+        playing.honey = save_game.honey;
+        playing.lives = save_game.lives;
+        playing.ticks = save_game.ticks;
+        playing.hardness = save_game.hardness;
+        playing.spawned_enemies = save_game.spawned_enemies;
+        for saved_tower in save_game.towers.iter() {
+            let position = saved_tower.position.into();
+            if let Some(tower) = playing.towers.iter_mut().find(|tower| tower.get_position() == position) {
+                tower.restore(saved_tower);
+            }
+        }
+        let level = &playing.level;
+        playing.enemies = save_game.enemies.iter().filter_map(|enemy| Enemy::from_saved(enemy, level)).collect();
+        Ok(playing)
+    }
+
+    fn start(ctx: &mut Context, shared: &mut Shared, level_choice: LevelChoice, endless_seed: u64) -> GameResult<Self> {
+        let level = match level_choice {
+            LevelChoice::Campaign(level_index) => shared.campaign.load_level(ctx, level_index)?,
+            LevelChoice::Endless => {
+                println!("endless seed: {}", endless_seed);
+                generator::generate_level(endless_seed)
+            }
+        };
+        shared.assets.set_level_images(ctx, &level.tile_images)?;
+
+        let camera = Camera::new((shared.screen.w, shared.screen.h), level.grid_size);
+        Ok(Playing {
+            level_choice,
+            endless_seed,
+            nexus: Nexus::new(&level.nexus),
+            enemies: VecDeque::new(),
+            towers: build_towers(&level),
+            score: 0,
+            honey: level.starting_honey,
+            lives: level.starting_lives,
+            level,
+            ticks: 0,
+            gameover: false,
+            completed: false,
+            spawned_enemies: 0,
+            last_update: Instant::now(),
+            score_board: ScoreBoard::new(),
+            hardness: 1,
+            editor: Editor::new(&shared.assets),
+            editing: false,
+            camera,
+            map_render: MapRender::new(),
+            debug: false,
+            debug_overlay: DebugOverlay::new(),
+            fast_forward: false,
+            effects: Effects::new(),
+            console: Console::new(),
+            console_panel: ConsolePanel::new(),
+            god_mode: false,
+            selected_tower: None,
+            ui_actions: Vec::new(),
+        })
+    }
+
+    /// Starts the loaded level over, e.g. after it was changed in the editor.
+    fn reset_session(&mut self) {
+        let level = &self.level;
+        self.nexus = Nexus::new(&level.nexus);
+        self.towers = build_towers(level);
+        self.selected_tower = None;
+        self.enemies.clear();
+        self.score = 0;
+        self.honey = level.starting_honey;
+        self.lives = level.starting_lives;
+        self.ticks = 0;
+        self.hardness = 1;
+        self.gameover = false;
+        self.completed = false;
+        self.spawned_enemies = 0;
+        self.effects.clear();
+    }
+
+    /// Writes the edited level back to the resources folder. Levels made in other formats
+    /// are saved as a level file next to the original.
+    fn save_level(&self, shared: &Shared) -> GameResult {
+        let level_path = match self.level_choice {
+            LevelChoice::Campaign(level_index) => shared.campaign.levels[level_index].path.clone(),
+            LevelChoice::Endless => format!("/levels/endless_{}.json", self.endless_seed),
+        };
+        let mut file_path = get_resources_dir().join(level_path.trim_start_matches('/'));
+        if file_path.extension().is_none_or(|extension| extension != "json") {
+            file_path.set_extension("json");
+            println!("{} is not a level file, saving the edited level as {} which takes its place in the campaign", level_path, file_path.display());
+        }
+
+        if let Some(folder) = file_path.parent() {
+            // without a resources folder the game runs on the embedded resources
+            fs::create_dir_all(folder)?;
+        }
+        fs::write(&file_path, self.level.to_json_string())?;
+        println!("saved {}", file_path.display());
+        Ok(())
+    }
+
+    fn quick_save(&self, ctx: &mut Context, shared: &Shared) -> GameResult {
+        let level_path = match self.level_choice {
+            LevelChoice::Campaign(level_index) => shared.campaign.levels[level_index].path.clone(),
+            LevelChoice::Endless => generator::ENDLESS_PROFILE_KEY.to_string(),
+        };
+        let save_game = SaveGame {
+            level_path,
+            endless_seed: self.endless_seed,
+            score: self.score,
+            honey: self.honey,
+            lives: self.lives,
+            ticks: self.ticks,
+            hardness: self.hardness,
+            spawned_enemies: self.spawned_enemies,
+            towers: self.towers.iter().map(|tower| tower.to_saved()).collect(),
+            // dying enemies are as good as gone
+            enemies: self.enemies.iter().filter(|enemy| enemy.is_alive()).map(|enemy| enemy.to_saved()).collect(),
+        };
+        save_game.save(ctx)
+    }
+
+    /// Selects the tower after the selected one and moves the camera to it.
+    fn cycle_towers(&mut self) {
+        if self.towers.is_empty() {
+            return;
+        }
+        let next = self.selected_tower.map_or(0, |index| (index + 1) % self.towers.len());
+        self.selected_tower = Some(next);
+        self.camera.look_at(self.towers[next].get_position());
+    }
+
+    /// The freshly loaded assets don't know the level's own tile images yet.
+    fn reload_assets(&mut self, ctx: &mut Context, shared: &mut Shared) -> GameResult {
+        shared.assets.set_level_images(ctx, &self.level.tile_images)?;
+        if !self.editing {
+            self.editor = Editor::new(&shared.assets);
+        }
+        self.map_render.invalidate();
+        Ok(())
+    }
+
+    /// Loads the level being played again. Towers keep their upgrades and enemies keep
+    /// walking, unless the routes changed under them.
+    fn reload_level(&mut self, ctx: &mut Context, shared: &mut Shared) -> GameResult {
+        let level_index = match self.level_choice {
+            LevelChoice::Campaign(level_index) => level_index,
+            // endless levels aren't read from a file
+            LevelChoice::Endless => return Ok(()),
+        };
+        let level = shared.campaign.load_level(ctx, level_index)?;
+        shared.assets.set_level_images(ctx, &level.tile_images)?;
+
+        let same_routes = level.spawns.len() == self.level.spawns.len()
+            && level.spawns.iter().zip(self.level.spawns.iter())
+                .all(|(spawn, old_spawn)| spawn.position == old_spawn.position && spawn.path == old_spawn.path);
+        if !same_routes {
+            println!("the enemy routes changed, removing the enemies on the way");
+            self.enemies.clear();
+        }
+        if level.grid_size != self.level.grid_size {
+            self.camera.set_grid_size(level.grid_size);
+        }
+
+        self.level = level;
+        let mut towers = build_towers(&self.level);
+        for tower in towers.iter_mut() {
+            if let Some(old_tower) = self.towers.iter().find(|old_tower| old_tower.get_position() == tower.get_position()) {
+                tower.set_level(old_tower.get_level());
+            }
+        }
+        self.towers = towers;
+        self.selected_tower = None;
+        self.nexus = Nexus::new(&self.level.nexus);
+        self.editor = Editor::new(&shared.assets);
+        self.map_render.invalidate();
+        Ok(())
+    }
+
+    /// Pans with WASD or when the mouse is at the edge of the window.
+    fn scroll_camera(&mut self, ctx: &mut Context) {
+        let cursor = mouse::position(ctx);
+        let screen = self.camera.screen_rect();
+        let mut direction = (0.0, 0.0);
+
+        if keyboard::is_key_pressed(ctx, KeyCode::A) || (cursor.x >= 0.0 && cursor.x < EDGE_SCROLL_MARGIN) {
+            direction.0 -= 1.0;
+        }
+        if keyboard::is_key_pressed(ctx, KeyCode::D) || (cursor.x <= screen.w && cursor.x > screen.w - EDGE_SCROLL_MARGIN) {
+            direction.0 += 1.0;
+        }
+        if keyboard::is_key_pressed(ctx, KeyCode::W) || (cursor.y >= 0.0 && cursor.y < EDGE_SCROLL_MARGIN) {
+            direction.1 -= 1.0;
+        }
+        if keyboard::is_key_pressed(ctx, KeyCode::S) || (cursor.y <= screen.h && cursor.y > screen.h - EDGE_SCROLL_MARGIN) {
+            direction.1 += 1.0;
+        }
+
+        if direction != (0.0, 0.0) {
+            self.camera.pan(direction.0 * SCROLL_SPEED, direction.1 * SCROLL_SPEED);
+        }
+    }
+
+    fn upgrade_tower(&mut self, shared: &mut Shared, index: usize) {
+        let tower = &mut self.towers[index];
+        let honey_to_upgrade: i32 = tower.honey_to_upgrade();
+        if honey_to_upgrade > self.honey {
+            // if the user doesn't have enough money nothing happens
+            return;
+        }
+
+        self.honey -= honey_to_upgrade;
+        tower.upgrade();
+        shared.events.push(GameEvent::Upgrade);
+    }
+
+    fn sell_tower(&mut self, index: usize) {
+        let tower = &mut self.towers[index];
+        self.honey += tower.sell_value();
+        tower.sell();
+    }
+
+    /// An enemy at a random spawn, as tough as the current hardness.
+    fn spawn_enemy(&mut self, sprite: &str) {
+        let mut rng = rand::thread_rng();
+        let health_multiply_noise = rng.gen_range(max(1, self.hardness - 2)..self.hardness + 1);
+        let health_m_noise = rng.gen_range(90..110);
+        let health_add_noise = rng.gen_range(0..10);
+        let spawn_index = rng.gen_range(0..self.level.spawns.len());
+        let health = Health::new(health_multiply_noise * health_m_noise + health_add_noise);
+        let enemy = Enemy::new(sprite, self.hardness, health, spawn_index, &self.level);
+        self.enemies.push_back(enemy);
+    }
+
+    /// Carries out a console command, printing what happened to the console.
+    fn execute(&mut self, ctx: &mut Context, shared: &mut Shared, command: Command) -> Transition {
+        match command {
+            Command::Honey(honey) => {
+                self.honey = honey.clamp(0, console::MAX_HONEY);
+                self.console.print(format!("honey set to {}", self.honey));
+            }
+            Command::Lives(lives) => {
+                self.lives = lives.clamp(0, console::MAX_LIVES);
+                self.console.print(format!("lives set to {}", self.lives));
+            }
+            Command::Spawn { sprite, count } => {
+                if !enemy::ENEMY_SPRITES.contains(&sprite.as_str()) {
+                    self.console.print(format!("unknown enemy {}, try one of {}", sprite, enemy::ENEMY_SPRITES.join(", ")));
+                    return Transition::None;
+                }
+                if count > console::MAX_SPAWN {
+                    self.console.print(format!("spawning {} at most", console::MAX_SPAWN));
+                }
+                let count = count.min(console::MAX_SPAWN);
+                // spawned on top of the level's own enemies, so they don't count towards them
+                for _ in 0..count {
+                    self.spawn_enemy(&sprite);
+                }
+                self.console.print(format!("spawned {} {}", count, sprite));
+            }
+            Command::Hardness(hardness) => {
+                self.hardness = hardness.clamp(1, console::MAX_HARDNESS);
+                self.console.print(format!("hardness set to {}", self.hardness));
+            }
+            Command::UpgradeAll => {
+                for tower in self.towers.iter_mut() {
+                    tower.upgrade();
+                }
+                shared.events.push(GameEvent::Upgrade);
+                self.console.print(format!("upgraded {} towers", self.towers.len()));
+            }
+            Command::Tick(count) => {
+                if count > console::MAX_TICKS {
+                    self.console.print(format!("running {} ticks at most", console::MAX_TICKS));
+                }
+                let count = count.min(console::MAX_TICKS);
+                for tick in 0..count {
+                    let transition = self.tick(ctx, shared);
+                    self.ticks += 1;
+                    if !matches!(transition, Transition::None) {
+                        self.console.print(format!("the level ended after {} ticks", tick + 1));
+                        return transition;
+                    }
+                }
+                self.console.print(format!("ran {} ticks", count));
+            }
+            Command::Seed => match self.level_choice {
+                LevelChoice::Endless => self.console.print(format!("endless seed: {}", self.endless_seed)),
+                LevelChoice::Campaign(_) => self.console.print("campaign levels have no seed".to_string()),
+            },
+            Command::Save => match self.quick_save(ctx, shared) {
+                Ok(()) => self.console.print("quick saved".to_string()),
+                Err(e) => self.console.print(format!("failed to quick save: {}", e)),
+            },
+            Command::Load => return quick_load(ctx, shared),
+            Command::GodMode => {
+                self.god_mode = !self.god_mode;
+                self.console.print(format!("god mode {}", if self.god_mode { "on" } else { "off" }));
+            }
+            Command::Clear => self.console.clear(),
+            Command::Help => {
+                for line in console::HELP.iter() {
+                    self.console.print(line.to_string());
+                }
+            }
+        }
+        Transition::None
+    }
+
+    /// Keys typed while the console is open go to it rather than the game.
+    /// Only the editing keys repeat while held.
+    fn console_key_down(&mut self, ctx: &mut Context, shared: &mut Shared, keycode: KeyCode, action: Option<Action>, repeat: bool) -> Transition {
+        match (keycode, action) {
+            (KeyCode::Back, _) => self.console.backspace(),
+            (KeyCode::Up, _) => self.console.history_back(),
+            (KeyCode::Down, _) => self.console.history_forward(),
+            _ if repeat => {}
+            (KeyCode::Escape, _) | (_, Some(Action::ToggleConsole)) => self.console.close(),
+            (KeyCode::Return, _) | (KeyCode::NumpadEnter, _) => {
+                if let Some(line) = self.console.submit() {
+                    match Command::parse(&line) {
+                        Ok(command) => return self.execute(ctx, shared, command),
+                        Err(e) => self.console.print(e),
+                    }
+                }
+            }
+            _ => {}
+        }
+        Transition::None
+    }
+
+    /// Moves the game forward by one tick.
+    fn tick(&mut self, ctx: &mut Context, shared: &mut Shared) -> Transition {
+        let assets = &shared.assets;
+        let events = &mut shared.events;
+        // update enemies
+        for enemy in self.enemies.iter_mut() {
+            enemy.update(&self.level, assets);
+        }
+        for tower in self.towers.iter_mut() {
+            tower.update(assets);
+        }
+        self.nexus.update(assets);
+
+        // every tower damages the enemies in its range, the ones spawned first first
+        let mut honey_rewarded: i32 = 0;
+        for tower in self.towers.iter_mut() {
+            let mut damage: i32 = tower.get_damage();
+            let mut attacked = false;
+            for enemy in self.enemies.iter_mut() {
+                if damage <= 0 {
+                    break;
+                }
+                if !enemy.is_alive() || !tower.is_in_range(enemy.get_position()) {
+                    continue;
+                }
+
+                let used = enemy.take_damage(damage);
+                damage -= used;
+                tower.attack(used, !enemy.is_alive());
+                attacked = true;
+                if enemy.is_alive() {
+                    events.push(GameEvent::Hit { position: enemy.center(), damage: used });
+                } else {
+                    let honey = enemy.get_honey_reward();
+                    honey_rewarded += honey;
+                    events.push(GameEvent::Kill { position: enemy.center(), damage: used, honey, color: enemy.splat_color() });
+                }
+            }
+            // damage spilling over to the next enemies is still one shot
+            if attacked {
+                events.push(GameEvent::Shot);
+            }
+        }
+
+        // dead enemies stay around until their death animation is over
+        self.enemies.retain(|enemy| !enemy.is_gone(assets));
+        self.score += honey_rewarded * 3;
+        self.honey += honey_rewarded;
+
+        // increase the hardness on every 10 enemies
+        if self.ticks % 70 == 0 {
+            self.hardness += 1;
+        }
+
+        // spawn the next enemy if its time to do so
+        let all_spawned = self.level.enemy_count.is_some_and(|count| self.spawned_enemies >= count);
+        if self.ticks % 7 == 0 && !all_spawned {
+            let sprite = enemy::ENEMY_SPRITES[rand::thread_rng().gen_range(0..enemy::ENEMY_SPRITES.len())];
+            self.spawn_enemy(sprite);
+            self.spawned_enemies += 1;
+        }
+
+        // Check if any new enemies have hit the nexus - if so reduce its health
+        // if the health is leq than 0 stop the game
+        // with several spawns the front enemy is not always the closest one so check them all
+        let enemies_before = self.enemies.len();
+        let nexus = &self.nexus;
+        self.enemies.retain(|enemy| !enemy.is_alive() || !nexus.is_enemy_in(enemy.get_position()));
+        let nexus_hits = (enemies_before - self.enemies.len()) as i32;
+        if nexus_hits > 0 {
+            self.nexus.hit();
+            events.push(GameEvent::NexusHit);
+        }
+        if !self.god_mode {
+            self.lives -= nexus_hits;
+        }
+
+        if self.lives <= 0 {
+            self.finish_level(ctx, shared)
+        } else if all_spawned && self.enemies.is_empty() {
+            self.completed = true;
+            self.finish_level(ctx, shared)
+        } else {
+            Transition::None
+        }
+    }
+
+    /// Records the result and brings up the game over screen.
+    fn finish_level(&mut self, ctx: &mut Context, shared: &mut Shared) -> Transition {
+        self.gameover = true;
+        if !self.completed {
+            shared.events.push(GameEvent::GameOver);
+        }
+        match self.level_choice {
+            LevelChoice::Campaign(level_index) => {
+                let level_path = &shared.campaign.levels[level_index].path;
+                shared.profile.record_result(level_index, level_path, self.score, self.completed);
+            }
+            // endless levels can't be completed, they only keep the best score
+            LevelChoice::Endless => shared.profile.record_result(0, generator::ENDLESS_PROFILE_KEY, self.score, false),
+        }
+        if let Err(e) = shared.profile.save(ctx) {
+            println!("failed to save the profile: {}", e);
+        }
+
+        let result_str = if self.completed {
+            format!("{} complete! Score: {}", self.level.name, self.score)
+        } else {
+            format!("Game over! Score: {}", self.score)
+        };
+        Transition::Push(Box::new(GameOver::new(result_str)))
+    }
+
+    /// The panel of the selected tower, with buttons to upgrade and sell it.
+    fn draw_selected_tower(&mut self, ctx: &mut Context, shared: &mut Shared, index: usize) -> GameResult {
+        let ui = &mut shared.ui;
+        let assets = &mut shared.assets;
+        let tower = &self.towers[index];
+        let info = tower.info().draw(ctx, ui, assets, [8.0, 8.0])?;
+
+        let upgrade_str = format!("Upgrade ({})", tower.honey_to_upgrade());
+        let sell_str = format!("Sell ({})", tower.sell_value());
+        let sizes = [
+            ui.button_size(ctx, assets, &upgrade_str),
+            ui.button_size(ctx, assets, &sell_str),
+        ];
+        let buttons = ui_layout::row([info.x, info.y + info.h + 4.0], &sizes, 4.0);
+        let can_upgrade = tower.honey_to_upgrade() <= self.honey;
+        let can_sell = tower.get_level() > 0;
+        if ui.button(ctx, assets, buttons[0], &upgrade_str, can_upgrade)? {
+            self.ui_actions.push(UiAction::UpgradeTower(index));
+        }
+        if ui.button(ctx, assets, buttons[1], &sell_str, can_sell)? {
+            self.ui_actions.push(UiAction::SellTower(index));
+        }
+        Ok(())
+    }
+}
+
+/// Plays the quick save, in place of whatever level is being played.
+pub fn quick_load(ctx: &mut Context, shared: &mut Shared) -> Transition {
+    let save_game = match SaveGame::load(ctx) {
+        Some(save_game) => save_game,
+        None => {
+            println!("there is no quick save to load");
+            return Transition::None;
+        }
+    };
+    match Playing::from_save(ctx, shared, &save_game) {
+        Ok(playing) => Transition::Unwind { to: level_select::NAME, push: Some(Box::new(playing)) },
+        Err(e) => {
+            println!("failed to load the quick save: {}", e);
+            Transition::None
+        }
+    }
+}
+
+/// A tower on every slot of the level, except for slots on terrain that can't be built on.
+fn build_towers(level: &Level) -> Vec<Tower> {
+    level.tower_slots.iter()
+        .filter_map(|slot| {
+            let terrain = level.tower_terrain(*slot);
+            if !terrain.buildable {
+                let (x, y): (i16, i16) = (*slot).into();
+                println!("tower slot ({}, {}) is not buildable, skipping it", x, y);
+                return None;
+            }
+            Some(Tower::new(*slot, terrain))
+        })
+        .collect()
+}
+
+impl Scene for Playing {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn update(&mut self, ctx: &mut Context, shared: &mut Shared) -> GameResult<Transition> {
+        self.console.update();
+        if self.gameover {
+            // the game over screen only goes back to the level to play it again
+            self.reset_session();
+        }
+        for action in std::mem::take(&mut self.ui_actions) {
+            match action {
+                UiAction::UpgradeTower(index) => self.upgrade_tower(shared, index),
+                UiAction::SellTower(index) => self.sell_tower(index),
+            }
+        }
+
+        let mut transition = Transition::None;
+        let speed = if self.fast_forward { FAST_FORWARD } else { 1 };
+        if Instant::now() - self.last_update >= Duration::from_millis(MILLIS_PER_UPDATE / speed) {
+            // WASD is being typed into the console
+            if !self.console.is_open() {
+                self.scroll_camera(ctx);
+            }
+            if !self.editing {
+                let tick_start = Instant::now();
+                transition = self.tick(ctx, shared);
+                self.debug_overlay.record_tick(tick_start.elapsed());
+            }
+            self.last_update = Instant::now();
+            self.ticks = self.ticks + 1;
+        }
+
+        if shared.settings.effects {
+            for event in shared.events.iter() {
+                self.effects.handle(*event);
+            }
+            self.effects.update(ggez::timer::delta(ctx));
+        } else {
+            self.effects.clear();
+        }
+        Ok(transition)
+    }
+
+    fn draw(&mut self, ctx: &mut Context, shared: &mut Shared) -> GameResult {
+        // the map and everything on it is drawn in world coordinates
+        let mut view = self.camera.view_rect();
+        let shake = self.effects.shake_offset();
+        view.translate([shake.0 / self.camera.zoom(), shake.1 / self.camera.zoom()]);
+        graphics::set_screen_coordinates(ctx, view)?;
+        let screen_cursor = mouse::position(ctx);
+        let cursor = self.camera.screen_to_grid(screen_cursor.x, screen_cursor.y);
+        // enemies are drawn over towers, so they get the tooltip when both are hovered
+        let over_ui = shared.ui.is_over(screen_cursor.x, screen_cursor.y);
+        let hovered_enemy = self.enemies.iter().rposition(|enemy| enemy.is_alive() && enemy.is_under(cursor)).filter(|_| !over_ui);
+        let hovered_tower = self.towers.iter().position(|tower| tower.is_clicking_on(cursor)).filter(|_| !over_ui);
+        let assets = &mut shared.assets;
+        let millis = self.ticks as u64 * MILLIS_PER_UPDATE;
+        self.map_render.draw(ctx, assets, &self.level, millis)?;
+
+        if self.editing {
+            self.nexus.draw(ctx, assets)?;
+            self.editor.draw(ctx, &self.level)?;
+        } else {
+            for enemy in self.enemies.iter() {
+                let show_health_bar = match shared.settings.health_bars {
+                    HealthBars::Always => true,
+                    HealthBars::Damaged => enemy.get_health().is_damaged(),
+                    HealthBars::Hover => enemy.is_under(cursor),
+                };
+                enemy.draw(ctx, assets, show_health_bar)?;
+            }
+
+            for tower in self.towers.iter() {
+                tower.draw(ctx, assets)?;
+            }
+            if let Some(tower) = hovered_tower.or(self.selected_tower) {
+                self.towers[tower].draw_range(ctx)?;
+            }
+
+            self.nexus.draw(ctx, assets)?;
+            self.effects.draw_particles(ctx)?;
+        }
+        self.map_render.draw_above_entities(ctx, assets, &self.level, millis)?;
+        if self.debug {
+            self.debug_overlay.draw_world(ctx, &self.camera, &self.level, &self.enemies, &self.towers, &self.nexus)?;
+        }
+
+        // while the UI stays in place on the screen
+        let screen = self.camera.screen_rect();
+        graphics::set_screen_coordinates(ctx, screen)?;
+        self.effects.draw_overlay(ctx, assets, &self.camera, screen)?;
+        if self.editing {
+            self.editor.draw_status(ctx, assets)?;
+        } else {
+            // levels with a fixed number of enemies show how far along they are
+            let spawned_enemies = self.spawned_enemies;
+            let hud = Hud {
+                score: self.score,
+                lives: self.lives,
+                honey: self.honey,
+                progress: self.level.enemy_count.map(|count| spawned_enemies as f32 / count.max(1) as f32),
+            };
+            self.score_board.draw(ctx, &mut shared.ui, assets, screen, &hud)?;
+
+            if let Some(tower) = self.selected_tower.filter(|_| !self.gameover) {
+                self.draw_selected_tower(ctx, shared, tower)?;
+            }
+            let screen_cursor = [screen_cursor.x, screen_cursor.y];
+            if let Some(enemy) = hovered_enemy {
+                self.enemies[enemy].info(&self.level).draw_at_cursor(ctx, &mut shared.ui, &mut shared.assets, screen_cursor, screen)?;
+            } else if let Some(tower) = hovered_tower.filter(|tower| Some(*tower) != self.selected_tower) {
+                self.towers[tower].info().draw_at_cursor(ctx, &mut shared.ui, &mut shared.assets, screen_cursor, screen)?;
+            }
+        }
+
+        if self.fast_forward && !self.editing {
+            let speed_str = format!("Speed x{}", FAST_FORWARD);
+            let speed_style = TextStyle::new(Color::WHITE).size(20.0).outline(Color::BLACK);
+            let (_, height) = shared.assets.text_dimensions(ctx, &speed_str, &speed_style);
+            shared.ui.label(ctx, &mut shared.assets, &speed_str, [8.0, screen.h - height - 8.0], &speed_style)?;
+        }
+        if self.debug {
+            let stats = DebugStats {
+                ticks: self.ticks,
+                hardness: self.hardness,
+                spawned_enemies: self.spawned_enemies,
+                enemy_count: self.level.enemy_count,
+                cursor,
+            };
+            self.debug_overlay.draw_stats(ctx, &mut shared.ui, &mut shared.assets, screen, &stats, &self.enemies)?;
+        }
+        if self.console.is_open() {
+            self.console_panel.draw(ctx, &mut shared.ui, &mut shared.assets, screen, &self.console)?;
+        }
+        Ok(())
+    }
+
+    fn music(&self) -> Option<&'static str> {
+        Some(audio::LEVEL_MUSIC)
+    }
+
+    fn mouse_button_down(&mut self, _ctx: &mut Context, shared: &mut Shared, button: MouseButton, x: f32, y: f32) {
+        let click_pos = self.camera.screen_to_grid(x, y);
+        if button == MouseButton::Middle {
+            return;
+        }
+
+        if self.editing {
+            let cell: (i16, i16) = click_pos.into();
+            self.editor.click(&mut self.level, cell, button);
+            self.map_render.invalidate();
+            self.nexus = Nexus::new(&self.level.nexus);
+            return;
+        }
+
+        // clicking a tower selects it, clicking anywhere else closes its info panel
+        self.selected_tower = self.towers.iter().position(|tower| tower.is_clicking_on(click_pos));
+
+        if let Some(index) = self.selected_tower {
+            if button == MouseButton::Right {
+                self.sell_tower(index);
+            } else {
+                self.upgrade_tower(shared, index);
+            }
+        }
+    }
+
+    fn mouse_motion(&mut self, ctx: &mut Context, _shared: &mut Shared, x: f32, y: f32, dx: f32, dy: f32) {
+        // drag the map along with the middle mouse button
+        if mouse::button_pressed(ctx, MouseButton::Middle) {
+            self.camera.pan(-dx, -dy);
+            return;
+        }
+
+        if !self.editing {
+            return;
+        }
+
+        let cell: (i16, i16) = self.camera.screen_to_grid(x, y).into();
+        for button in [MouseButton::Left, MouseButton::Right].iter() {
+            if mouse::button_pressed(ctx, *button) {
+                self.editor.drag(&mut self.level, cell, *button);
+                self.map_render.invalidate();
+            }
+        }
+    }
+
+    fn mouse_wheel(&mut self, ctx: &mut Context, _shared: &mut Shared, _x: f32, y: f32) {
+        if y != 0.0 {
+            let cursor = mouse::position(ctx);
+            let factor = if y > 0.0 { ZOOM_STEP } else { 1.0 / ZOOM_STEP };
+            self.camera.zoom_at(factor, (cursor.x, cursor.y));
+        }
+    }
+
+    fn key_down(&mut self, ctx: &mut Context, shared: &mut Shared, keycode: KeyCode, keymods: KeyMods, repeat: bool) -> Transition {
+        let action = shared.bindings.action_for(keycode);
+        if self.console.is_open() {
+            return self.console_key_down(ctx, shared, keycode, action, repeat);
+        }
+        // a held key would flip toggles back and forth
+        if repeat {
+            return Transition::None;
+        }
+        match (keycode, action) {
+            (_, Some(Action::ToggleConsole)) => self.console.open(),
+            (KeyCode::F2, _) => {
+                self.editing = !self.editing;
+                // the level may have changed, so start it over with the new layout
+                if !self.editing {
+                    self.reset_session();
+                }
+            }
+            (KeyCode::Escape, _) if self.editing => {
+                self.editing = false;
+                self.reset_session();
+            }
+            // plain S pans the camera
+            (KeyCode::S, _) if self.editing && keymods.contains(KeyMods::CTRL) => {
+                if let Err(e) = self.save_level(shared) {
+                    println!("failed to save the level: {}", e);
+                }
+            }
+            (_, Some(Action::ToggleDebug)) => self.debug = !self.debug,
+            _ if self.editing => self.editor.key_down(keycode),
+            (_, Some(Action::Pause)) => return Transition::Push(Box::new(Paused::new())),
+            (_, Some(Action::SpeedUp)) => self.fast_forward = !self.fast_forward,
+            (_, Some(Action::UpgradeSelected)) => {
+                if let Some(index) = self.selected_tower {
+                    self.upgrade_tower(shared, index);
+                }
+            }
+            (_, Some(Action::SellSelected)) => {
+                if let Some(index) = self.selected_tower {
+                    self.sell_tower(index);
+                }
+            }
+            (_, Some(Action::CycleTowers)) => self.cycle_towers(),
+            (_, Some(Action::QuickSave)) => match self.quick_save(ctx, shared) {
+                Ok(()) => println!("quick saved"),
+                Err(e) => println!("failed to quick save: {}", e),
+            },
+            (_, Some(Action::QuickLoad)) => return quick_load(ctx, shared),
+            (KeyCode::H, _) => {
+                let mut settings = shared.settings;
+                settings.health_bars = settings.health_bars.next();
+                println!("health bars: {}", settings.health_bars.key());
+                shared.set_settings(ctx, settings);
+            }
+            (KeyCode::Equals, _) | (KeyCode::Minus, _) => {
+                let factor = if keycode == KeyCode::Equals { ZOOM_STEP } else { 1.0 / ZOOM_STEP };
+                let screen = self.camera.screen_rect();
+                self.camera.zoom_at(factor, (screen.w / 2.0, screen.h / 2.0));
+            }
+            _ => {}
+        }
+        Transition::None
+    }
+
+    fn text_input(&mut self, _ctx: &mut Context, _shared: &mut Shared, character: char) {
+        self.console.type_char(character);
+    }
+
+    fn resize(&mut self, width: f32, height: f32) {
+        self.camera.resize((width, height));
+    }
+
+    fn resources_changed(&mut self, ctx: &mut Context, shared: &mut Shared, changed: &[String]) {
+        if changed.iter().any(|path| !path.starts_with("/levels/")) {
+            if let Err(e) = self.reload_assets(ctx, shared) {
+                println!("failed to reload the level's tile images: {}", e);
+            }
+        }
+        if changed.iter().any(|path| path.starts_with("/levels/")) {
+            if self.editing {
+                // the level on screen has edits that would be lost
+                println!("not reloading the level while it is edited");
+            } else if let Err(e) = self.reload_level(ctx, shared) {
+                println!("failed to reload the level, keeping the old one: {}", e);
+            }
+        }
+    }
+}
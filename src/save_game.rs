@@ -0,0 +1,152 @@
+//! A snapshot of a level being played, for quick saving and loading. It only records what
+//! changes while playing; the level itself is loaded again from its file, or generated
+//! again from its seed.
+
+use ggez::{filesystem, Context, GameResult};
+use serde_json::{json, Value};
+use std::io::Write;
+use crate::health::Health;
+
+const QUICKSAVE_PATH: &str = "/quicksave.json";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SavedTower {
+    pub position: (i16, i16),
+    pub level: i32,
+    pub kills: i32,
+    pub damage_dealt: i32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SavedEnemy {
+    pub sprite: String,
+    pub hardness: i32,
+    pub health: Health,
+    pub spawn_index: usize,
+    pub checkpoint_index: usize,
+    pub position: (f32, f32),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SaveGame {
+    /// The path of a campaign level, or `generator::ENDLESS_PROFILE_KEY`.
+    pub level_path: String,
+    pub endless_seed: u64,
+    pub score: i32,
+    pub honey: i32,
+    pub lives: i32,
+    pub ticks: i32,
+    pub hardness: i32,
+    pub spawned_enemies: i32,
+    pub towers: Vec<SavedTower>,
+    pub enemies: Vec<SavedEnemy>,
+}
+
+impl SaveGame {
+    /// The quick save, if there is one that can be read.
+    pub fn load(ctx: &mut Context) -> Option<Self> {
+        let save_file = filesystem::open(ctx, QUICKSAVE_PATH).ok()?;
+        let json: Value = match serde_json::from_reader(save_file) {
+            Ok(json) => json,
+            Err(e) => {
+                println!("quick save is corrupted: {}", e);
+                return None;
+            }
+        };
+        let save_game = SaveGame::from_json(&json);
+        if save_game.is_none() {
+            println!("quick save is missing fields");
+        }
+        save_game
+    }
+
+    pub fn save(&self, ctx: &mut Context) -> GameResult {
+        let mut save_file = filesystem::create(ctx, QUICKSAVE_PATH)?;
+        save_file.write_all(self.to_json().to_string().as_bytes())?;
+        Ok(())
+    }
+
+    pub fn from_json(json: &Value) -> Option<Self> {
+        let int = |value: &Value| value.as_i64().map(|value| value as i32);
+        let towers = json["towers"].as_array()?.iter()
+            .map(|tower| Some(SavedTower {
+                position: (tower["x"].as_i64()? as i16, tower["y"].as_i64()? as i16),
+                level: int(&tower["level"])?,
+                kills: int(&tower["kills"]).unwrap_or(0),
+                damage_dealt: int(&tower["damage_dealt"]).unwrap_or(0),
+            }))
+            .collect::<Option<Vec<SavedTower>>>()?;
+        let enemies = json["enemies"].as_array()?.iter()
+            .map(|enemy| {
+                let health = Health {
+                    current: int(&enemy["health"])?,
+                    max: int(&enemy["max_health"])?,
+                    shield: int(&enemy["shield"]).unwrap_or(0),
+                    max_shield: int(&enemy["max_shield"]).unwrap_or(0),
+                    armor: int(&enemy["armor"]).unwrap_or(0),
+                };
+                Some(SavedEnemy {
+                    sprite: enemy["sprite"].as_str()?.to_string(),
+                    hardness: int(&enemy["hardness"])?,
+                    health,
+                    spawn_index: enemy["spawn"].as_u64()? as usize,
+                    checkpoint_index: enemy["checkpoint"].as_u64()? as usize,
+                    position: (enemy["x"].as_f64()? as f32, enemy["y"].as_f64()? as f32),
+                })
+            })
+            .collect::<Option<Vec<SavedEnemy>>>()?;
+
+        Some(SaveGame {
+            level_path: json["level"].as_str()?.to_string(),
+            endless_seed: json["endless_seed"].as_u64().unwrap_or(0),
+            score: int(&json["score"])?,
+            honey: int(&json["honey"])?,
+            lives: int(&json["lives"])?,
+            ticks: int(&json["ticks"])?,
+            hardness: int(&json["hardness"])?,
+            spawned_enemies: int(&json["spawned_enemies"])?,
+            towers,
+            enemies,
+        })
+    }
+
+    pub fn to_json(&self) -> Value {
+        let towers: Vec<Value> = self.towers.iter()
+            .map(|tower| json!({
+                "x": tower.position.0,
+                "y": tower.position.1,
+                "level": tower.level,
+                "kills": tower.kills,
+                "damage_dealt": tower.damage_dealt,
+            }))
+            .collect();
+        let enemies: Vec<Value> = self.enemies.iter()
+            .map(|enemy| json!({
+                "sprite": enemy.sprite,
+                "hardness": enemy.hardness,
+                "health": enemy.health.current,
+                "max_health": enemy.health.max,
+                "shield": enemy.health.shield,
+                "max_shield": enemy.health.max_shield,
+                "armor": enemy.health.armor,
+                "spawn": enemy.spawn_index,
+                "checkpoint": enemy.checkpoint_index,
+                "x": enemy.position.0,
+                "y": enemy.position.1,
+            }))
+            .collect();
+
+        json!({
+            "level": self.level_path,
+            "endless_seed": self.endless_seed,
+            "score": self.score,
+            "honey": self.honey,
+            "lives": self.lives,
+            "ticks": self.ticks,
+            "hardness": self.hardness,
+            "spawned_enemies": self.spawned_enemies,
+            "towers": towers,
+            "enemies": enemies,
+        })
+    }
+}
@@ -0,0 +1,298 @@
+use ggez::{event, graphics, Context, GameResult};
+use ggez::event::{KeyCode, KeyMods};
+use ggez::graphics::{Color, Rect};
+use ggez::input::mouse::MouseButton;
+use crate::assets::Assets;
+use crate::audio::AudioManager;
+use crate::campaign::{Campaign, Profile};
+use crate::game_event::GameEvent;
+use crate::hot_reload::ResourceWatcher;
+use crate::input::Bindings;
+use crate::settings::Settings;
+use crate::settings_menu::VOLUME_STEP;
+use crate::ui::Ui;
+
+/// What a scene wants to happen to the stack after handling an event.
+pub enum Transition {
+    None,
+    Push(Box<dyn Scene>),
+    Pop,
+    /// Pops scenes until the one named `to` is on top, then pushes `push` if there is one.
+    Unwind { to: &'static str, push: Option<Box<dyn Scene>> },
+    Quit,
+}
+
+/// A screen of the game. Only the scene on top of the stack gets updated and gets the
+/// input; the ones below it are drawn when it's an overlay.
+pub trait Scene {
+    /// Names the scene for `Transition::Unwind`.
+    fn name(&self) -> &'static str;
+
+    fn update(&mut self, ctx: &mut Context, shared: &mut Shared) -> GameResult<Transition>;
+
+    /// Draws in screen coordinates, which the scene is free to change.
+    fn draw(&mut self, ctx: &mut Context, shared: &mut Shared) -> GameResult;
+
+    /// Overlays are drawn over the scene below them, which stays frozen while they're up.
+    fn is_overlay(&self) -> bool {
+        false
+    }
+
+    /// The music to play while the scene is on top. Scenes without music keep the music
+    /// of the scene below them.
+    fn music(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Only gets the clicks that didn't land on the UI.
+    fn mouse_button_down(&mut self, _ctx: &mut Context, _shared: &mut Shared, _button: MouseButton, _x: f32, _y: f32) {}
+
+    fn mouse_motion(&mut self, _ctx: &mut Context, _shared: &mut Shared, _x: f32, _y: f32, _dx: f32, _dy: f32) {}
+
+    fn mouse_wheel(&mut self, _ctx: &mut Context, _shared: &mut Shared, _x: f32, _y: f32) {}
+
+    /// `repeat` is set for the presses a held key repeats, which most keys ignore.
+    fn key_down(&mut self, _ctx: &mut Context, _shared: &mut Shared, _keycode: KeyCode, _keymods: KeyMods, _repeat: bool) -> Transition {
+        Transition::None
+    }
+
+    /// A character typed, after the key press that typed it.
+    fn text_input(&mut self, _ctx: &mut Context, _shared: &mut Shared, _character: char) {}
+
+    /// Every scene on the stack hears about the window changing size.
+    fn resize(&mut self, _width: f32, _height: f32) {}
+
+    /// Every scene on the stack hears about changed resources, after the assets were
+    /// reloaded.
+    fn resources_changed(&mut self, _ctx: &mut Context, _shared: &mut Shared, _changed: &[String]) {}
+}
+
+/// What the scenes share and keep between them.
+pub struct Shared {
+    pub assets: Assets,
+    pub campaign: Campaign,
+    pub profile: Profile,
+    pub settings: Settings,
+    pub bindings: Bindings,
+    pub audio: AudioManager,
+    pub ui: Ui,
+    /// The window, in screen coordinates.
+    pub screen: Rect,
+    /// What happened since the last update, for the sounds to play.
+    pub events: Vec<GameEvent>,
+    resource_watcher: ResourceWatcher,
+}
+
+impl Shared {
+    /// `audio_enabled` is whether the context has an audio device to play on.
+    pub fn new(ctx: &mut Context, audio_enabled: bool, resource_watcher: ResourceWatcher) -> GameResult<Self> {
+        let settings = Settings::load(ctx);
+        let mut assets = Assets::new(ctx)?;
+        assets.set_text_scale(settings.text_scale);
+        let audio = AudioManager::new(ctx, audio_enabled, settings)?;
+        let campaign = Campaign::discover(ctx)?;
+        let profile = Profile::load(ctx);
+        let bindings = Bindings::load(ctx);
+        let (width, height) = graphics::drawable_size(ctx);
+
+        Ok(Shared {
+            assets,
+            campaign,
+            profile,
+            settings,
+            bindings,
+            audio,
+            ui: Ui::new(),
+            screen: Rect::new(0.0, 0.0, width, height),
+            events: Vec::new(),
+            resource_watcher,
+        })
+    }
+
+    /// Puts new settings into effect and saves them.
+    pub fn set_settings(&mut self, ctx: &mut Context, settings: Settings) {
+        self.settings = settings;
+        self.audio.set_settings(settings);
+        self.assets.set_text_scale(settings.text_scale);
+        if let Err(e) = settings.save(ctx) {
+            println!("failed to save the settings: {}", e);
+        }
+    }
+
+    pub fn set_bindings(&mut self, ctx: &mut Context, bindings: Bindings) {
+        self.bindings = bindings;
+        if let Err(e) = self.bindings.save(ctx) {
+            println!("failed to save the key bindings: {}", e);
+        }
+    }
+
+    fn reload_assets(&mut self, ctx: &mut Context) -> GameResult {
+        let mut assets = Assets::new(ctx)?;
+        assets.set_text_scale(self.settings.text_scale);
+        self.assets = assets;
+        self.audio.reload(ctx)
+    }
+}
+
+/// Routes the ggez events to the scene on top of the stack.
+pub struct SceneStack {
+    shared: Shared,
+    scenes: Vec<Box<dyn Scene>>,
+}
+
+impl SceneStack {
+    pub fn new(shared: Shared, first: Box<dyn Scene>) -> Self {
+        SceneStack { shared, scenes: vec![first] }
+    }
+
+    fn apply(&mut self, ctx: &mut Context, transition: Transition) {
+        match transition {
+            Transition::None => {}
+            Transition::Push(scene) => self.scenes.push(scene),
+            Transition::Pop => {
+                self.scenes.pop();
+            }
+            Transition::Unwind { to, push } => {
+                while self.scenes.last().is_some_and(|scene| scene.name() != to) {
+                    self.scenes.pop();
+                }
+                if self.scenes.is_empty() {
+                    println!("no {} scene to go back to", to);
+                }
+                self.scenes.extend(push);
+            }
+            Transition::Quit => event::quit(ctx),
+        }
+        if self.scenes.is_empty() {
+            event::quit(ctx);
+        }
+    }
+
+    /// Reloads the assets if any of them changed and lets the scenes reload the rest.
+    fn hot_reload(&mut self, ctx: &mut Context, changed: &[String]) {
+        println!("resources changed: {}", changed.join(", "));
+        if changed.iter().any(|path| !path.starts_with("/levels/")) {
+            if let Err(e) = self.shared.reload_assets(ctx) {
+                println!("failed to reload the assets, keeping the old ones: {}", e);
+            }
+        }
+        for scene in self.scenes.iter_mut() {
+            scene.resources_changed(ctx, &mut self.shared, changed);
+        }
+    }
+}
+
+impl event::EventHandler<ggez::GameError> for SceneStack {
+    fn update(&mut self, ctx: &mut Context) -> GameResult {
+        let changed = self.shared.resource_watcher.poll();
+        if !changed.is_empty() {
+            self.hot_reload(ctx, &changed);
+        }
+
+        if let Some(scene) = self.scenes.last_mut() {
+            let transition = scene.update(ctx, &mut self.shared)?;
+            self.apply(ctx, transition);
+        }
+
+        let shared = &mut self.shared;
+        for event in shared.events.drain(..) {
+            shared.audio.play(ctx, event);
+        }
+        if let Some(music) = self.scenes.iter().rev().find_map(|scene| scene.music()) {
+            shared.audio.play_music(ctx, music);
+        }
+        shared.audio.update(ctx, ggez::timer::delta(ctx));
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        graphics::clear(ctx, Color::BLACK);
+        // the top scene and every overlay under it down to the first full screen scene
+        let top = self.scenes.len().saturating_sub(1);
+        let mut bottom = top;
+        while bottom > 0 && self.scenes[bottom].is_overlay() {
+            bottom -= 1;
+        }
+        for index in bottom..self.scenes.len() {
+            graphics::set_screen_coordinates(ctx, self.shared.screen)?;
+            self.shared.ui.set_interactive(index == top);
+            self.scenes[index].draw(ctx, &mut self.shared)?;
+        }
+
+        self.shared.ui.end_frame();
+        self.shared.assets.end_frame();
+        graphics::present(ctx)?;
+        ggez::timer::yield_now();
+        Ok(())
+    }
+
+    fn mouse_button_down_event(&mut self, ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
+        // the UI takes the clicks on it
+        let ui = &mut self.shared.ui;
+        let over_ui = if button == MouseButton::Left { ui.mouse_down(x, y) } else { ui.is_over(x, y) };
+        if over_ui {
+            return;
+        }
+        if let Some(scene) = self.scenes.last_mut() {
+            scene.mouse_button_down(ctx, &mut self.shared, button, x, y);
+        }
+    }
+
+    fn mouse_button_up_event(&mut self, _ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
+        if button == MouseButton::Left {
+            self.shared.ui.mouse_up(x, y);
+        }
+    }
+
+    fn mouse_motion_event(&mut self, ctx: &mut Context, x: f32, y: f32, dx: f32, dy: f32) {
+        self.shared.ui.mouse_motion(x, y);
+        if let Some(scene) = self.scenes.last_mut() {
+            scene.mouse_motion(ctx, &mut self.shared, x, y, dx, dy);
+        }
+    }
+
+    fn mouse_wheel_event(&mut self, ctx: &mut Context, x: f32, y: f32) {
+        if let Some(scene) = self.scenes.last_mut() {
+            scene.mouse_wheel(ctx, &mut self.shared, x, y);
+        }
+    }
+
+    fn resize_event(&mut self, _ctx: &mut Context, width: f32, height: f32) {
+        self.shared.screen = Rect::new(0.0, 0.0, width, height);
+        for scene in self.scenes.iter_mut() {
+            scene.resize(width, height);
+        }
+    }
+
+    fn text_input_event(&mut self, ctx: &mut Context, character: char) {
+        if let Some(scene) = self.scenes.last_mut() {
+            scene.text_input(ctx, &mut self.shared, character);
+        }
+    }
+
+    fn key_down_event(&mut self, ctx: &mut Context, keycode: KeyCode, keymods: KeyMods, repeat: bool) {
+        match keycode {
+            // F7 and F8 turn the master volume down and up, with shift the music and with
+            // ctrl the sound effects, on every screen
+            KeyCode::F7 | KeyCode::F8 => {
+                let step = if keycode == KeyCode::F8 { VOLUME_STEP } else { -VOLUME_STEP };
+                let mut settings = self.shared.settings;
+                let volume = if keymods.contains(KeyMods::SHIFT) {
+                    &mut settings.music_volume
+                } else if keymods.contains(KeyMods::CTRL) {
+                    &mut settings.sfx_volume
+                } else {
+                    &mut settings.master_volume
+                };
+                *volume = (*volume + step).clamp(0.0, 1.0);
+                self.shared.set_settings(ctx, settings);
+            }
+            _ => {
+                if let Some(scene) = self.scenes.last_mut() {
+                    let transition = scene.key_down(ctx, &mut self.shared, keycode, keymods, repeat);
+                    self.apply(ctx, transition);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,314 @@
+//! Builds levels from color-coded schema images with one pixel per grid cell, so a level can
+//! be sketched in any paint program:
+//!
+//! | color              | cell                                                  |
+//! |--------------------|-------------------------------------------------------|
+//! | `#00FF00` green    | grass                                                 |
+//! | `#FFFF00` yellow   | grass with decor                                      |
+//! | `#00FFFF` cyan     | water                                                 |
+//! | `#FF0000` red      | road, two cells wide                                  |
+//! | `#FFFFFF` white    | spawn, the top left cell of a road at the map's edge  |
+//! | `#FF00FF` magenta  | the nexus pad, the road has to lead into it           |
+//! | `#0000FF` blue     | tower slot, a 2x2 block per tower                     |
+//!
+//! Schemas without spawn or nexus pixels are read the way the first ones were drawn: every
+//! road running into the map's edge is a spawn and a blue block bigger than 2x2 is the nexus.
+//!
+//! Enemy routes are traced along the road from every spawn to the nexus and tile sprites
+//! are picked from the cell's neighbours.
+
+use ggez::{GameError, GameResult};
+use std::collections::VecDeque;
+use crate::autotile;
+use crate::level::{Level, NexusLayout, Spawn};
+use crate::movement_helpers::{GridPosition, RectangleBorder};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Cell {
+    Grass,
+    Decor,
+    Water,
+    Road,
+    Spawn,
+    Nexus,
+    TowerSlot,
+}
+
+const PALETTE: [([u8; 3], Cell); 7] = [
+    ([0, 255, 0], Cell::Grass),
+    ([255, 255, 0], Cell::Decor),
+    ([0, 255, 255], Cell::Water),
+    ([255, 0, 0], Cell::Road),
+    ([255, 255, 255], Cell::Spawn),
+    ([255, 0, 255], Cell::Nexus),
+    ([0, 0, 255], Cell::TowerSlot),
+];
+
+struct Schema {
+    width: i16,
+    height: i16,
+    cells: Vec<Cell>,
+}
+
+impl Schema {
+    fn get(&self, x: i16, y: i16) -> Option<Cell> {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return None;
+        }
+        Some(self.cells[y as usize * self.width as usize + x as usize])
+    }
+
+    fn is_road(&self, x: i16, y: i16) -> bool {
+        match self.get(x, y) {
+            Some(cell) => cell == Cell::Road || cell == Cell::Spawn || cell == Cell::Nexus,
+            // roads may run off the map
+            None => true,
+        }
+    }
+
+    fn is_walkable(&self, x: i16, y: i16) -> bool {
+        self.get(x, y).is_some() && self.is_road(x, y)
+    }
+
+    /// Enemies are two cells wide, so they can stand where the 2x2 block starting at the cell is road.
+    fn is_lane(&self, x: i16, y: i16) -> bool {
+        self.is_walkable(x, y) && self.is_walkable(x + 1, y) && self.is_walkable(x, y + 1) && self.is_walkable(x + 1, y + 1)
+    }
+
+    fn positions_of(&self, cell: Cell) -> Vec<(i16, i16)> {
+        let mut positions: Vec<(i16, i16)> = Vec::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.get(x, y) == Some(cell) {
+                    positions.push((x, y));
+                }
+            }
+        }
+        positions
+    }
+}
+
+fn schema_error(source: &str, message: &str) -> GameError {
+    GameError::ResourceLoadError(format!("level schema {}: {}", source, message))
+}
+
+/// Builds a level from the bytes of a schema image. The level is named after `source`
+/// and starts with the default honey and lives.
+pub fn load_schema(bytes: &[u8], source: &str) -> GameResult<Level> {
+    let image = image::load_from_memory(bytes)
+        .map_err(|e| schema_error(source, &format!("invalid image: {}", e)))?
+        .into_rgba8();
+    let (width, height) = image.dimensions();
+    if width > i16::MAX as u32 || height > i16::MAX as u32 {
+        return Err(schema_error(source, &format!("the image can be at most {} pixels a side", i16::MAX)));
+    }
+
+    let mut cells: Vec<Cell> = Vec::with_capacity((width * height) as usize);
+    for (x, y, pixel) in image.enumerate_pixels() {
+        let rgb = [pixel[0], pixel[1], pixel[2]];
+        let cell = PALETTE.iter()
+            .find(|(color, _)| *color == rgb)
+            .map(|(_, cell)| *cell)
+            .ok_or_else(|| schema_error(source, &format!(
+                "unknown color #{:02X}{:02X}{:02X} at ({}, {})", rgb[0], rgb[1], rgb[2], x, y
+            )))?;
+        cells.push(cell);
+    }
+    let mut schema = Schema { width: width as i16, height: height as i16, cells };
+    if schema.positions_of(Cell::Nexus).is_empty() {
+        mark_nexus_block(&mut schema);
+    }
+    if schema.positions_of(Cell::Spawn).is_empty() {
+        mark_edge_spawns(&mut schema);
+    }
+
+    let nexus_cells = schema.positions_of(Cell::Nexus);
+    if nexus_cells.is_empty() {
+        return Err(schema_error(source, "there is no nexus"));
+    }
+    let nexus_top_left = (
+        nexus_cells.iter().map(|(x, _)| *x).min().unwrap(),
+        nexus_cells.iter().map(|(_, y)| *y).min().unwrap(),
+    );
+    let nexus_bot_right = (
+        nexus_cells.iter().map(|(x, _)| *x).max().unwrap(),
+        nexus_cells.iter().map(|(_, y)| *y).max().unwrap(),
+    );
+    let nexus_borders = RectangleBorder::new(nexus_top_left.into(), nexus_bot_right.into());
+    let nexus = NexusLayout {
+        position: GridPosition::new(
+            (nexus_top_left.0 + nexus_bot_right.0) as f32 / 2.0,
+            (nexus_top_left.1 + nexus_bot_right.1) as f32 / 2.0,
+        ),
+        render_position: GridPosition::new(nexus_top_left.0 as f32 + 0.5, nexus_top_left.1 as f32 + 0.5),
+        borders: nexus_borders,
+    };
+
+    let name = source.rsplit('/').next().unwrap_or(source).trim_end_matches(".png").to_string();
+    let mut level = Level::new(name, (schema.width, schema.height), nexus);
+
+    for (x, y) in schema.positions_of(Cell::Spawn) {
+        let lane = [(x, y), (x - 1, y), (x, y - 1), (x - 1, y - 1)].iter()
+            .copied()
+            .find(|(x, y)| schema.is_lane(*x, *y))
+            .ok_or_else(|| schema_error(source, &format!("spawn at ({}, {}) is not on a two cells wide road", x, y)))?;
+        let path = trace_route(&schema, lane, &nexus_borders)
+            .ok_or_else(|| schema_error(source, &format!("the road from the spawn at ({}, {}) does not reach the nexus", x, y)))?;
+        level.spawns.push(Spawn { position: lane.into(), path });
+    }
+    if level.spawns.is_empty() {
+        return Err(schema_error(source, "there is no spawn"));
+    }
+
+    level.tower_slots = find_tower_slots(&schema);
+
+    for y in 0..schema.height {
+        for x in 0..schema.width {
+            let tile = level.tile_mut(x, y).unwrap();
+            match schema.get(x, y).unwrap() {
+                Cell::Grass | Cell::TowerSlot => tile.sprite = autotile::GRASS.to_string(),
+                Cell::Decor => {
+                    tile.sprite = autotile::GRASS.to_string();
+                    tile.decor = Some(autotile::decor_sprite(x, y).to_string());
+                }
+                Cell::Water => tile.sprite = autotile::WATER.to_string(),
+                Cell::Road | Cell::Spawn | Cell::Nexus => {
+                    tile.sprite = autotile::road_sprite(|x, y| schema.is_road(x, y), x, y).to_string();
+                }
+            }
+        }
+    }
+
+    for slot in level.tower_slots.clone() {
+        let (x, y): (i16, i16) = slot.into();
+        for (dx, dy, sprite) in autotile::TOWER_PAD.iter() {
+            if let Some(tile) = level.tile_mut(x + dx, y + dy) {
+                tile.sprite = sprite.to_string();
+                tile.decor = None;
+            }
+        }
+    }
+
+    Ok(level)
+}
+
+/// Finds the shortest way along the road from `start` into the nexus and returns its
+/// corners as checkpoints, ending with the first cell inside the nexus.
+fn trace_route(schema: &Schema, start: (i16, i16), nexus: &RectangleBorder) -> Option<Vec<GridPosition>> {
+    let width = schema.width as usize;
+    let index = |(x, y): (i16, i16)| y as usize * width + x as usize;
+
+    let mut previous: Vec<Option<(i16, i16)>> = vec![None; schema.cells.len()];
+    let mut queue: VecDeque<(i16, i16)> = VecDeque::new();
+    queue.push_back(start);
+    previous[index(start)] = Some(start);
+
+    let mut end: Option<(i16, i16)> = None;
+    while let Some((x, y)) = queue.pop_front() {
+        if nexus.is_it_in((x, y).into()) {
+            end = Some((x, y));
+            break;
+        }
+        for next in [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)].iter() {
+            if schema.is_lane(next.0, next.1) && previous[index(*next)].is_none() {
+                previous[index(*next)] = Some((x, y));
+                queue.push_back(*next);
+            }
+        }
+    }
+
+    let mut cells: Vec<(i16, i16)> = vec![end?];
+    while *cells.last().unwrap() != start {
+        cells.push(previous[index(*cells.last().unwrap())].unwrap());
+    }
+    cells.reverse();
+
+    let mut checkpoints: Vec<GridPosition> = Vec::new();
+    for window in cells.windows(3) {
+        let (a, b, c) = (window[0], window[1], window[2]);
+        let turns = (b.0 - a.0, b.1 - a.1) != (c.0 - b.0, c.1 - b.1);
+        if turns {
+            checkpoints.push(b.into());
+        }
+    }
+    checkpoints.push((*cells.last().unwrap()).into());
+    Some(checkpoints)
+}
+
+/// The connected blocks of `cell` pixels.
+fn blocks_of(schema: &Schema, cell: Cell) -> Vec<Vec<(i16, i16)>> {
+    let mut visited: Vec<bool> = vec![false; schema.cells.len()];
+    let mut blocks: Vec<Vec<(i16, i16)>> = Vec::new();
+
+    for (x, y) in schema.positions_of(cell) {
+        let start_index = y as usize * schema.width as usize + x as usize;
+        if visited[start_index] {
+            continue;
+        }
+
+        let mut block: Vec<(i16, i16)> = Vec::new();
+        let mut stack: Vec<(i16, i16)> = vec![(x, y)];
+        visited[start_index] = true;
+        while let Some((x, y)) = stack.pop() {
+            block.push((x, y));
+            for (nx, ny) in [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)].iter().copied() {
+                if schema.get(nx, ny) == Some(cell) {
+                    let index = ny as usize * schema.width as usize + nx as usize;
+                    if !visited[index] {
+                        visited[index] = true;
+                        stack.push((nx, ny));
+                    }
+                }
+            }
+        }
+        blocks.push(block);
+    }
+
+    blocks
+}
+
+/// Every connected block of tower slot pixels is one slot, centered on its bottom right cell.
+fn find_tower_slots(schema: &Schema) -> Vec<GridPosition> {
+    blocks_of(schema, Cell::TowerSlot).iter()
+        .map(|block| {
+            let bot_right = block.iter().fold(block[0], |(max_x, max_y), (x, y)| (max_x.max(*x), max_y.max(*y)));
+            bot_right.into()
+        })
+        .collect()
+}
+
+/// Turns the blue blocks too big to be a tower slot into the nexus.
+fn mark_nexus_block(schema: &mut Schema) {
+    for block in blocks_of(schema, Cell::TowerSlot) {
+        if block.len() > 4 {
+            for (x, y) in block {
+                schema.cells[y as usize * schema.width as usize + x as usize] = Cell::Nexus;
+            }
+        }
+    }
+}
+
+/// Puts a spawn on the first cell of every road running into the map's edge.
+fn mark_edge_spawns(schema: &mut Schema) {
+    let (width, height) = (schema.width, schema.height);
+    let mut edge: Vec<((i16, i16), (i16, i16))> = Vec::new();
+    for y in 0..height {
+        edge.push(((0, y), (0, y - 1)));
+        edge.push(((width - 1, y), (width - 1, y - 1)));
+    }
+    for x in 0..width {
+        edge.push(((x, 0), (x - 1, 0)));
+        edge.push(((x, height - 1), (x - 1, height - 1)));
+    }
+
+    let mut spawns: Vec<(i16, i16)> = Vec::new();
+    for ((x, y), (before_x, before_y)) in edge {
+        let starts_road = schema.get(x, y) == Some(Cell::Road) && schema.get(before_x, before_y) != Some(Cell::Road);
+        if starts_road && !spawns.contains(&(x, y)) {
+            spawns.push((x, y));
+        }
+    }
+    for (x, y) in spawns {
+        schema.cells[y as usize * width as usize + x as usize] = Cell::Spawn;
+    }
+}
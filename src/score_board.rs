@@ -1,77 +1,74 @@
 use ggez::{graphics, Context, GameResult};
-use crate::movement_helpers::{GridPosition, Direction};
 use crate::assets::Assets;
+use crate::manifest::AssetCategory;
 use ggez::graphics::Color;
+use crate::text::TextStyle;
+use crate::ui::Ui;
+use crate::ui_layout::{self, Anchor};
 
-pub struct ScoreBoard {
-    label_render_position: GridPosition,
-    score_text_render_position: GridPosition,
-    honey_text_render_position: GridPosition,
-    honey_image_render_position: GridPosition,
-    lives_text_render_position: GridPosition,
-    lives_hearts_render_position: GridPosition,
+const HEIGHT: f32 = 40.0;
+const PADDING: f32 = 16.0;
+const SPACING: f32 = 10.0;
+const HONEY_ICON_SIZE: f32 = 24.0;
+const HEART_SIZE: f32 = 16.0;
+const PROGRESS_HEIGHT: f32 = 6.0;
+
+/// What the score board shows.
+pub struct Hud {
+    pub score: i32,
+    pub lives: i32,
+    pub honey: i32,
+    /// How far along a level with a fixed number of enemies is, from 0 to 1.
+    pub progress: Option<f32>,
 }
 
+/// The HUD with the score, honey and lives, centered at the top of the screen, and below
+/// it how many of the level's enemies have come out yet.
+pub struct ScoreBoard;
+
 impl ScoreBoard {
     pub fn new() -> Self {
-        ScoreBoard {
-            label_render_position: (21.0, 0.3).into(),
-            score_text_render_position: (21.5, 1.5).into(),
-            honey_text_render_position: (28.5, 1.5).into(),
-            honey_image_render_position: (31.7, 1.1).into(),
-            lives_text_render_position: (36.5, 1.5).into(),
-            lives_hearts_render_position: (40., 1.4).into(),
-        }
+        ScoreBoard
     }
 
-    pub fn update(&mut self) {}
+    pub fn draw(&self, ctx: &mut Context, ui: &mut Ui, assets: &mut Assets, screen: graphics::Rect, hud: &Hud) -> GameResult {
+        // the labels sit on the score board image, so they don't need an outline
+        let style = TextStyle::new(Color::BLACK).size(17.0);
+        let score_str = format!("Score: {}", hud.score);
+        let honey_str = format!("Honey: {}", hud.honey);
+        let lives_str = "Lives:";
 
-    pub fn draw(&self, ctx: &mut Context, assets: &mut Assets, score: i32, lives: i32, honey: i32) -> GameResult {
-        {
-            let label_sprite = assets.get_score_label_image();
-            let label_sprite_dest: ggez::mint::Point2<f32> = self.label_render_position.into();
-            let label_sprite_scale: ggez::mint::Point2<f32> = GridPosition::new(0.2, 0.1).into();
-            let label_draw_params = graphics::DrawParam::new()
-                .dest(label_sprite_dest)
-                .scale(label_sprite_scale);
-
-            graphics::draw(ctx, label_sprite, label_draw_params);
-        }
+        let text_size = |ctx: &mut Context, assets: &mut Assets, text: &str| assets.text_dimensions(ctx, text, &style);
+        let mut sizes = vec![
+            text_size(ctx, assets, &score_str),
+            text_size(ctx, assets, &honey_str),
+            (HONEY_ICON_SIZE, HONEY_ICON_SIZE),
+            text_size(ctx, assets, lives_str),
+        ];
+        sizes.extend((0..hud.lives.max(0)).map(|_| (HEART_SIZE, HEART_SIZE)));
 
-        {
-            let honey_sprite = assets.get_honey_image();
-            let honey_sprite_dest: ggez::mint::Point2<f32> = self.honey_image_render_position.into();
-            let honey_draw_params = graphics::DrawParam::new()
-                .dest(honey_sprite_dest);
+        let width = sizes.iter().map(|size| size.0).sum::<f32>() + SPACING * (sizes.len() - 1) as f32 + PADDING * 2.0;
+        let board = Anchor::Top.place(screen, (width, HEIGHT), 4.0);
+        ui.icon(ctx, assets.get_image(AssetCategory::Ui, "score_label"), board)?;
 
-            graphics::draw(ctx, honey_sprite, honey_draw_params);
+        let mut cells = ui_layout::row([board.x + PADDING, board.y], &sizes, SPACING);
+        // everything is centered vertically on the board
+        for cell in cells.iter_mut() {
+            cell.y = board.y + (HEIGHT - cell.h) / 2.0;
         }
 
-        for i in 0..lives {
-            let heart_sprite = assets.get_heart_image();
-            let heart_sprite_dest: ggez::mint::Point2<f32> = GridPosition::new_from_move(self.lives_hearts_render_position, Direction::Right, i as f32 * 1.).into();
-            let heart_draw_params = graphics::DrawParam::new()
-                .dest(heart_sprite_dest);
-
-            graphics::draw(ctx, heart_sprite, heart_draw_params);
+        ui.label(ctx, assets, &score_str, [cells[0].x, cells[0].y], &style)?;
+        ui.label(ctx, assets, &honey_str, [cells[1].x, cells[1].y], &style)?;
+        ui.icon(ctx, assets.get_image(AssetCategory::Ui, "honey"), cells[2])?;
+        ui.label(ctx, assets, lives_str, [cells[3].x, cells[3].y], &style)?;
+        for heart in cells[4..].iter() {
+            ui.icon(ctx, assets.get_image(AssetCategory::Ui, "heart"), *heart)?;
         }
 
-        let score_str = format!("Score: {}", score);
-        let score_display = graphics::Text::new((score_str));
-        let score_dest: ggez::mint::Point2<f32> = self.score_text_render_position.into();
-
-        let honey_str = format!("Honey:   {}", honey);
-        let honey_display = graphics::Text::new((honey_str));
-        let honey_dest: ggez::mint::Point2<f32> = self.honey_text_render_position.into();
-
-        let lives_str = format!("Lives: ");
-        let lives_display = graphics::Text::new((lives_str));
-            // .set_font(ggez::graphics::Font::default(), (1.1).into());
-        let lives_dest: ggez::mint::Point2<f32> = self.lives_text_render_position.into();
-
-        graphics::draw(ctx, &score_display, (score_dest, 0.0, Color::BLACK))?;
-        graphics::draw(ctx, &honey_display, (honey_dest, 0.0, Color::BLACK))?;
-        graphics::draw(ctx, &lives_display, (lives_dest, 0.0, Color::BLACK))?;
+        if let Some(progress) = hud.progress {
+            let bar = graphics::Rect::new(board.x + PADDING, board.y + board.h + 2.0, board.w - PADDING * 2.0, PROGRESS_HEIGHT);
+            ui.progress_bar(ctx, bar, progress, Color::from_rgb(240, 190, 60))?;
+        }
         Ok(())
     }
 }
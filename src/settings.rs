@@ -0,0 +1,113 @@
+//! Player settings that survive restarts, stored next to the profile in the user's
+//! config folder.
+
+use ggez::{filesystem, Context, GameResult};
+use serde_json::{json, Value};
+use std::io::Write;
+
+const SETTINGS_PATH: &str = "/settings.json";
+pub const MIN_TEXT_SCALE: f32 = 0.5;
+pub const MAX_TEXT_SCALE: f32 = 2.0;
+
+/// Which enemies show a health bar.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HealthBars {
+    Always,
+    /// Only enemies that took damage.
+    Damaged,
+    /// Only the enemy under the mouse.
+    Hover,
+}
+
+impl HealthBars {
+    pub const ALL: [HealthBars; 3] = [HealthBars::Always, HealthBars::Damaged, HealthBars::Hover];
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            HealthBars::Always => "always",
+            HealthBars::Damaged => "damaged",
+            HealthBars::Hover => "hover",
+        }
+    }
+
+    /// The option after this one, wrapping around.
+    pub fn next(&self) -> HealthBars {
+        let index = HealthBars::ALL.iter().position(|option| option == self).unwrap();
+        HealthBars::ALL[(index + 1) % HealthBars::ALL.len()]
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Settings {
+    /// Volumes from 0 to 1. Music and sound effects are scaled by the master volume.
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    /// Multiplies the size of all text, from `MIN_TEXT_SCALE` to `MAX_TEXT_SCALE`.
+    pub text_scale: f32,
+    pub health_bars: HealthBars,
+    /// Floating numbers, particles and screen shake.
+    pub effects: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            master_volume: 1.0,
+            music_volume: 0.6,
+            sfx_volume: 0.8,
+            text_scale: 1.0,
+            health_bars: HealthBars::Damaged,
+            effects: true,
+        }
+    }
+}
+
+impl Settings {
+    /// Loads the saved settings, falling back to the defaults for anything missing or unreadable.
+    pub fn load(ctx: &mut Context) -> Self {
+        let settings_file = match filesystem::open(ctx, SETTINGS_PATH) {
+            Ok(settings_file) => settings_file,
+            Err(_) => return Settings::default(),
+        };
+
+        match serde_json::from_reader(settings_file) {
+            Ok(json) => Settings::from_json(&json),
+            Err(e) => {
+                println!("settings are corrupted, using the defaults: {}", e);
+                Settings::default()
+            }
+        }
+    }
+
+    pub fn save(&self, ctx: &mut Context) -> GameResult {
+        let mut settings_file = filesystem::create(ctx, SETTINGS_PATH)?;
+        settings_file.write_all(self.to_json().to_string().as_bytes())?;
+        Ok(())
+    }
+
+    pub fn from_json(json: &Value) -> Self {
+        let mut settings = Settings::default();
+        let volume = |field: &str, default: f32| json[field].as_f64().map_or(default, |v| (v as f32).clamp(0.0, 1.0));
+        settings.master_volume = volume("master_volume", settings.master_volume);
+        settings.music_volume = volume("music_volume", settings.music_volume);
+        settings.sfx_volume = volume("sfx_volume", settings.sfx_volume);
+        settings.text_scale = json["text_scale"].as_f64().map_or(settings.text_scale, |v| (v as f32).clamp(MIN_TEXT_SCALE, MAX_TEXT_SCALE));
+        if let Some(health_bars) = HealthBars::ALL.iter().find(|option| json["health_bars"] == option.key()) {
+            settings.health_bars = *health_bars;
+        }
+        settings.effects = json["effects"].as_bool().unwrap_or(settings.effects);
+        settings
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "master_volume": self.master_volume,
+            "music_volume": self.music_volume,
+            "sfx_volume": self.sfx_volume,
+            "text_scale": self.text_scale,
+            "health_bars": self.health_bars.key(),
+            "effects": self.effects,
+        })
+    }
+}
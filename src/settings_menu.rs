@@ -0,0 +1,145 @@
+use ggez::{Context, GameResult};
+use ggez::event::{KeyCode, KeyMods};
+use ggez::graphics::{Color, Rect};
+use crate::assets::Assets;
+use crate::controls_menu::ControlsMenu;
+use crate::scene::{Scene, Shared, Transition};
+use crate::settings::{Settings, MAX_TEXT_SCALE, MIN_TEXT_SCALE};
+use crate::text::TextStyle;
+use crate::ui::{Ui, ACCENT_COLOR};
+use crate::ui_layout::{self, Anchor};
+
+pub const NAME: &str = "settings";
+pub const VOLUME_STEP: f32 = 0.1;
+const TEXT_SCALE_STEP: f32 = 0.1;
+const LABEL_WIDTH: f32 = 160.0;
+const BAR_WIDTH: f32 = 140.0;
+const ROW_HEIGHT: f32 = 28.0;
+const SPACING: f32 = 8.0;
+const PADDING: f32 = 24.0;
+/// The steppers, the health bars and effects options, the controls and the back button.
+const ROW_COUNT: usize = 8;
+
+/// Changes the settings, which take effect and are saved right away.
+pub struct SettingsMenu {
+    /// The settings as changed in the last frame, put into effect at the next update.
+    changed: Option<Settings>,
+    controls: bool,
+    back: bool,
+}
+
+impl SettingsMenu {
+    pub fn new() -> Self {
+        SettingsMenu { changed: None, controls: false, back: false }
+    }
+}
+
+/// `value` moved by `step` and kept within `min` and `max`, rounded so repeated steps
+/// don't drift.
+fn step_value(value: f32, step: f32, min: f32, max: f32) -> f32 {
+    ((value + step) * 100.0).round().max(min * 100.0).min(max * 100.0) / 100.0
+}
+
+/// A label, a bar `fraction` full and buttons to turn it down and up. Returns -1 or 1
+/// when one of them was clicked, 0 otherwise.
+fn stepper(ctx: &mut Context, ui: &mut Ui, assets: &mut Assets, dest: [f32; 2], label: &str, fraction: f32) -> GameResult<f32> {
+    let style = TextStyle::new(Color::WHITE).outline(Color::BLACK);
+    let (_, label_height) = assets.text_dimensions(ctx, label, &style);
+    ui.label(ctx, assets, label, [dest[0], dest[1] + (ROW_HEIGHT - label_height) / 2.0], &style)?;
+
+    let sizes = [(ROW_HEIGHT, ROW_HEIGHT), (BAR_WIDTH, ROW_HEIGHT / 2.0), (ROW_HEIGHT, ROW_HEIGHT)];
+    let rects = ui_layout::row([dest[0] + LABEL_WIDTH, dest[1]], &sizes, SPACING);
+    let bar = Rect::new(rects[1].x, dest[1] + ROW_HEIGHT / 4.0, rects[1].w, rects[1].h);
+    let mut direction = 0.0;
+    if ui.button(ctx, assets, rects[0], "-", fraction > 0.0)? {
+        direction = -1.0;
+    }
+    ui.progress_bar(ctx, bar, fraction, ACCENT_COLOR)?;
+    if ui.button(ctx, assets, rects[2], "+", fraction < 1.0)? {
+        direction = 1.0;
+    }
+    Ok(direction)
+}
+
+impl Scene for SettingsMenu {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn update(&mut self, ctx: &mut Context, shared: &mut Shared) -> GameResult<Transition> {
+        if let Some(settings) = self.changed.take() {
+            shared.set_settings(ctx, settings);
+        }
+        if std::mem::take(&mut self.controls) {
+            return Ok(Transition::Push(Box::new(ControlsMenu::new())));
+        }
+        Ok(if self.back { Transition::Pop } else { Transition::None })
+    }
+
+    fn draw(&mut self, ctx: &mut Context, shared: &mut Shared) -> GameResult {
+        let ui = &mut shared.ui;
+        let assets = &mut shared.assets;
+        ui.shade(ctx, shared.screen)?;
+
+        let title = "Settings";
+        let title_style = TextStyle::new(Color::WHITE).size(24.0).outline(Color::BLACK);
+        let title_size = assets.text_dimensions(ctx, title, &title_style);
+        let row_width = LABEL_WIDTH + BAR_WIDTH + ROW_HEIGHT * 2.0 + SPACING * 2.0;
+        let height = title_size.1 + SPACING + ROW_COUNT as f32 * (ROW_HEIGHT + SPACING);
+        let panel = Anchor::Center.place(shared.screen, (row_width + PADDING * 2.0, height + PADDING * 2.0), 0.0);
+        ui.panel(ctx, panel)?;
+        ui.label(ctx, assets, title, [panel.x + (panel.w - title_size.0) / 2.0, panel.y + PADDING], &title_style)?;
+
+        let sizes = vec![(row_width, ROW_HEIGHT); ROW_COUNT];
+        let rows = ui_layout::column([panel.x + PADDING, panel.y + PADDING + title_size.1 + SPACING * 2.0], &sizes, SPACING);
+        let mut settings = shared.settings;
+        let mut volumes = [
+            ("Master volume", &mut settings.master_volume),
+            ("Music", &mut settings.music_volume),
+            ("Sound effects", &mut settings.sfx_volume),
+        ];
+        for ((label, volume), row) in volumes.iter_mut().zip(rows.iter()) {
+            let direction = stepper(ctx, ui, assets, [row.x, row.y], label, **volume)?;
+            if direction != 0.0 {
+                **volume = step_value(**volume, direction * VOLUME_STEP, 0.0, 1.0);
+            }
+        }
+
+        let text_fraction = (settings.text_scale - MIN_TEXT_SCALE) / (MAX_TEXT_SCALE - MIN_TEXT_SCALE);
+        let direction = stepper(ctx, ui, assets, [rows[3].x, rows[3].y], "Text size", text_fraction)?;
+        if direction != 0.0 {
+            settings.text_scale = step_value(settings.text_scale, direction * TEXT_SCALE_STEP, MIN_TEXT_SCALE, MAX_TEXT_SCALE);
+        }
+
+        let health_bars_str = format!("Health bars: {}", settings.health_bars.key());
+        if ui.button(ctx, assets, rows[4], &health_bars_str, true)? {
+            settings.health_bars = settings.health_bars.next();
+        }
+        let effects_str = format!("Effects: {}", if settings.effects { "on" } else { "off" });
+        if ui.button(ctx, assets, rows[5], &effects_str, true)? {
+            settings.effects = !settings.effects;
+        }
+        if ui.button(ctx, assets, rows[6], "Controls", true)? {
+            self.controls = true;
+        }
+        if ui.button(ctx, assets, rows[7], "Back", true)? {
+            self.back = true;
+        }
+
+        if settings != shared.settings {
+            self.changed = Some(settings);
+        }
+        Ok(())
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+
+    fn key_down(&mut self, _ctx: &mut Context, _shared: &mut Shared, keycode: KeyCode, _keymods: KeyMods, repeat: bool) -> Transition {
+        match keycode {
+            KeyCode::Escape if !repeat => Transition::Pop,
+            _ => Transition::None,
+        }
+    }
+}
@@ -0,0 +1,271 @@
+//! Sprite sheets for enemies, towers and the nexus. A sheet is a JSON file next to its image,
+//! listed in the asset manifest in place of the image:
+//!
+//! ```json
+//! {
+//!   "image": "/slime_blue.png",
+//!   "grid": [16, 24],
+//!   "scale": 2,
+//!   "origin": [0, 6],
+//!   "animations": {
+//!     "walk": { "frames": [0, 1, 2, 3], "duration": 120 },
+//!     "death": { "frames": [1, 2], "duration": 150, "loop": false }
+//!   }
+//! }
+//! ```
+//!
+//! `grid` cuts the image into equal frames, numbered row by row. An atlas lists the frames
+//! instead, as `"frames": [[x, y, width, height], ...]`. `origin` is the pixel of a frame that
+//! is drawn at the entity's position. Animations are named after an `Action`, optionally
+//! with a facing, e.g. `walk_up`. Without one, sprites face right and are mirrored while the
+//! entity last moved left.
+
+use ggez::{GameError, GameResult};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use crate::movement_helpers::Direction;
+
+const DEFAULT_FRAME_DURATION: u32 = 100;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Idle,
+    Walk,
+    Attack,
+    Hit,
+    Death,
+}
+
+impl Action {
+    pub fn key(&self) -> &'static str {
+        match self {
+            Action::Idle => "idle",
+            Action::Walk => "walk",
+            Action::Attack => "attack",
+            Action::Hit => "hit",
+            Action::Death => "death",
+        }
+    }
+
+    /// Whether the action plays once instead of looping.
+    pub fn is_one_shot(&self) -> bool {
+        matches!(self, Action::Attack | Action::Hit | Action::Death)
+    }
+}
+
+fn facing_key(direction: Direction) -> &'static str {
+    match direction {
+        Direction::Up => "up",
+        Direction::Down => "down",
+        Direction::Left => "left",
+        Direction::Right => "right",
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SheetAnimation {
+    pub frames: Vec<usize>,
+    /// How long each frame is shown, in milliseconds.
+    pub frame_duration: u32,
+    pub looping: bool,
+}
+
+impl SheetAnimation {
+    pub fn length(&self) -> u64 {
+        self.frames.len() as u64 * self.frame_duration as u64
+    }
+}
+
+/// The part of the sheet's image to draw, in pixels, and whether to mirror it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Frame {
+    pub rect: [u32; 4],
+    pub flip_x: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpriteSheet {
+    pub image_size: (u32, u32),
+    pub frames: Vec<[u32; 4]>,
+    pub animations: BTreeMap<String, SheetAnimation>,
+    pub scale: f32,
+    pub origin: (f32, f32),
+}
+
+impl SpriteSheet {
+    /// A plain image: one frame that every action shows.
+    pub fn single(image_size: (u32, u32)) -> Self {
+        SpriteSheet {
+            image_size,
+            frames: vec![[0, 0, image_size.0, image_size.1]],
+            animations: BTreeMap::new(),
+            scale: 1.0,
+            origin: (0.0, 0.0),
+        }
+    }
+
+    /// The path of the sheet's image, to be loaded before `from_json` can be called.
+    pub fn image_path<'a>(json: &'a Value, source: &str) -> GameResult<&'a str> {
+        json["image"].as_str().ok_or_else(|| sheet_error(source, "image must be a path"))
+    }
+
+    pub fn from_json(json: &Value, source: &str, image_size: (u32, u32)) -> GameResult<SpriteSheet> {
+        let mut sheet = SpriteSheet::single(image_size);
+
+        if let Some(grid) = json["grid"].as_array() {
+            let (width, height) = match (grid.first().and_then(|v| v.as_u64()), grid.get(1).and_then(|v| v.as_u64())) {
+                (Some(width), Some(height)) if width > 0 && height > 0 => (width as u32, height as u32),
+                _ => return Err(sheet_error(source, "grid must be [frame_width, frame_height]")),
+            };
+            sheet.frames = Vec::new();
+            for row in 0..image_size.1 / height {
+                for column in 0..image_size.0 / width {
+                    sheet.frames.push([column * width, row * height, width, height]);
+                }
+            }
+        } else if let Some(frames) = json["frames"].as_array() {
+            sheet.frames = Vec::new();
+            for frame in frames.iter() {
+                let rect: Vec<u32> = frame.as_array().map_or(Vec::new(), |rect| {
+                    rect.iter().filter_map(|v| v.as_u64()).map(|v| v as u32).collect()
+                });
+                let fits = |start: u32, size: u32, limit: u32| size > 0 && start.checked_add(size).is_some_and(|end| end <= limit);
+                if rect.len() != 4 || !fits(rect[0], rect[2], image_size.0) || !fits(rect[1], rect[3], image_size.1) {
+                    return Err(sheet_error(source, &format!("frame {} is not an [x, y, width, height] inside the image", frame)));
+                }
+                sheet.frames.push([rect[0], rect[1], rect[2], rect[3]]);
+            }
+        }
+        if sheet.frames.is_empty() {
+            return Err(sheet_error(source, "the sheet has no frames"));
+        }
+
+        if let Some(scale) = json["scale"].as_f64() {
+            sheet.scale = scale as f32;
+        }
+        if let (Some(x), Some(y)) = (json["origin"][0].as_f64(), json["origin"][1].as_f64()) {
+            sheet.origin = (x as f32, y as f32);
+        }
+
+        if let Some(animations) = json["animations"].as_object() {
+            for (name, animation) in animations.iter() {
+                let frames: Vec<usize> = animation["frames"].as_array()
+                    .map_or(Vec::new(), |frames| frames.iter().filter_map(|v| v.as_u64()).map(|v| v as usize).collect());
+                if frames.is_empty() {
+                    return Err(sheet_error(source, &format!("animation {} has no frames", name)));
+                }
+                if let Some(frame) = frames.iter().find(|frame| **frame >= sheet.frames.len()) {
+                    return Err(sheet_error(source, &format!("animation {} shows frame {} of {}", name, frame, sheet.frames.len())));
+                }
+
+                sheet.animations.insert(name.clone(), SheetAnimation {
+                    frames,
+                    frame_duration: animation["duration"].as_u64().map_or(DEFAULT_FRAME_DURATION, |v| v as u32).max(1),
+                    looping: animation["loop"].as_bool().unwrap_or(true),
+                });
+            }
+        }
+        Ok(sheet)
+    }
+
+    /// The animation for `action` facing `facing`, and whether it was made for that facing.
+    /// Falls back to the action without a facing and then, unless the action is one-shot,
+    /// to `idle`. A looping idle would keep a one-shot action from ever finishing.
+    pub fn animation(&self, action: Action, facing: Direction) -> Option<(&SheetAnimation, bool)> {
+        let faced = self.animations.get(&format!("{}_{}", action.key(), facing_key(facing)));
+        if let Some(animation) = faced {
+            return Some((animation, true));
+        }
+
+        self.animations.get(action.key())
+            .or_else(|| if action.is_one_shot() { None } else { self.animations.get(Action::Idle.key()) })
+            .map(|animation| (animation, false))
+    }
+}
+
+fn sheet_error(source: &str, message: &str) -> GameError {
+    GameError::ResourceLoadError(format!("sprite sheet {}: {}", source, message))
+}
+
+/// Plays the animations of one entity. One-shot actions like `Hit` go back to the
+/// action that was playing before once they're over, except for `Death`, which stays
+/// on its last frame.
+#[derive(Clone, Debug)]
+pub struct Animator {
+    action: Action,
+    /// What to go back to after a one-shot action.
+    base_action: Action,
+    facing: Direction,
+    /// Whether the last horizontal facing was left.
+    mirrored: bool,
+    elapsed: u64,
+}
+
+impl Animator {
+    pub fn new(action: Action) -> Self {
+        Animator { action, base_action: action, facing: Direction::Right, mirrored: false, elapsed: 0 }
+    }
+
+    pub fn action(&self) -> Action {
+        self.action
+    }
+
+    /// Switches to `action`, restarting it unless it's already playing. Idle and walk
+    /// wait for a one-shot action to finish.
+    pub fn play(&mut self, action: Action) {
+        if self.action == Action::Death || self.action == action {
+            return;
+        }
+        if let Action::Idle | Action::Walk = action {
+            self.base_action = action;
+            if self.action != Action::Idle && self.action != Action::Walk {
+                return;
+            }
+        }
+        self.action = action;
+        self.elapsed = 0;
+    }
+
+    pub fn face(&mut self, direction: Direction) {
+        self.facing = direction;
+        match direction {
+            Direction::Left => self.mirrored = true,
+            Direction::Right => self.mirrored = false,
+            Direction::Up | Direction::Down => {}
+        }
+    }
+
+    /// Advances the animation, `millis` being the time since the last update.
+    pub fn update(&mut self, sheet: &SpriteSheet, millis: u64) {
+        self.elapsed += millis;
+        if self.action == Action::Death {
+            return;
+        }
+        if self.action.is_one_shot() && self.is_finished(sheet) {
+            self.action = self.base_action;
+            self.elapsed = 0;
+        }
+    }
+
+    /// Whether a one-shot animation like `Death` has played to its end. One the sheet
+    /// has no animation for is over right away.
+    pub fn is_finished(&self, sheet: &SpriteSheet) -> bool {
+        match sheet.animation(self.action, self.facing) {
+            Some((animation, _)) => !animation.looping && self.elapsed >= animation.length(),
+            None => true,
+        }
+    }
+
+    pub fn frame(&self, sheet: &SpriteSheet) -> Frame {
+        // a one-shot action without an animation shows the action it goes back to
+        let animation = sheet.animation(self.action, self.facing).or_else(|| sheet.animation(self.base_action, self.facing));
+        let (animation, faced) = match animation {
+            Some(animation) => animation,
+            None => return Frame { rect: sheet.frames[0], flip_x: self.mirrored },
+        };
+
+        let mut index = (self.elapsed / animation.frame_duration as u64) as usize;
+        index = if animation.looping { index % animation.frames.len() } else { index.min(animation.frames.len() - 1) };
+        Frame { rect: sheet.frames[animation.frames[index]], flip_x: self.mirrored && !faced }
+    }
+}
@@ -0,0 +1,82 @@
+//! Gameplay properties of the map. Every sprite stands for a kind of terrain: mud and honey
+//! slow down enemies walking over them, towers on hills reach further and nothing can be
+//! built on water. Levels can give any sprite its own properties, see `Level::terrain`.
+
+use serde_json::{json, Value};
+use crate::autotile;
+
+pub const MUD: &str = "82";
+pub const HONEY: &str = "83";
+pub const HILL: &str = "84";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Terrain {
+    /// Multiplies the speed of enemies walking over the terrain.
+    pub speed: f32,
+    /// Extra range in cells for towers standing on the terrain.
+    pub range_bonus: f32,
+    pub buildable: bool,
+}
+
+impl Default for Terrain {
+    fn default() -> Self {
+        Terrain { speed: 1.0, range_bonus: 0.0, buildable: true }
+    }
+}
+
+impl Terrain {
+    /// The properties of the sprites shipped with the game, for levels that don't say otherwise.
+    pub fn of_sprite(sprite: &str) -> Option<Terrain> {
+        let terrain = match sprite {
+            MUD => Terrain { speed: 0.5, ..Terrain::default() },
+            HONEY => Terrain { speed: 0.25, ..Terrain::default() },
+            HILL => Terrain { range_bonus: 2.0, ..Terrain::default() },
+            autotile::WATER | autotile::WATER_RIPPLE => Terrain { buildable: false, ..Terrain::default() },
+            _ => return None,
+        };
+        Some(terrain)
+    }
+
+    /// Stacks terrain on top of each other, e.g. mud with a hill overlay.
+    pub fn combine(self, other: Terrain) -> Terrain {
+        Terrain {
+            speed: self.speed * other.speed,
+            range_bonus: self.range_bonus + other.range_bonus,
+            buildable: self.buildable && other.buildable,
+        }
+    }
+
+    /// Reads `{"speed": 0.5, "range_bonus": 2, "buildable": false}`, every field is optional.
+    /// Enemies have to keep moving and towers can't lose range, so the speed must be above 0
+    /// and the range bonus can't be negative.
+    pub fn from_json(json: &Value) -> Option<Terrain> {
+        let fields = json.as_object()?;
+        let mut terrain = Terrain::default();
+        if let Some(speed) = fields.get("speed") {
+            terrain.speed = speed.as_f64().filter(|speed| *speed > 0.0)? as f32;
+        }
+        if let Some(range_bonus) = fields.get("range_bonus") {
+            terrain.range_bonus = range_bonus.as_f64().filter(|range_bonus| *range_bonus >= 0.0)? as f32;
+        }
+        if let Some(buildable) = fields.get("buildable") {
+            terrain.buildable = buildable.as_bool()?;
+        }
+        Some(terrain)
+    }
+
+    /// The fields that differ from plain terrain, in the format `from_json` reads.
+    pub fn to_json(&self) -> Value {
+        let default = Terrain::default();
+        let mut terrain = json!({});
+        if self.speed != default.speed {
+            terrain["speed"] = json!(self.speed);
+        }
+        if self.range_bonus != default.range_bonus {
+            terrain["range_bonus"] = json!(self.range_bonus);
+        }
+        if self.buildable != default.buildable {
+            terrain["buildable"] = json!(self.buildable);
+        }
+        terrain
+    }
+}
@@ -0,0 +1,199 @@
+use ggez::graphics::{self, Color, DrawParam, PxScale};
+use ggez::{Context, GameResult};
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+use crate::bitmap_font::{BitmapFont, PlacedGlyph};
+use crate::manifest::{AssetCategory, AssetManifest};
+
+/// The font of the HUD, menus and labels. Without it in the manifest ggez's built in font is used.
+pub const UI_FONT: &str = "ui";
+/// The size ggez draws text at by default, in pixels.
+pub const DEFAULT_TEXT_SIZE: f32 = 16.0;
+
+/// How a piece of text is drawn. Outlines and shadows keep text readable on any tile.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TextStyle {
+    pub font: &'static str,
+    /// The height of a line in pixels, before the player's text scale.
+    pub size: f32,
+    pub color: Color,
+    pub outline: Option<Color>,
+    pub shadow: Option<Color>,
+}
+
+impl TextStyle {
+    pub fn new(color: Color) -> Self {
+        TextStyle { font: UI_FONT, size: DEFAULT_TEXT_SIZE, color, outline: None, shadow: None }
+    }
+
+    pub fn size(mut self, size: f32) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn outline(mut self, color: Color) -> Self {
+        self.outline = Some(color);
+        self
+    }
+
+    pub fn shadow(mut self, color: Color) -> Self {
+        self.shadow = Some(color);
+        self
+    }
+}
+
+enum FontFace {
+    TrueType(graphics::Font),
+    Bitmap(graphics::Image, BitmapFont),
+}
+
+/// What a drawn text looked like last time, so it isn't laid out again every frame.
+enum CachedText {
+    TrueType(graphics::Text),
+    Bitmap(Vec<PlacedGlyph>),
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct TextKey {
+    font: &'static str,
+    /// The scaled size, in hundredths of a pixel.
+    size: u32,
+    text: String,
+}
+
+/// Draws text in the fonts of the asset manifest. Texts are cached until a frame goes by
+/// without them being drawn.
+pub struct TextRenderer {
+    fonts: HashMap<String, FontFace>,
+    cache: HashMap<TextKey, CachedText>,
+    drawn: HashSet<TextKey>,
+    /// The player's text scale, multiplying every size.
+    scale: f32,
+}
+
+impl TextRenderer {
+    pub fn new(ctx: &mut Context, manifest: &AssetManifest) -> GameResult<Self> {
+        let mut fonts: HashMap<String, FontFace> = HashMap::new();
+        for (id, path) in manifest.entries(AssetCategory::Fonts) {
+            fonts.insert(id.to_string(), load_font(ctx, path)?);
+        }
+        Ok(TextRenderer { fonts, cache: HashMap::new(), drawn: HashSet::new(), scale: 1.0 })
+    }
+
+    pub fn set_scale(&mut self, scale: f32) {
+        self.scale = scale;
+    }
+
+    /// Draws `text` with its top left corner at `dest`.
+    pub fn draw(&mut self, ctx: &mut Context, text: &str, dest: [f32; 2], style: &TextStyle) -> GameResult {
+        let key = self.key(text, style);
+        let size = key.size as f32 / 100.0;
+        self.lay_out(ctx, &key);
+        self.drawn.insert(key.clone());
+
+        // the outline is the text drawn a bit off to every side
+        let offset = (size / 16.0).max(1.0);
+        if let Some(shadow) = style.shadow {
+            self.draw_layer(ctx, &key, [dest[0] + offset, dest[1] + offset], shadow)?;
+        }
+        if let Some(outline) = style.outline {
+            for (x, y) in [(-1.0, -1.0), (0.0, -1.0), (1.0, -1.0), (-1.0, 0.0), (1.0, 0.0), (-1.0, 1.0), (0.0, 1.0), (1.0, 1.0)].iter() {
+                self.draw_layer(ctx, &key, [dest[0] + x * offset, dest[1] + y * offset], outline)?;
+            }
+        }
+        self.draw_layer(ctx, &key, dest, style.color)
+    }
+
+    /// The width and height `text` takes up when drawn in `style`.
+    pub fn dimensions(&mut self, ctx: &mut Context, text: &str, style: &TextStyle) -> (f32, f32) {
+        let key = self.key(text, style);
+        let size = key.size as f32 / 100.0;
+        self.lay_out(ctx, &key);
+        match (&self.cache[&key], self.fonts.get(key.font)) {
+            (CachedText::Bitmap(_), Some(FontFace::Bitmap(_, font))) => {
+                let (width, height) = font.measure(&key.text);
+                let scale = size / font.glyph_size.1 as f32;
+                (width * scale, height * scale)
+            }
+            (CachedText::TrueType(text), _) => {
+                let rect = text.dimensions(ctx);
+                (rect.w, rect.h)
+            }
+            _ => (0.0, 0.0),
+        }
+    }
+
+    /// Forgets the texts that weren't drawn since the last call. Meant to be called after
+    /// every frame.
+    pub fn end_frame(&mut self) {
+        let drawn = std::mem::take(&mut self.drawn);
+        self.cache.retain(|key, _| drawn.contains(key));
+    }
+
+    fn key(&self, text: &str, style: &TextStyle) -> TextKey {
+        TextKey { font: style.font, size: (style.size * self.scale * 100.0).round() as u32, text: text.to_string() }
+    }
+
+    fn lay_out(&mut self, ctx: &mut Context, key: &TextKey) {
+        if self.cache.contains_key(key) {
+            return;
+        }
+        let size = key.size as f32 / 100.0;
+        let cached = match self.fonts.get(key.font) {
+            Some(FontFace::Bitmap(_, font)) => CachedText::Bitmap(font.layout(&key.text)),
+            Some(FontFace::TrueType(font)) => {
+                let mut text = graphics::Text::new(key.text.as_str());
+                text.set_font(*font, PxScale::from(size));
+                CachedText::TrueType(text)
+            }
+            None => {
+                let mut text = graphics::Text::new(key.text.as_str());
+                text.set_font(graphics::Font::default(), PxScale::from(size));
+                CachedText::TrueType(text)
+            }
+        };
+        // measuring now keeps the glyph layout out of the draw calls
+        if let CachedText::TrueType(text) = &cached {
+            text.dimensions(ctx);
+        }
+        self.cache.insert(key.clone(), cached);
+    }
+
+    fn draw_layer(&self, ctx: &mut Context, key: &TextKey, dest: [f32; 2], color: Color) -> GameResult {
+        match (&self.cache[key], self.fonts.get(key.font)) {
+            (CachedText::Bitmap(glyphs), Some(FontFace::Bitmap(image, font))) => {
+                let scale = key.size as f32 / 100.0 / font.glyph_size.1 as f32;
+                let (image_width, image_height) = (image.width() as f32, image.height() as f32);
+                let mut batch = graphics::spritebatch::SpriteBatch::new(image.clone());
+                for glyph in glyphs.iter() {
+                    let [x, y, width, height] = glyph.rect;
+                    batch.add(DrawParam::new()
+                        .src(graphics::Rect::new(x as f32 / image_width, y as f32 / image_height, width as f32 / image_width, height as f32 / image_height))
+                        .dest([dest[0] + glyph.x * scale, dest[1] + glyph.y * scale])
+                        .scale([scale, scale])
+                        .color(color));
+                }
+                graphics::draw(ctx, &batch, DrawParam::new())
+            }
+            (CachedText::TrueType(text), _) => graphics::draw(ctx, text, DrawParam::new().dest(dest).color(color)),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Loads a TrueType font, or a bitmap font and its image when `path` is a descriptor.
+fn load_font(ctx: &mut Context, path: &str) -> GameResult<FontFace> {
+    if !path.ends_with(".json") {
+        return Ok(FontFace::TrueType(graphics::Font::new(ctx, path)?));
+    }
+
+    let mut contents = String::new();
+    ggez::filesystem::open(ctx, path)?.read_to_string(&mut contents)?;
+    let json: serde_json::Value = serde_json::from_str(&contents)
+        .map_err(|e| ggez::GameError::ResourceLoadError(format!("bitmap font {}: invalid JSON: {}", path, e)))?;
+    let mut image = graphics::Image::new(ctx, BitmapFont::image_path(&json, path)?)?;
+    // pixel fonts stay crisp when scaled up
+    image.set_filter(graphics::FilterMode::Nearest);
+    let font = BitmapFont::from_json(&json, path, (image.width() as u32, image.height() as u32))?;
+    Ok(FontFace::Bitmap(image, font))
+}
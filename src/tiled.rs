@@ -0,0 +1,680 @@
+//! Loads levels made in the Tiled map editor, saved either as JSON (`.tmj`) or XML (`.tmx`).
+//!
+//! The map is expected to have:
+//! - a tile layer with the terrain (named `ground`, or simply the first tile layer),
+//! - an optional tile layer named `decor`,
+//! - any number of further tile layers, drawn over the decor in order, or over enemies and
+//!   towers when the layer has the bool property `above_entities`,
+//! - objects with the class (or type) `path`, `nexus` and `tower_slot`:
+//!   every `path` polyline is one enemy route starting at its first point, the `nexus` rectangle
+//!   covers the cells that damage the nexus and every `tower_slot` point is the center of a tower.
+//!
+//! Map properties `name`, `starting_honey`, `starting_lives` and `enemy_count` mirror the
+//! fields of the native level format. A tile with a `sprite` property is drawn with that
+//! sprite from `Assets`; any other tile brings its tileset image along with the level.
+//! Tile animations become level animations, and tiles with any of the properties `speed`,
+//! `range_bonus` or `buildable` give their sprite that terrain.
+
+use ggez::{GameError, GameResult};
+use serde_json::Value;
+use std::convert::TryFrom;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
+use crate::level::{Animation, AnimationFrame, Layer, Level, NexusLayout, Spawn, TileImage};
+use crate::movement_helpers::{GridPosition, RectangleBorder};
+use crate::terrain::Terrain;
+
+const GID_FLAGS_MASK: u32 = 0x0FFF_FFFF;
+
+/// Reads the file at the given (already resolved) path, used for external tilesets.
+pub type FileOpener<'a> = dyn FnMut(&str) -> GameResult<String> + 'a;
+
+struct TiledTile {
+    image: Option<String>,
+    sprite: Option<String>,
+    /// Local tile ids and durations in milliseconds.
+    animation: Vec<(u32, u32)>,
+    terrain: Option<Terrain>,
+}
+
+struct TiledTileset {
+    first_gid: u32,
+    name: String,
+    tile_width: u32,
+    tile_height: u32,
+    columns: u32,
+    margin: u32,
+    spacing: u32,
+    image: Option<String>,
+    tiles: HashMap<u32, TiledTile>,
+}
+
+struct TiledLayer {
+    name: String,
+    data: Vec<u32>,
+    properties: HashMap<String, Value>,
+}
+
+/// What the level needs besides sprite ids to draw the resolved tiles.
+#[derive(Default)]
+struct ResolvedSprites {
+    tile_images: HashMap<(String, bool), TileImage>,
+    animations: HashMap<String, Animation>,
+    terrain: BTreeMap<String, Terrain>,
+}
+
+struct TiledObject {
+    class: String,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    points: Vec<(f32, f32)>,
+    properties: HashMap<String, Value>,
+}
+
+struct TiledMap {
+    width: u32,
+    height: u32,
+    tile_width: f32,
+    tile_height: f32,
+    properties: HashMap<String, Value>,
+    tilesets: Vec<TiledTileset>,
+    layers: Vec<TiledLayer>,
+    objects: Vec<TiledObject>,
+}
+
+fn tiled_error(source: &str, message: &str) -> GameError {
+    GameError::ResourceLoadError(format!("tiled map {}: {}", source, message))
+}
+
+pub fn is_tiled_json(json: &Value) -> bool {
+    json["type"] == "map" && !json["tiledversion"].is_null()
+}
+
+/// Loads a map saved in Tiled's JSON format. `source` is the map's path, external tileset
+/// and image paths are resolved relative to it.
+pub fn load_tmj(contents: &str, source: &str, open: &mut FileOpener) -> GameResult<Level> {
+    let json: Value = serde_json::from_str(contents)
+        .map_err(|e| tiled_error(source, &format!("invalid JSON: {}", e)))?;
+    let map = parse_json_map(&json, source, open)?;
+    map.into_level(source)
+}
+
+/// Loads a map saved in Tiled's XML format. `source` is the map's path, external tileset
+/// and image paths are resolved relative to it.
+pub fn load_tmx(contents: &str, source: &str, open: &mut FileOpener) -> GameResult<Level> {
+    let document = roxmltree::Document::parse(contents)
+        .map_err(|e| tiled_error(source, &format!("invalid XML: {}", e)))?;
+    let map = parse_xml_map(document.root_element(), source, open)?;
+    map.into_level(source)
+}
+
+impl TiledMap {
+    fn into_level(self, source: &str) -> GameResult<Level> {
+        let grid_size = match (i16::try_from(self.width), i16::try_from(self.height)) {
+            (Ok(width), Ok(height)) if width > 0 && height > 0 => (width, height),
+            (Ok(_), Ok(_)) => return Err(tiled_error(source, "the map must be at least one cell wide and high")),
+            _ => return Err(tiled_error(source, &format!("the map can be at most {} cells a side", i16::MAX))),
+        };
+        let name = self.properties.get("name")
+            .and_then(|name| name.as_str())
+            .unwrap_or(source)
+            .to_string();
+
+        let nexus_object = self.objects.iter()
+            .find(|object| object.class == "nexus")
+            .ok_or_else(|| tiled_error(source, "a `nexus` rectangle object is required"))?;
+        let nexus = self.nexus_layout(nexus_object);
+
+        // the layers have to fill the map before the level's grid is made that big
+        let cell_count = self.width as usize * self.height as usize;
+        let decor_layer = self.layers.iter().find(|layer| layer.name == "decor");
+        let ground_layer = self.layers.iter()
+            .find(|layer| layer.name == "ground")
+            .or_else(|| self.layers.iter().find(|layer| layer.name != "decor"))
+            .ok_or_else(|| tiled_error(source, "a ground tile layer is required"))?;
+
+        for layer in self.layers.iter() {
+            if layer.data.len() != cell_count {
+                return Err(tiled_error(source, &format!("layer {} has {} cells, expected {}", layer.name, layer.data.len(), cell_count)));
+            }
+        }
+
+        let mut level = Level::new(name, grid_size, nexus);
+        if let Some(starting_honey) = self.properties.get("starting_honey").and_then(|v| v.as_i64()) {
+            level.starting_honey = starting_honey as i32;
+        }
+        if let Some(starting_lives) = self.properties.get("starting_lives").and_then(|v| v.as_i64()) {
+            level.starting_lives = starting_lives as i32;
+        }
+        level.enemy_count = self.properties.get("enemy_count").and_then(|v| v.as_i64()).map(|v| v as i32);
+
+        for object in self.objects.iter() {
+            match object.class.as_str() {
+                "path" => {
+                    let mut points = object.points.iter()
+                        .map(|(x, y)| self.to_cell(object.x + x, object.y + y));
+                    let position = points.next()
+                        .ok_or_else(|| tiled_error(source, "a `path` object must be a polyline"))?;
+                    level.spawns.push(Spawn { position, path: points.collect() });
+                }
+                "tower_slot" => {
+                    let center = self.to_cell(object.x + object.width / 2.0, object.y + object.height / 2.0);
+                    level.tower_slots.push(center);
+                }
+                _ => {}
+            }
+        }
+        if level.spawns.is_empty() {
+            return Err(tiled_error(source, "at least one `path` polyline object is required"));
+        }
+
+        let mut resolved = ResolvedSprites::default();
+        for index in 0..cell_count {
+            let x = (index % self.width as usize) as i16;
+            let y = (index / self.width as usize) as i16;
+
+            let ground_gid = ground_layer.data[index] & GID_FLAGS_MASK;
+            if ground_gid == 0 {
+                return Err(tiled_error(source, &format!("ground layer is empty at ({}, {})", x, y)));
+            }
+            let sprite = self.resolve_sprite(ground_gid, false, source, &mut resolved)?;
+
+            let decor = match decor_layer.map(|layer| layer.data[index] & GID_FLAGS_MASK) {
+                Some(decor_gid) if decor_gid != 0 => Some(self.resolve_sprite(decor_gid, true, source, &mut resolved)?),
+                _ => None,
+            };
+
+            let tile = level.tile_mut(x, y).unwrap();
+            tile.sprite = sprite;
+            tile.decor = decor;
+        }
+
+        let overlay_layers = self.layers.iter()
+            .filter(|layer| !std::ptr::eq(*layer, ground_layer) && layer.name != "decor");
+        for tiled_layer in overlay_layers {
+            let mut layer = Layer {
+                name: tiled_layer.name.clone(),
+                above_entities: tiled_layer.properties.get("above_entities").and_then(|v| v.as_bool()).unwrap_or(false),
+                sprites: BTreeMap::new(),
+            };
+            for (index, gid) in tiled_layer.data.iter().enumerate() {
+                let gid = gid & GID_FLAGS_MASK;
+                if gid != 0 {
+                    let position = ((index % self.width as usize) as i16, (index / self.width as usize) as i16);
+                    // overlays are looked up with the decor first, so their images go there too
+                    layer.sprites.insert(position, self.resolve_sprite(gid, true, source, &mut resolved)?);
+                }
+            }
+            level.layers.push(layer);
+        }
+
+        level.tile_images = resolved.tile_images.into_values().collect();
+        level.tile_images.sort_by(|a, b| a.id.cmp(&b.id));
+        level.animations = resolved.animations.into_values().collect();
+        level.animations.sort_by(|a, b| a.id.cmp(&b.id));
+        level.terrain = resolved.terrain;
+        Ok(level)
+    }
+
+    fn to_cell(&self, x: f32, y: f32) -> GridPosition {
+        GridPosition::new((x / self.tile_width).floor(), (y / self.tile_height).floor())
+    }
+
+    fn nexus_layout(&self, object: &TiledObject) -> NexusLayout {
+        let top_left = self.to_cell(object.x, object.y);
+        let bot_right = GridPosition::new(
+            ((object.x + object.width) / self.tile_width).ceil() - 1.0,
+            ((object.y + object.height) / self.tile_height).ceil() - 1.0,
+        );
+        let center = self.to_cell(object.x + object.width / 2.0, object.y + object.height / 2.0);
+
+        let (left, top): (f32, f32) = top_left.into();
+        let render_x = object.properties.get("render_x").and_then(|v| v.as_f64()).map_or(left, |v| v as f32);
+        let render_y = object.properties.get("render_y").and_then(|v| v.as_f64()).map_or(top, |v| v as f32);
+
+        NexusLayout {
+            position: center,
+            render_position: GridPosition::new(render_x, render_y),
+            borders: RectangleBorder::new(top_left, bot_right),
+        }
+    }
+
+    fn tileset_of(&self, gid: u32, source: &str) -> GameResult<&TiledTileset> {
+        self.tilesets.iter()
+            .filter(|tileset| tileset.first_gid <= gid)
+            .max_by_key(|tileset| tileset.first_gid)
+            .ok_or_else(|| tiled_error(source, &format!("tile {} does not belong to any tileset", gid)))
+    }
+
+    /// Maps a global tile id to the sprite id used by `Assets`, or to the id of a level
+    /// animation for animated tiles.
+    fn resolve_sprite(&self, gid: u32, decor: bool, source: &str, resolved: &mut ResolvedSprites) -> GameResult<String> {
+        let tileset = self.tileset_of(gid, source)?;
+        let tile = tileset.tiles.get(&(gid - tileset.first_gid));
+        let sprite = match tile {
+            Some(tile) if !tile.animation.is_empty() => self.resolve_animation(gid, decor, source, resolved)?,
+            _ => self.resolve_static_sprite(gid, decor, source, &mut resolved.tile_images)?,
+        };
+
+        if let Some(terrain) = tile.and_then(|tile| tile.terrain) {
+            resolved.terrain.insert(sprite.clone(), terrain);
+        }
+        Ok(sprite)
+    }
+
+    fn resolve_animation(&self, gid: u32, decor: bool, source: &str, resolved: &mut ResolvedSprites) -> GameResult<String> {
+        let tileset = self.tileset_of(gid, source)?;
+        let local_id = gid - tileset.first_gid;
+        let animation = &tileset.tiles[&local_id].animation;
+
+        let id = format!("{}:{}:animation", tileset.name, local_id);
+        if !resolved.animations.contains_key(&id) {
+            let mut frames: Vec<AnimationFrame> = Vec::new();
+            // an animated tile usually is its own first frame, so frames are never animated
+            for (frame_id, duration) in animation.iter() {
+                let sprite = self.resolve_static_sprite(tileset.first_gid + frame_id, decor, source, &mut resolved.tile_images)?;
+                frames.push(AnimationFrame { sprite, duration: *duration });
+            }
+            resolved.animations.insert(id.clone(), Animation { id: id.clone(), frames });
+        }
+        Ok(id)
+    }
+
+    /// Maps a global tile id to the sprite id used by `Assets`, remembering which tileset
+    /// images the level has to register.
+    fn resolve_static_sprite(&self, gid: u32, decor: bool, source: &str, tile_images: &mut HashMap<(String, bool), TileImage>) -> GameResult<String> {
+        let tileset = self.tileset_of(gid, source)?;
+        let local_id = gid - tileset.first_gid;
+        let tile = tileset.tiles.get(&local_id);
+
+        if let Some(sprite) = tile.and_then(|tile| tile.sprite.clone()) {
+            return Ok(sprite);
+        }
+
+        // level images are namespaced by the level, so they can't replace our own sprites
+        // or show up in the palette of another level
+        let level_sprite = format!("{}:{}:{}", source, tileset.name, local_id);
+        let (sprite, image) = if let Some(image) = tile.and_then(|tile| tile.image.clone()) {
+            if let Some(sprite) = shipped_sprite_id(&image) {
+                return Ok(sprite);
+            }
+            (level_sprite, TileImage {
+                id: String::new(),
+                path: image,
+                source_rect: None,
+                decor,
+            })
+        } else if let Some(image) = &tileset.image {
+            let column = local_id % tileset.columns.max(1);
+            let row = local_id / tileset.columns.max(1);
+            (level_sprite, TileImage {
+                id: String::new(),
+                path: image.clone(),
+                source_rect: Some([
+                    tileset.margin + column * (tileset.tile_width + tileset.spacing),
+                    tileset.margin + row * (tileset.tile_height + tileset.spacing),
+                    tileset.tile_width,
+                    tileset.tile_height,
+                ]),
+                decor,
+            })
+        } else {
+            return Err(tiled_error(source, &format!("tile {} of tileset {} has no image", local_id, tileset.name)));
+        };
+
+        tile_images.entry((sprite.clone(), decor))
+            .or_insert(TileImage { id: sprite.clone(), ..image });
+        Ok(sprite)
+    }
+}
+
+/// Our own resources (`/06.png`) are already loaded under the sprite id the native format
+/// uses (`6`). Images named like them anywhere else are the level's own.
+fn shipped_sprite_id(image: &str) -> Option<String> {
+    let stem = image.strip_prefix('/')?.strip_suffix(".png")?;
+    stem.parse::<u32>().ok().filter(|_| stem.bytes().all(|byte| byte.is_ascii_digit())).map(|id| id.to_string())
+}
+
+/// Resolves `relative` against the directory of the file at `base`, e.g.
+/// `("/levels/04_hairpin.tmx", "../06.png")` becomes `/06.png`.
+fn resolve_path(base: &str, relative: &str) -> String {
+    let mut parts: Vec<&str> = base.split('/').collect();
+    parts.pop();
+    for part in relative.split('/') {
+        match part {
+            "." | "" => {}
+            ".." => {
+                if parts.len() > 1 {
+                    parts.pop();
+                }
+            }
+            _ => parts.push(part),
+        }
+    }
+    let path = parts.join("/");
+    if path.starts_with('/') { path } else { format!("/{}", path) }
+}
+
+fn decode_base64_data(data: &str, compression: &str, source: &str) -> GameResult<Vec<u32>> {
+    let compressed = base64::decode(data.trim())
+        .map_err(|e| tiled_error(source, &format!("invalid base64 tile data: {}", e)))?;
+
+    let mut bytes: Vec<u8> = Vec::new();
+    match compression {
+        "" => bytes = compressed,
+        "zlib" => {
+            flate2::read::ZlibDecoder::new(&compressed[..]).read_to_end(&mut bytes)?;
+        }
+        "gzip" => {
+            flate2::read::GzDecoder::new(&compressed[..]).read_to_end(&mut bytes)?;
+        }
+        _ => return Err(tiled_error(source, &format!("unsupported tile data compression {}", compression))),
+    }
+
+    Ok(bytes.chunks_exact(4)
+        .map(|gid| u32::from_le_bytes([gid[0], gid[1], gid[2], gid[3]]))
+        .collect())
+}
+
+fn decode_csv_data(data: &str, source: &str) -> GameResult<Vec<u32>> {
+    data.split(',')
+        .map(|gid| gid.trim().parse::<u32>().map_err(|_| tiled_error(source, &format!("invalid tile id {}", gid.trim()))))
+        .collect()
+}
+
+fn parse_json_map(json: &Value, source: &str, open: &mut FileOpener) -> GameResult<TiledMap> {
+    if json["infinite"].as_bool() == Some(true) {
+        return Err(tiled_error(source, "infinite maps are not supported"));
+    }
+
+    let mut map = TiledMap {
+        width: json["width"].as_u64().ok_or_else(|| tiled_error(source, "width is missing"))? as u32,
+        height: json["height"].as_u64().ok_or_else(|| tiled_error(source, "height is missing"))? as u32,
+        tile_width: json["tilewidth"].as_f64().ok_or_else(|| tiled_error(source, "tilewidth is missing"))? as f32,
+        tile_height: json["tileheight"].as_f64().ok_or_else(|| tiled_error(source, "tileheight is missing"))? as f32,
+        properties: parse_json_properties(&json["properties"]),
+        tilesets: Vec::new(),
+        layers: Vec::new(),
+        objects: Vec::new(),
+    };
+
+    for tileset in json["tilesets"].as_array().unwrap_or(&Vec::new()) {
+        let first_gid = tileset["firstgid"].as_u64().unwrap_or(1) as u32;
+        let tileset = match tileset["source"].as_str() {
+            Some(tileset_source) => {
+                let tileset_path = resolve_path(source, tileset_source);
+                let contents = open(&tileset_path)?;
+                if tileset_path.ends_with(".tsx") {
+                    let document = roxmltree::Document::parse(&contents)
+                        .map_err(|e| tiled_error(&tileset_path, &format!("invalid XML: {}", e)))?;
+                    parse_xml_tileset(document.root_element(), first_gid, &tileset_path)?
+                } else {
+                    let json: Value = serde_json::from_str(&contents)
+                        .map_err(|e| tiled_error(&tileset_path, &format!("invalid JSON: {}", e)))?;
+                    parse_json_tileset(&json, first_gid, &tileset_path)?
+                }
+            }
+            None => parse_json_tileset(tileset, first_gid, source)?,
+        };
+        map.tilesets.push(tileset);
+    }
+
+    parse_json_layers(&json["layers"], source, &mut map)?;
+    Ok(map)
+}
+
+fn parse_json_layers(layers: &Value, source: &str, map: &mut TiledMap) -> GameResult {
+    for layer in layers.as_array().unwrap_or(&Vec::new()) {
+        match layer["type"].as_str() {
+            Some("tilelayer") => {
+                let data = match &layer["data"] {
+                    Value::Array(gids) => gids.iter().map(|gid| gid.as_u64().unwrap_or(0) as u32).collect(),
+                    Value::String(data) => decode_base64_data(data, layer["compression"].as_str().unwrap_or(""), source)?,
+                    _ => return Err(tiled_error(source, "tile layers must have data (chunked layers are not supported)")),
+                };
+                map.layers.push(TiledLayer {
+                    name: layer["name"].as_str().unwrap_or("").to_string(),
+                    data,
+                    properties: parse_json_properties(&layer["properties"]),
+                });
+            }
+            Some("objectgroup") => {
+                for object in layer["objects"].as_array().unwrap_or(&Vec::new()) {
+                    let class = object["class"].as_str()
+                        .filter(|class| !class.is_empty())
+                        .or_else(|| object["type"].as_str())
+                        .unwrap_or("");
+                    let points = object["polyline"].as_array().unwrap_or(&Vec::new()).iter()
+                        .map(|point| (point["x"].as_f64().unwrap_or(0.0) as f32, point["y"].as_f64().unwrap_or(0.0) as f32))
+                        .collect();
+                    map.objects.push(TiledObject {
+                        class: class.to_string(),
+                        x: object["x"].as_f64().unwrap_or(0.0) as f32,
+                        y: object["y"].as_f64().unwrap_or(0.0) as f32,
+                        width: object["width"].as_f64().unwrap_or(0.0) as f32,
+                        height: object["height"].as_f64().unwrap_or(0.0) as f32,
+                        points,
+                        properties: parse_json_properties(&object["properties"]),
+                    });
+                }
+            }
+            Some("group") => parse_json_layers(&layer["layers"], source, map)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn parse_json_tileset(json: &Value, first_gid: u32, source: &str) -> GameResult<TiledTileset> {
+    let mut tiles: HashMap<u32, TiledTile> = HashMap::new();
+    for tile in json["tiles"].as_array().unwrap_or(&Vec::new()) {
+        let properties = parse_json_properties(&tile["properties"]);
+        tiles.insert(tile["id"].as_u64().unwrap_or(0) as u32, TiledTile {
+            image: tile["image"].as_str().map(|image| resolve_path(source, image)),
+            sprite: properties.get("sprite").and_then(property_to_sprite),
+            terrain: properties_to_terrain(&properties),
+            animation: tile["animation"].as_array().unwrap_or(&Vec::new()).iter()
+                .map(|frame| (frame["tileid"].as_u64().unwrap_or(0) as u32, frame["duration"].as_u64().unwrap_or(0) as u32))
+                .collect(),
+        });
+    }
+
+    let name = json["name"].as_str().unwrap_or("tileset").to_string();
+    Ok(TiledTileset {
+        tile_width: json["tilewidth"].as_u64().ok_or_else(|| tiled_error(source, &format!("tileset {} has no tilewidth", name)))? as u32,
+        tile_height: json["tileheight"].as_u64().ok_or_else(|| tiled_error(source, &format!("tileset {} has no tileheight", name)))? as u32,
+        first_gid,
+        name,
+        columns: json["columns"].as_u64().unwrap_or(0) as u32,
+        margin: json["margin"].as_u64().unwrap_or(0) as u32,
+        spacing: json["spacing"].as_u64().unwrap_or(0) as u32,
+        image: json["image"].as_str().map(|image| resolve_path(source, image)),
+        tiles,
+    })
+}
+
+fn parse_json_properties(properties: &Value) -> HashMap<String, Value> {
+    properties.as_array().unwrap_or(&Vec::new()).iter()
+        .filter_map(|property| Some((property["name"].as_str()?.to_string(), property["value"].clone())))
+        .collect()
+}
+
+fn property_to_sprite(value: &Value) -> Option<String> {
+    match value {
+        Value::Number(n) => Some(n.to_string()),
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        _ => None,
+    }
+}
+
+fn properties_to_terrain(properties: &HashMap<String, Value>) -> Option<Terrain> {
+    let terrain: serde_json::Map<String, Value> = ["speed", "range_bonus", "buildable"].iter()
+        .filter_map(|name| Some((name.to_string(), properties.get(*name)?.clone())))
+        .collect();
+    if terrain.is_empty() {
+        return None;
+    }
+    Terrain::from_json(&terrain.into())
+}
+
+fn xml_attribute<T: std::str::FromStr>(node: roxmltree::Node, name: &str) -> Option<T> {
+    node.attribute(name).and_then(|value| value.parse().ok())
+}
+
+fn parse_xml_map(node: roxmltree::Node, source: &str, open: &mut FileOpener) -> GameResult<TiledMap> {
+    if node.attribute("infinite") == Some("1") {
+        return Err(tiled_error(source, "infinite maps are not supported"));
+    }
+
+    let mut map = TiledMap {
+        width: xml_attribute(node, "width").ok_or_else(|| tiled_error(source, "width is missing"))?,
+        height: xml_attribute(node, "height").ok_or_else(|| tiled_error(source, "height is missing"))?,
+        tile_width: xml_attribute(node, "tilewidth").ok_or_else(|| tiled_error(source, "tilewidth is missing"))?,
+        tile_height: xml_attribute(node, "tileheight").ok_or_else(|| tiled_error(source, "tileheight is missing"))?,
+        properties: parse_xml_properties(node),
+        tilesets: Vec::new(),
+        layers: Vec::new(),
+        objects: Vec::new(),
+    };
+
+    for tileset in node.children().filter(|child| child.has_tag_name("tileset")) {
+        let first_gid = xml_attribute(tileset, "firstgid").unwrap_or(1);
+        let tileset = match tileset.attribute("source") {
+            Some(tileset_source) => {
+                let tileset_path = resolve_path(source, tileset_source);
+                let contents = open(&tileset_path)?;
+                if tileset_path.ends_with(".tsj") || tileset_path.ends_with(".json") {
+                    let json: Value = serde_json::from_str(&contents)
+                        .map_err(|e| tiled_error(&tileset_path, &format!("invalid JSON: {}", e)))?;
+                    parse_json_tileset(&json, first_gid, &tileset_path)?
+                } else {
+                    let document = roxmltree::Document::parse(&contents)
+                        .map_err(|e| tiled_error(&tileset_path, &format!("invalid XML: {}", e)))?;
+                    parse_xml_tileset(document.root_element(), first_gid, &tileset_path)?
+                }
+            }
+            None => parse_xml_tileset(tileset, first_gid, source)?,
+        };
+        map.tilesets.push(tileset);
+    }
+
+    parse_xml_layers(node, source, &mut map)?;
+    Ok(map)
+}
+
+fn parse_xml_layers(node: roxmltree::Node, source: &str, map: &mut TiledMap) -> GameResult {
+    for layer in node.children().filter(|child| child.is_element()) {
+        match layer.tag_name().name() {
+            "layer" => {
+                let data_node = layer.children()
+                    .find(|child| child.has_tag_name("data"))
+                    .ok_or_else(|| tiled_error(source, "tile layers must have data"))?;
+                let text = data_node.text().unwrap_or("");
+                let data = match data_node.attribute("encoding") {
+                    Some("csv") => decode_csv_data(text, source)?,
+                    Some("base64") => decode_base64_data(text, data_node.attribute("compression").unwrap_or(""), source)?,
+                    _ => data_node.children()
+                        .filter(|child| child.has_tag_name("tile"))
+                        .map(|tile| xml_attribute(tile, "gid").unwrap_or(0))
+                        .collect(),
+                };
+                map.layers.push(TiledLayer {
+                    name: layer.attribute("name").unwrap_or("").to_string(),
+                    data,
+                    properties: parse_xml_properties(layer),
+                });
+            }
+            "objectgroup" => {
+                for object in layer.children().filter(|child| child.has_tag_name("object")) {
+                    let class = object.attribute("class")
+                        .or_else(|| object.attribute("type"))
+                        .unwrap_or("");
+                    let points = object.children()
+                        .find(|child| child.has_tag_name("polyline"))
+                        .and_then(|polyline| polyline.attribute("points"))
+                        .unwrap_or("")
+                        .split_whitespace()
+                        .filter_map(|point| {
+                            let mut coords = point.split(',').map(|coord| coord.parse::<f32>());
+                            match (coords.next(), coords.next()) {
+                                (Some(Ok(x)), Some(Ok(y))) => Some((x, y)),
+                                _ => None,
+                            }
+                        })
+                        .collect();
+                    map.objects.push(TiledObject {
+                        class: class.to_string(),
+                        x: xml_attribute(object, "x").unwrap_or(0.0),
+                        y: xml_attribute(object, "y").unwrap_or(0.0),
+                        width: xml_attribute(object, "width").unwrap_or(0.0),
+                        height: xml_attribute(object, "height").unwrap_or(0.0),
+                        points,
+                        properties: parse_xml_properties(object),
+                    });
+                }
+            }
+            "group" => parse_xml_layers(layer, source, map)?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn parse_xml_tileset(node: roxmltree::Node, first_gid: u32, source: &str) -> GameResult<TiledTileset> {
+    let image_source = |node: roxmltree::Node| node.children()
+        .find(|child| child.has_tag_name("image"))
+        .and_then(|image| image.attribute("source"))
+        .map(|image| resolve_path(source, image));
+
+    let mut tiles: HashMap<u32, TiledTile> = HashMap::new();
+    for tile in node.children().filter(|child| child.has_tag_name("tile")) {
+        let properties = parse_xml_properties(tile);
+        tiles.insert(xml_attribute(tile, "id").unwrap_or(0), TiledTile {
+            image: image_source(tile),
+            sprite: properties.get("sprite").and_then(property_to_sprite),
+            terrain: properties_to_terrain(&properties),
+            animation: tile.children()
+                .filter(|child| child.has_tag_name("animation"))
+                .flat_map(|animation| animation.children().filter(|frame| frame.has_tag_name("frame")))
+                .map(|frame| (xml_attribute(frame, "tileid").unwrap_or(0), xml_attribute(frame, "duration").unwrap_or(0)))
+                .collect(),
+        });
+    }
+
+    let name = node.attribute("name").unwrap_or("tileset").to_string();
+    Ok(TiledTileset {
+        tile_width: xml_attribute(node, "tilewidth").ok_or_else(|| tiled_error(source, &format!("tileset {} has no tilewidth", name)))?,
+        tile_height: xml_attribute(node, "tileheight").ok_or_else(|| tiled_error(source, &format!("tileset {} has no tileheight", name)))?,
+        first_gid,
+        name,
+        columns: xml_attribute(node, "columns").unwrap_or(0),
+        margin: xml_attribute(node, "margin").unwrap_or(0),
+        spacing: xml_attribute(node, "spacing").unwrap_or(0),
+        image: image_source(node),
+        tiles,
+    })
+}
+
+fn parse_xml_properties(node: roxmltree::Node) -> HashMap<String, Value> {
+    let properties = match node.children().find(|child| child.has_tag_name("properties")) {
+        Some(properties) => properties,
+        None => return HashMap::new(),
+    };
+
+    properties.children()
+        .filter(|child| child.has_tag_name("property"))
+        .filter_map(|property| {
+            let name = property.attribute("name")?.to_string();
+            let raw = property.attribute("value").or_else(|| property.text()).unwrap_or("");
+            let value = match property.attribute("type") {
+                Some("int") => raw.parse::<i64>().map(Value::from).unwrap_or(Value::Null),
+                Some("float") => raw.parse::<f64>().map(Value::from).unwrap_or(Value::Null),
+                Some("bool") => Value::from(raw == "true"),
+                _ => Value::from(raw),
+            };
+            Some((name, value))
+        })
+        .collect()
+}
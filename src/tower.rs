@@ -2,22 +2,42 @@ use ggez::{graphics, Context, GameResult};
 use crate::movement_helpers::{GridPosition, Direction, RectangleBorder};
 use crate::assets::Assets;
 use ggez::graphics::Color;
+use crate::config::{GRID_CELL_SIZE, MILLIS_PER_UPDATE, UPDATES_PER_SECOND};
+use crate::info_panel::InfoPanel;
+use crate::manifest::AssetCategory;
+use crate::save_game::SavedTower;
+use crate::sprite_sheet::{Action, Animator};
+use crate::terrain::Terrain;
+
+/// How far a tower reaches, in cells from the center of its pad to the center of an enemy.
+pub const TOWER_RANGE: f32 = 6.0;
 
 pub struct Tower {
+    position: GridPosition,
     render_position: GridPosition,
     borders: RectangleBorder,
+    range: f32,
     level: i32,
+    animator: Animator,
+    kills: i32,
+    damage_dealt: i32,
 }
 
 impl Tower {
-    pub fn new(position: GridPosition) -> Self {
+    /// A tower centered at `position`, standing on `terrain`.
+    pub fn new(position: GridPosition, terrain: Terrain) -> Self {
         let pos: (f32, f32) = position.into();
         let offset_render_position = GridPosition::new(pos.0 - 1.0, pos.1 - 1.0);
 
         Tower {
+            position,
             render_position: offset_render_position,
             borders: RectangleBorder::new((pos.0 - 1.0, pos.1 - 1.0).into(), (pos.0 + 1.0, pos.1 + 1.0).into()),
+            range: TOWER_RANGE + terrain.range_bonus,
             level: 0,
+            animator: Animator::new(Action::Idle),
+            kills: 0,
+            damage_dealt: 0,
         }
     }
 
@@ -25,6 +45,16 @@ impl Tower {
         (self.level * self.level + 1) * 100
     }
 
+    /// Half of the honey spent on the tower's upgrades.
+    pub fn sell_value(&self) -> i32 {
+        (0..self.level).map(|level| (level * level + 1) * 100).sum::<i32>() / 2
+    }
+
+    /// Takes the tower back down to an empty slot.
+    pub fn sell(&mut self) {
+        self.level = 0;
+    }
+
     pub fn is_clicking_on(&self, click_pos: GridPosition) -> bool {
         self.borders.is_it_in(click_pos)
     }
@@ -33,23 +63,113 @@ impl Tower {
         self.level * 2
     }
 
+    /// Whether the enemy at `enemy_position`, the top left of its 2x2 cells, can be hit.
+    pub fn is_in_range(&self, enemy_position: GridPosition) -> bool {
+        let (x, y): (f32, f32) = self.position.into();
+        let (enemy_x, enemy_y): (f32, f32) = enemy_position.into();
+        let (dx, dy) = (enemy_x + 1.0 - x, enemy_y + 1.0 - y);
+        (dx * dx + dy * dy).sqrt() <= self.range
+    }
+
     pub fn upgrade(&mut self) {
         self.level += 1;
     }
 
+    pub fn get_position(&self) -> GridPosition {
+        self.position
+    }
+
+    pub fn render_position(&self) -> GridPosition {
+        self.render_position
+    }
+
+    pub fn borders(&self) -> RectangleBorder {
+        self.borders
+    }
+
+    /// How far the tower reaches, in cells.
+    pub fn range(&self) -> f32 {
+        self.range
+    }
+
+    pub fn get_level(&self) -> i32 {
+        self.level
+    }
+
+    /// Takes over the upgrades of a tower the level was reloaded under.
+    pub fn set_level(&mut self, level: i32) {
+        self.level = level;
+    }
+
+    pub fn to_saved(&self) -> SavedTower {
+        SavedTower { position: self.position.into(), level: self.level, kills: self.kills, damage_dealt: self.damage_dealt }
+    }
+
+    /// Takes over the upgrades and stats of a saved tower on the same slot.
+    pub fn restore(&mut self, saved: &SavedTower) {
+        self.level = saved.level;
+        self.kills = saved.kills;
+        self.damage_dealt = saved.damage_dealt;
+    }
+
+    /// Plays the attack animation and counts the hit, call when the tower hit an enemy.
+    pub fn attack(&mut self, damage: i32, killed: bool) {
+        self.animator.play(Action::Attack);
+        self.damage_dealt += damage;
+        if killed {
+            self.kills += 1;
+        }
+    }
+
+    pub fn update(&mut self, assets: &Assets) {
+        self.animator.update(assets.get_sheet(AssetCategory::Towers, self.sprite()), MILLIS_PER_UPDATE);
+    }
+
+    /// The tower's look, from the towers of the asset manifest.
+    fn sprite(&self) -> &'static str {
+        if self.level >= 5 {
+            "tower_5"
+        } else if self.level >= 3 {
+            "tower_3"
+        } else if self.level >= 1 {
+            "tower_1"
+        } else {
+            "tower_disabled"
+        }
+    }
+
     pub fn draw(&self, ctx: &mut Context, assets: &mut Assets) -> GameResult {
-        let current_position: (f32, f32) = self.render_position.into();
-        let tower_sprite = assets.get_tower_image(self.level);
-        let tower_sprite_dest: ggez::mint::Point2<f32> = self.render_position.into();
-        let tower_draw_params = graphics::DrawParam::new().dest(tower_sprite_dest);
-
-        let upgrade_position: GridPosition = (current_position.0 - 1.0, current_position.1 + 2.0).into();
-        let upgrade_str = format!("Price: {}", self.honey_to_upgrade());
-        let upgrade_display = graphics::Text::new((upgrade_str));
-        let upgrade_dest: ggez::mint::Point2<f32> = upgrade_position.into();
-
-        graphics::draw(ctx, tower_sprite, tower_draw_params);
-        graphics::draw(ctx, &upgrade_display, (upgrade_dest, 0.0, Color::BLACK))?;
-        Ok(())
+        assets.draw_sprite(ctx, AssetCategory::Towers, self.sprite(), &self.animator, self.render_position)
+    }
+
+    /// Outlines the area the tower reaches, for the hovered or selected tower.
+    pub fn draw_range(&self, ctx: &mut Context) -> GameResult {
+        let center: ggez::mint::Point2<f32> = self.position.into();
+        let radius = self.range * GRID_CELL_SIZE.0 as f32;
+        let mut mesh = graphics::MeshBuilder::new();
+        mesh.circle(graphics::DrawMode::fill(), center, radius, 0.5, Color::new(1.0, 0.85, 0.3, 0.12))?;
+        mesh.circle(graphics::DrawMode::stroke(1.5), center, radius, 0.5, Color::new(1.0, 0.85, 0.3, 0.6))?;
+        let mesh = mesh.build(ctx)?;
+        graphics::draw(ctx, &mesh, graphics::DrawParam::new())
+    }
+
+    pub fn info(&self) -> InfoPanel {
+        if self.level == 0 {
+            return InfoPanel::new("Empty tower slot".to_string(), vec![
+                format!("Range: {:.1} cells", self.range),
+                format!("Build cost: {} honey", self.honey_to_upgrade()),
+                "Click to build".to_string(),
+            ]);
+        }
+
+        InfoPanel::new(format!("Honey tower, level {}", self.level), vec![
+            format!("Damage: {} per hit", self.get_damage()),
+            format!("Range: {:.1} cells", self.range),
+            format!("Fire rate: {:.0} hits/s", UPDATES_PER_SECOND),
+            format!("Kills: {}", self.kills),
+            format!("Damage dealt: {}", self.damage_dealt),
+            format!("Upgrade cost: {} honey", self.honey_to_upgrade()),
+            format!("Sell value: {} honey (right click)", self.sell_value()),
+        ])
     }
 }
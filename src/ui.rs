@@ -0,0 +1,191 @@
+use ggez::graphics::{self, Color, DrawMode, DrawParam, Rect};
+use ggez::{Context, GameResult};
+use crate::assets::Assets;
+use crate::text::TextStyle;
+use crate::ui_layout::{self, Anchor, ButtonState};
+
+pub const PANEL_COLOR: Color = Color::new(0.08, 0.06, 0.02, 0.85);
+pub const ACCENT_COLOR: Color = Color::new(0.94, 0.75, 0.24, 1.0);
+const BUTTON_HEIGHT: f32 = 28.0;
+const BUTTON_PADDING: f32 = 12.0;
+const MENU_PADDING: f32 = 24.0;
+const MENU_SPACING: f32 = 12.0;
+const MENU_MIN_WIDTH: f32 = 200.0;
+
+/// Immediate mode UI: widgets are drawn and hit tested in the same call, every frame, and
+/// a button reports its click as it's drawn. The areas the last frame's widgets covered
+/// tell whether a click was meant for the UI or for the map below it.
+pub struct Ui {
+    mouse: [f32; 2],
+    /// Where the left button went down and, once it's up again, where it was released.
+    press: Option<[f32; 2]>,
+    release: Option<[f32; 2]>,
+    covered: Vec<Rect>,
+    last_covered: Vec<Rect>,
+    /// Off while drawing the scenes under an overlay, whose widgets are only shown.
+    interactive: bool,
+}
+
+impl Ui {
+    pub fn new() -> Self {
+        Ui { mouse: [-1.0, -1.0], press: None, release: None, covered: Vec::new(), last_covered: Vec::new(), interactive: true }
+    }
+
+    pub fn mouse_motion(&mut self, x: f32, y: f32) {
+        self.mouse = [x, y];
+    }
+
+    /// Records a press of the left button and returns whether it landed on the UI.
+    pub fn mouse_down(&mut self, x: f32, y: f32) -> bool {
+        self.mouse = [x, y];
+        self.press = Some([x, y]);
+        self.release = None;
+        self.is_over(x, y)
+    }
+
+    pub fn mouse_up(&mut self, x: f32, y: f32) {
+        self.mouse = [x, y];
+        if self.press.is_some() {
+            self.release = Some([x, y]);
+        }
+    }
+
+    /// Whether the point is on a widget drawn in the last frame.
+    pub fn is_over(&self, x: f32, y: f32) -> bool {
+        self.last_covered.iter().any(|rect| rect.contains([x, y]))
+    }
+
+    /// Call after drawing every widget of the frame.
+    pub fn end_frame(&mut self) {
+        self.last_covered = std::mem::take(&mut self.covered);
+        if self.release.is_some() {
+            self.press = None;
+            self.release = None;
+        }
+    }
+
+    /// Widgets drawn while the UI isn't interactive don't react to the mouse or take clicks.
+    pub fn set_interactive(&mut self, interactive: bool) {
+        self.interactive = interactive;
+    }
+
+    fn cover(&mut self, rect: Rect) {
+        if self.interactive {
+            self.covered.push(rect);
+        }
+    }
+
+    pub fn panel(&mut self, ctx: &mut Context, rect: Rect) -> GameResult {
+        self.cover(rect);
+        let mut mesh = graphics::MeshBuilder::new();
+        mesh.rectangle(DrawMode::fill(), rect, PANEL_COLOR)?;
+        mesh.rectangle(DrawMode::stroke(1.0), rect, ACCENT_COLOR)?;
+        let mesh = mesh.build(ctx)?;
+        graphics::draw(ctx, &mesh, DrawParam::new())
+    }
+
+    /// Darkens `rect`, e.g. the whole screen behind a menu.
+    pub fn shade(&mut self, ctx: &mut Context, rect: Rect) -> GameResult {
+        let mesh = graphics::Mesh::new_rectangle(ctx, DrawMode::fill(), rect, Color::new(0.0, 0.0, 0.0, 0.5))?;
+        graphics::draw(ctx, &mesh, DrawParam::new())
+    }
+
+    /// Text with its top left corner at `dest`. Labels don't take clicks.
+    pub fn label(&mut self, ctx: &mut Context, assets: &mut Assets, text: &str, dest: [f32; 2], style: &TextStyle) -> GameResult {
+        assets.draw_text(ctx, text, dest.into(), style)
+    }
+
+    /// An image stretched over `rect`.
+    pub fn icon(&mut self, ctx: &mut Context, image: &graphics::Image, rect: Rect) -> GameResult {
+        let scale = [rect.w / image.width() as f32, rect.h / image.height() as f32];
+        graphics::draw(ctx, image, DrawParam::new().dest([rect.x, rect.y]).scale(scale))
+    }
+
+    /// A bar `fraction` full of `color`.
+    pub fn progress_bar(&mut self, ctx: &mut Context, rect: Rect, fraction: f32, color: Color) -> GameResult {
+        let fraction = fraction.clamp(0.0, 1.0);
+        let mut mesh = graphics::MeshBuilder::new();
+        mesh.rectangle(DrawMode::fill(), rect, Color::new(0.15, 0.15, 0.15, 1.0))?;
+        if fraction > 0.0 {
+            mesh.rectangle(DrawMode::fill(), Rect::new(rect.x, rect.y, rect.w * fraction, rect.h), color)?;
+        }
+        mesh.rectangle(DrawMode::stroke(1.0), rect, Color::BLACK)?;
+        let mesh = mesh.build(ctx)?;
+        graphics::draw(ctx, &mesh, DrawParam::new())
+    }
+
+    /// The size of a button showing `text`.
+    pub fn button_size(&mut self, ctx: &mut Context, assets: &mut Assets, text: &str) -> (f32, f32) {
+        let (width, _) = assets.text_dimensions(ctx, text, &button_style(ButtonState::Normal));
+        (width + BUTTON_PADDING * 2.0, BUTTON_HEIGHT)
+    }
+
+    /// Draws a button and returns whether it was clicked, pressed and released on it.
+    pub fn button(&mut self, ctx: &mut Context, assets: &mut Assets, rect: Rect, text: &str, enabled: bool) -> GameResult<bool> {
+        self.cover(rect);
+        let (mouse, held) = if self.interactive {
+            (self.mouse, self.press.filter(|_| self.release.is_none()))
+        } else {
+            ([-1.0, -1.0], None)
+        };
+        let state = ButtonState::of(rect, enabled, mouse, held);
+
+        let fill = match state {
+            ButtonState::Normal => Color::from_rgb(240, 190, 60),
+            ButtonState::Hovered => Color::from_rgb(255, 214, 102),
+            ButtonState::Pressed => Color::from_rgb(196, 148, 36),
+            ButtonState::Disabled => Color::from_rgb(90, 90, 90),
+        };
+        let mut mesh = graphics::MeshBuilder::new();
+        mesh.rectangle(DrawMode::fill(), rect, fill)?;
+        mesh.rectangle(DrawMode::stroke(1.0), rect, Color::BLACK)?;
+        let mesh = mesh.build(ctx)?;
+        graphics::draw(ctx, &mesh, DrawParam::new())?;
+
+        let style = button_style(state);
+        let (width, height) = assets.text_dimensions(ctx, text, &style);
+        // pressed buttons sink in a little
+        let sink = if state == ButtonState::Pressed { 1.0 } else { 0.0 };
+        let dest = [rect.x + (rect.w - width) / 2.0 + sink, rect.y + (rect.h - height) / 2.0 + sink];
+        assets.draw_text(ctx, text, dest.into(), &style)?;
+
+        let clicked = match (self.press, self.release) {
+            (Some(press), Some(release)) => self.interactive && enabled && rect.contains(press) && rect.contains(release),
+            _ => false,
+        };
+        Ok(clicked)
+    }
+
+    /// A panel centered on `screen` with a title over a column of buttons, one for each
+    /// item and whether it's enabled. Returns the index of the button clicked.
+    pub fn menu(&mut self, ctx: &mut Context, assets: &mut Assets, screen: Rect, title: &str, items: &[(&str, bool)]) -> GameResult<Option<usize>> {
+        let title_style = TextStyle::new(Color::WHITE).size(24.0).outline(Color::BLACK);
+        let title_size = assets.text_dimensions(ctx, title, &title_style);
+        let mut button_width = MENU_MIN_WIDTH;
+        for (text, _) in items.iter() {
+            button_width = button_width.max(self.button_size(ctx, assets, text).0);
+        }
+        let sizes = vec![(button_width, BUTTON_HEIGHT); items.len()];
+
+        let height = title_size.1 + items.len() as f32 * (BUTTON_HEIGHT + MENU_SPACING);
+        let size = (title_size.0.max(button_width) + MENU_PADDING * 2.0, height + MENU_PADDING * 2.0);
+        let panel = Anchor::Center.place(screen, size, 0.0);
+        self.panel(ctx, panel)?;
+        self.label(ctx, assets, title, [panel.x + (panel.w - title_size.0) / 2.0, panel.y + MENU_PADDING], &title_style)?;
+
+        let start = [panel.x + (panel.w - button_width) / 2.0, panel.y + MENU_PADDING + title_size.1 + MENU_SPACING];
+        let buttons = ui_layout::column(start, &sizes, MENU_SPACING);
+        let mut clicked: Option<usize> = None;
+        for (index, ((text, enabled), rect)) in items.iter().zip(buttons).enumerate() {
+            if self.button(ctx, assets, rect, text, *enabled)? {
+                clicked = Some(index);
+            }
+        }
+        Ok(clicked)
+    }
+}
+
+fn button_style(state: ButtonState) -> TextStyle {
+    let color = if state == ButtonState::Disabled { Color::from_rgb(170, 170, 170) } else { Color::BLACK };
+    TextStyle::new(color).size(16.0)
+}
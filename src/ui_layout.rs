@@ -0,0 +1,96 @@
+//! Placing UI on the screen. Widgets are anchored to a point of the screen, an edge or a
+//! corner, so they stay in place when the window is resized.
+
+use ggez::graphics::Rect;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    /// Where the anchor is across `within`, from 0 for the left or top to 1 for the right
+    /// or bottom.
+    fn fractions(&self) -> (f32, f32) {
+        match self {
+            Anchor::TopLeft => (0.0, 0.0),
+            Anchor::Top => (0.5, 0.0),
+            Anchor::TopRight => (1.0, 0.0),
+            Anchor::Left => (0.0, 0.5),
+            Anchor::Center => (0.5, 0.5),
+            Anchor::Right => (1.0, 0.5),
+            Anchor::BottomLeft => (0.0, 1.0),
+            Anchor::Bottom => (0.5, 1.0),
+            Anchor::BottomRight => (1.0, 1.0),
+        }
+    }
+
+    /// A rectangle of `size` pinned to this anchor of `within`, `margin` pixels away from
+    /// the edges it touches. Centered rectangles ignore the margin on that axis.
+    pub fn place(&self, within: Rect, size: (f32, f32), margin: f32) -> Rect {
+        let (fx, fy) = self.fractions();
+        let inset = |fraction: f32| if fraction == 0.0 { margin } else if fraction == 1.0 { -margin } else { 0.0 };
+        let x = within.x + (within.w - size.0) * fx + inset(fx);
+        let y = within.y + (within.h - size.1) * fy + inset(fy);
+        Rect::new(x, y, size.0, size.1)
+    }
+}
+
+/// How a button looks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ButtonState {
+    Normal,
+    Hovered,
+    /// The mouse went down on the button and is still held over it.
+    Pressed,
+    Disabled,
+}
+
+impl ButtonState {
+    /// The state of a button at `rect` given where the mouse is and where it was pressed,
+    /// if it is held down.
+    pub fn of(rect: Rect, enabled: bool, mouse: [f32; 2], press: Option<[f32; 2]>) -> ButtonState {
+        if !enabled {
+            return ButtonState::Disabled;
+        }
+        if !rect.contains(mouse) {
+            return ButtonState::Normal;
+        }
+        match press {
+            Some(press) if rect.contains(press) => ButtonState::Pressed,
+            _ => ButtonState::Hovered,
+        }
+    }
+}
+
+/// Lays out rectangles in a row from left to right, `spacing` pixels apart.
+pub fn row(start: [f32; 2], sizes: &[(f32, f32)], spacing: f32) -> Vec<Rect> {
+    let mut x = start[0];
+    sizes.iter()
+        .map(|(width, height)| {
+            let rect = Rect::new(x, start[1], *width, *height);
+            x += width + spacing;
+            rect
+        })
+        .collect()
+}
+
+/// Lays out rectangles in a column from top to bottom, `spacing` pixels apart.
+pub fn column(start: [f32; 2], sizes: &[(f32, f32)], spacing: f32) -> Vec<Rect> {
+    let mut y = start[1];
+    sizes.iter()
+        .map(|(width, height)| {
+            let rect = Rect::new(start[0], y, *width, *height);
+            y += height + spacing;
+            rect
+        })
+        .collect()
+}
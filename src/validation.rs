@@ -0,0 +1,157 @@
+//! Sanity checks for levels that the loaders can't catch on their own: sprites without an
+//! image, which the game would silently draw with the default tile, and routes that leave
+//! the map, never reach the nexus or run over a tower. Tower slots on terrain that can't be
+//! built on are reported too.
+
+use std::collections::HashSet;
+use crate::level::Level;
+use crate::manifest::{AssetCategory, AssetManifest};
+use crate::movement_helpers::{GridPosition, RectangleBorder};
+
+/// Lists everything wrong with `level`, in a form meant to be read by whoever edits it.
+/// An empty list means the level is good to play. `manifest` tells which sprites the game has.
+pub fn validate_level(level: &Level, manifest: &AssetManifest) -> Vec<String> {
+    let mut problems: Vec<String> = Vec::new();
+    check_tiles(level, manifest, &mut problems);
+    check_routes(level, &mut problems);
+    problems
+}
+
+fn check_tiles(level: &Level, manifest: &AssetManifest, problems: &mut Vec<String>) {
+    let mut tile_sprites: HashSet<&str> = manifest.entries(AssetCategory::Tiles).map(|(id, _)| id).collect();
+    let mut decor_sprites: HashSet<&str> = manifest.entries(AssetCategory::Decor).map(|(id, _)| id).collect();
+    for tile_image in level.tile_images.iter() {
+        if tile_image.decor {
+            decor_sprites.insert(&tile_image.id);
+        } else {
+            tile_sprites.insert(&tile_image.id);
+        }
+    }
+
+    for animation in level.animations.iter() {
+        for frame in animation.frames.iter() {
+            if !tile_sprites.contains(frame.sprite.as_str()) && !decor_sprites.contains(frame.sprite.as_str()) {
+                problems.push(format!("animation {} shows sprite {} which has no image", animation.id, frame.sprite));
+            }
+        }
+        if animation.frames.iter().all(|frame| frame.duration == 0) {
+            problems.push(format!("animation {} has no frame with a duration", animation.id));
+        }
+    }
+    // animations can be used wherever a sprite can
+    for animation in level.animations.iter() {
+        tile_sprites.insert(&animation.id);
+        decor_sprites.insert(&animation.id);
+    }
+
+    for y in 0..level.grid_size.1 {
+        for x in 0..level.grid_size.0 {
+            let tile = match level.tile(x, y) {
+                Some(tile) => tile,
+                None => {
+                    problems.push(format!("cell ({}, {}) is missing", x, y));
+                    continue;
+                }
+            };
+
+            if tile.sprite.is_empty() {
+                problems.push(format!("cell ({}, {}) has no tile", x, y));
+            } else if !tile_sprites.contains(tile.sprite.as_str()) {
+                problems.push(format!("cell ({}, {}) uses tile {} which has no image", x, y, tile.sprite));
+            }
+            if let Some(decor) = &tile.decor {
+                if !decor_sprites.contains(decor.as_str()) {
+                    problems.push(format!("cell ({}, {}) uses decor {} which has no image", x, y, decor));
+                }
+            }
+        }
+    }
+
+    for layer in level.layers.iter() {
+        for ((x, y), sprite) in layer.sprites.iter() {
+            if !tile_sprites.contains(sprite.as_str()) && !decor_sprites.contains(sprite.as_str()) {
+                problems.push(format!("layer {} at ({}, {}) uses sprite {} which has no image", layer.name, x, y, sprite));
+            }
+        }
+    }
+}
+
+fn check_routes(level: &Level, problems: &mut Vec<String>) {
+    let on_grid = |(x, y): (i16, i16)| x >= 0 && y >= 0 && x < level.grid_size.0 && y < level.grid_size.1;
+    let tower_pads: Vec<RectangleBorder> = level.tower_slots.iter()
+        .map(|slot| {
+            let (x, y): (f32, f32) = (*slot).into();
+            RectangleBorder::new((x - 1.0, y - 1.0).into(), (x, y).into())
+        })
+        .collect();
+    let mut blocked_slots: Vec<bool> = vec![false; tower_pads.len()];
+
+    for (spawn_index, spawn) in level.spawns.iter().enumerate() {
+        let start: (i16, i16) = spawn.position.into();
+        if !on_grid(start) {
+            problems.push(format!("spawn {} at ({}, {}) is outside the grid", spawn_index, start.0, start.1));
+        }
+
+        let mut previous = start;
+        let mut reaches_nexus = false;
+        for (checkpoint_index, checkpoint) in spawn.path.iter().enumerate() {
+            let next: (i16, i16) = (*checkpoint).into();
+            if !on_grid(next) {
+                problems.push(format!(
+                    "spawn {} checkpoint {} at ({}, {}) is outside the grid", spawn_index, checkpoint_index, next.0, next.1
+                ));
+            }
+            if next.0 != previous.0 && next.1 != previous.1 {
+                problems.push(format!(
+                    "spawn {} checkpoint {} at ({}, {}) is not in line with ({}, {})",
+                    spawn_index, checkpoint_index, next.0, next.1, previous.0, previous.1
+                ));
+                previous = next;
+                continue;
+            }
+
+            // enemies leave the route as soon as they step into the nexus
+            for (x, y) in walk(previous, next) {
+                if reaches_nexus {
+                    break;
+                }
+                reaches_nexus = level.nexus.borders.is_it_in((x, y).into());
+
+                // an enemy covers the 2x2 cells starting at its position
+                for cell in [(x, y), (x + 1, y), (x, y + 1), (x + 1, y + 1)].iter() {
+                    let cell: GridPosition = (*cell).into();
+                    for (slot_index, pad) in tower_pads.iter().enumerate() {
+                        blocked_slots[slot_index] |= pad.is_it_in(cell);
+                    }
+                }
+            }
+            previous = next;
+        }
+
+        if !reaches_nexus {
+            problems.push(format!("the route of spawn {} never reaches the nexus", spawn_index));
+        }
+    }
+
+    for (slot, blocked) in level.tower_slots.iter().zip(blocked_slots) {
+        let (x, y): (i16, i16) = (*slot).into();
+        if blocked {
+            problems.push(format!("tower slot ({}, {}) overlaps an enemy route", x, y));
+        }
+        if !level.tower_terrain(*slot).buildable {
+            problems.push(format!("tower slot ({}, {}) is on terrain that can't be built on", x, y));
+        }
+    }
+}
+
+/// Every cell from `from` to `to` along a straight line, both ends included.
+fn walk(from: (i16, i16), to: (i16, i16)) -> Vec<(i16, i16)> {
+    let step = ((to.0 - from.0).signum(), (to.1 - from.1).signum());
+    let mut cells: Vec<(i16, i16)> = vec![from];
+    let mut cell = from;
+    while cell != to {
+        cell = (cell.0 + step.0, cell.1 + step.1);
+        cells.push(cell);
+    }
+    cells
+}
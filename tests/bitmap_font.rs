@@ -0,0 +1,24 @@
+use rust_game::bitmap_font::{BitmapFont, PlacedGlyph};
+use serde_json::json;
+
+#[test]
+fn test_bitmap_font_layout() {
+    let json = json!({"image": "/fonts/pixel.png", "grid": [6, 8], "chars": "ABCDE", "spacing": 1});
+    assert_eq!(BitmapFont::image_path(&json, "pixel.json").unwrap(), "/fonts/pixel.png");
+    let font = BitmapFont::from_json(&json, "pixel.json", (18, 16)).unwrap();
+
+    // the glyphs wrap to the second row after three, unknown characters leave a gap
+    assert_eq!(font.layout("AD?B\nE"), vec![
+        PlacedGlyph { rect: [0, 0, 6, 8], x: 0.0, y: 0.0 },
+        PlacedGlyph { rect: [0, 8, 6, 8], x: 7.0, y: 0.0 },
+        PlacedGlyph { rect: [6, 0, 6, 8], x: 21.0, y: 0.0 },
+        PlacedGlyph { rect: [6, 8, 6, 8], x: 0.0, y: 9.0 },
+    ]);
+    assert_eq!(font.measure("AD?B\nE"), (27.0, 17.0));
+}
+
+#[test]
+fn test_bitmap_font_rejects_too_many_chars() {
+    let json = json!({"image": "/fonts/pixel.png", "grid": [6, 8], "chars": "ABCDEFG"});
+    assert!(BitmapFont::from_json(&json, "pixel.json", (18, 16)).is_err());
+}
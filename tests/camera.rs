@@ -0,0 +1,43 @@
+use rust_game::camera::Camera;
+use rust_game::movement_helpers::GridPosition;
+
+#[test]
+fn test_screen_to_grid_follows_pan_and_zoom() {
+    let mut camera = Camera::new((1024., 512.), (128, 64));
+    assert_eq!(camera.screen_to_grid(40., 24.), GridPosition::new(2.5, 1.5));
+
+    camera.pan(160., 32.);
+    assert_eq!(camera.screen_to_grid(0., 0.), GridPosition::new(10., 2.));
+
+    // the cell under the cursor stays in place while zooming
+    camera.zoom_at(2., (512., 256.));
+    assert_eq!(camera.screen_to_grid(512., 256.), GridPosition::new(42., 18.));
+    assert_eq!(camera.screen_to_grid(0., 0.), GridPosition::new(26., 10.));
+    assert_eq!(camera.world_to_screen(42. * 16., 18. * 16.), (512., 256.));
+}
+
+#[test]
+fn test_camera_stays_on_the_map() {
+    let mut camera = Camera::new((1024., 512.), (128, 64));
+    camera.pan(-500., -500.);
+    assert_eq!(camera.screen_to_grid(0., 0.), GridPosition::new(0., 0.));
+
+    camera.pan(10000., 10000.);
+    assert_eq!(camera.screen_to_grid(1024., 512.), GridPosition::new(128., 64.));
+    assert_eq!(camera.visible_cells((128, 64)), ((64, 32), (128, 64)));
+
+    // maps smaller than the window are centered
+    let small = Camera::new((1024., 512.), (32, 16));
+    assert_eq!(small.screen_to_grid(256., 128.), GridPosition::new(0., 0.));
+}
+
+#[test]
+fn test_look_at_centers_the_view() {
+    let mut camera = Camera::new((1024., 512.), (128, 64));
+    camera.look_at(GridPosition::new(64., 32.));
+    assert_eq!(camera.screen_to_grid(512., 256.), GridPosition::new(64., 32.));
+
+    // near the edge the view stops at the map
+    camera.look_at(GridPosition::new(2., 2.));
+    assert_eq!(camera.screen_to_grid(0., 0.), GridPosition::new(0., 0.));
+}
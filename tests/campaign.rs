@@ -0,0 +1,57 @@
+use rust_game::campaign::{self, Profile};
+
+#[test]
+fn test_new_profile_unlocks_only_the_first_level() {
+    let profile = Profile::new();
+    assert!(profile.is_unlocked(0));
+    assert!(!profile.is_unlocked(1));
+    assert_eq!(profile.best_score("/levels/01_meadow.json"), None);
+}
+
+#[test]
+fn test_completing_a_level_unlocks_the_next() {
+    let mut profile = Profile::new();
+    profile.record_result(0, "/levels/01_meadow.json", 100, false);
+    assert!(!profile.is_unlocked(1));
+
+    profile.record_result(0, "/levels/01_meadow.json", 100, true);
+    assert!(profile.is_unlocked(1));
+    assert!(!profile.is_unlocked(2));
+
+    // replaying an earlier level doesn't lock the later ones again
+    profile.record_result(2, "/levels/03_canyon.json", 50, true);
+    profile.record_result(0, "/levels/01_meadow.json", 10, true);
+    assert!(profile.is_unlocked(3));
+    assert!(!profile.is_unlocked(4));
+}
+
+#[test]
+fn test_best_score_only_goes_up() {
+    let mut profile = Profile::new();
+    profile.record_result(0, "/levels/01_meadow.json", 300, false);
+    assert_eq!(profile.best_score("/levels/01_meadow.json"), Some(300));
+
+    profile.record_result(0, "/levels/01_meadow.json", 200, true);
+    assert_eq!(profile.best_score("/levels/01_meadow.json"), Some(300));
+
+    profile.record_result(0, "/levels/01_meadow.json", 450, false);
+    assert_eq!(profile.best_score("/levels/01_meadow.json"), Some(450));
+    assert_eq!(profile.best_score("/levels/02_switchback.json"), None);
+}
+
+#[test]
+fn test_level_paths_prefer_the_saved_level_file() {
+    let paths = vec![
+        "/levels/03_canyon.tmx".to_string(),
+        "/levels/01_meadow.json".to_string(),
+        "/levels/02_switchback.png".to_string(),
+        "/levels/03_canyon.json".to_string(),
+        "/levels/04_river.tmj".to_string(),
+    ];
+    assert_eq!(campaign::level_paths(paths), vec![
+        "/levels/01_meadow.json",
+        "/levels/02_switchback.png",
+        "/levels/03_canyon.json",
+        "/levels/04_river.tmj",
+    ]);
+}
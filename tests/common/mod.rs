@@ -0,0 +1,7 @@
+use rust_game::level::Level;
+
+/// The first campaign level, which is known to be valid.
+pub fn load_meadow() -> Level {
+    let contents = std::fs::read_to_string("src/resources/levels/01_meadow.json").unwrap();
+    Level::from_json(&serde_json::from_str(&contents).unwrap(), "01_meadow.json").unwrap()
+}
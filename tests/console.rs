@@ -0,0 +1,88 @@
+use rust_game::console::{Command, Console};
+
+/// An open console that can be typed into.
+fn open_console() -> Console {
+    let mut console = Console::new();
+    console.open();
+    console.update();
+    console
+}
+
+#[test]
+fn test_parse_commands_with_arguments() {
+    assert_eq!(Command::parse("honey 5000"), Ok(Command::Honey(5000)));
+    assert_eq!(Command::parse("  lives   10 "), Ok(Command::Lives(10)));
+    assert_eq!(Command::parse("spawn slime_orange 20"), Ok(Command::Spawn { sprite: "slime_orange".to_string(), count: 20 }));
+    assert_eq!(Command::parse("spawn slime_blue"), Ok(Command::Spawn { sprite: "slime_blue".to_string(), count: 1 }));
+    assert_eq!(Command::parse("hardness 8"), Ok(Command::Hardness(8)));
+    assert_eq!(Command::parse("upgrade all"), Ok(Command::UpgradeAll));
+    assert_eq!(Command::parse("tick 1000"), Ok(Command::Tick(1000)));
+    assert_eq!(Command::parse("godmode"), Ok(Command::GodMode));
+}
+
+#[test]
+fn test_parse_rejects_bad_commands() {
+    assert!(Command::parse("honey").is_err());
+    assert!(Command::parse("honey lots").is_err());
+    assert!(Command::parse("tick -5").is_err());
+    assert!(Command::parse("spawn").is_err());
+    assert!(Command::parse("spawn slime_blue many").is_err());
+    assert!(Command::parse("upgrade").is_err());
+    assert!(Command::parse("fly").is_err());
+    assert!(Command::parse("").is_err());
+}
+
+#[test]
+fn test_submit_echoes_and_clears_the_input() {
+    let mut console = open_console();
+    for character in "seed\r`".chars() {
+        console.type_char(character);
+    }
+    assert_eq!(console.input, "seed`");
+    console.backspace();
+    assert_eq!(console.submit(), Some("seed".to_string()));
+    assert_eq!(console.input, "");
+    assert_eq!(console.log().back().map(String::as_str), Some("> seed"));
+
+    console.type_char(' ');
+    assert_eq!(console.submit(), None);
+}
+
+#[test]
+fn test_key_that_opened_the_console_is_not_typed() {
+    let mut console = Console::new();
+    console.type_char('c');
+    assert_eq!(console.input, "");
+
+    // whatever key the console is bound to, the character it types comes before the next update
+    console.open();
+    console.type_char('c');
+    assert_eq!(console.input, "");
+    console.update();
+    console.type_char('c');
+    assert_eq!(console.input, "c");
+
+    console.close();
+    console.type_char('c');
+    assert_eq!(console.input, "c");
+}
+
+#[test]
+fn test_history_goes_back_and_forward() {
+    let mut console = open_console();
+    for line in ["honey 1", "lives 2"].iter() {
+        console.input = line.to_string();
+        console.submit();
+    }
+
+    console.history_back();
+    assert_eq!(console.input, "lives 2");
+    console.history_back();
+    assert_eq!(console.input, "honey 1");
+    console.history_back();
+    assert_eq!(console.input, "honey 1");
+    console.history_forward();
+    assert_eq!(console.input, "lives 2");
+    console.history_forward();
+    assert_eq!(console.input, "");
+}
@@ -0,0 +1,13 @@
+<?xml version="1.0" encoding="UTF-8"?>
+<tileset version="1.5" tiledversion="1.7.2" name="meadow" tilewidth="16" tileheight="16" tilecount="2" columns="0">
+ <tile id="0">
+  <image width="16" height="16" source="../06.png"/>
+ </tile>
+ <tile id="1">
+  <properties>
+   <property name="speed" type="float" value="0.5"/>
+   <property name="buildable" type="bool" value="false"/>
+  </properties>
+  <image width="16" height="16" source="../82.png"/>
+ </tile>
+</tileset>
@@ -0,0 +1,26 @@
+use rust_game::generator;
+use rust_game::manifest::AssetManifest;
+use rust_game::validation;
+
+fn load_manifest() -> AssetManifest {
+    let contents = std::fs::read_to_string("src/resources/assets.json").unwrap();
+    AssetManifest::from_json(&serde_json::from_str(&contents).unwrap(), "assets.json").unwrap()
+}
+
+#[test]
+fn test_generated_levels_are_playable() {
+    let manifest = load_manifest();
+    for seed in 0..50 {
+        let level = generator::generate_level(seed);
+        assert_eq!(validation::validate_level(&level, &manifest), Vec::<String>::new(), "seed {}", seed);
+        assert!(level.spawns[0].path.len() >= 3, "seed {}", seed);
+        assert!(!level.tower_slots.is_empty(), "seed {}", seed);
+        assert_eq!(level.enemy_count, None);
+    }
+}
+
+#[test]
+fn test_same_seed_generates_the_same_level() {
+    assert_eq!(generator::generate_level(7).to_json(), generator::generate_level(7).to_json());
+    assert_ne!(generator::generate_level(7).to_json(), generator::generate_level(8).to_json());
+}
@@ -0,0 +1,47 @@
+use rust_game::health::{self, Health};
+
+#[test]
+fn test_take_damage_goes_through_shield_then_armor() {
+    let mut health = Health::new(100).with_shield(20).with_armor(50);
+    assert!(!health.is_damaged());
+
+    assert_eq!(health.take_damage(10), 10);
+    assert_eq!((health.shield, health.current), (10, 100));
+    assert!(health.is_damaged());
+
+    // 10 breaks the shield, armor blocks half of the other 20
+    assert_eq!(health.take_damage(30), 30);
+    assert_eq!((health.shield, health.current), (0, 90));
+
+    // the damage that isn't needed to kill is left over for the next enemy
+    assert_eq!(health.take_damage(1000), 179);
+    assert!(!health.is_alive());
+    assert_eq!(health.take_damage(10), 0);
+}
+
+#[test]
+fn test_fractions_and_bar_color() {
+    let mut health = Health::new(200);
+    health.take_damage(150);
+    assert_eq!(health.fraction(), 0.25);
+    assert_eq!(health.shield_fraction(), 0.0);
+
+    assert_eq!(health::bar_color(1.0).g, 0.8);
+    assert_eq!(health::bar_color(1.0).r, 0.0);
+    assert_eq!(health::bar_color(0.0).r, 1.0);
+    assert_eq!(health::bar_color(0.0).g, 0.0);
+}
+
+#[test]
+fn test_killing_through_armor_uses_the_least_damage() {
+    // half of 1 is blocked down to nothing, so 1 damage already kills
+    let mut health = Health::new(1).with_armor(50);
+    assert_eq!(health.take_damage(5), 1);
+    assert!(!health.is_alive());
+
+    let mut health = Health::new(3).with_armor(50);
+    assert_eq!(health.take_damage(10), 5);
+
+    let mut health = Health::new(10).with_armor(30);
+    assert_eq!(health.take_damage(100), 13);
+}
@@ -0,0 +1,23 @@
+use rust_game::hot_reload::ResourceWatcher;
+use std::fs;
+
+#[test]
+fn test_watcher_reports_changed_files() {
+    let root = std::env::temp_dir().join(format!("rust_game_hot_reload_{}", std::process::id()));
+    fs::create_dir_all(root.join("levels")).unwrap();
+    fs::write(root.join("levels/01.json"), "{}").unwrap();
+    fs::write(root.join("70.png"), "grass").unwrap();
+
+    let mut watcher = ResourceWatcher::new(&root);
+    assert!(watcher.scan().is_empty());
+
+    fs::write(root.join("levels/01.json"), "{\"name\": \"changed\"}").unwrap();
+    fs::write(root.join("71.png"), "road").unwrap();
+    assert_eq!(watcher.scan(), vec!["/71.png".to_string(), "/levels/01.json".to_string()]);
+    assert!(watcher.scan().is_empty());
+
+    fs::remove_file(root.join("70.png")).unwrap();
+    assert_eq!(watcher.scan(), vec!["/70.png".to_string()]);
+
+    fs::remove_dir_all(&root).unwrap();
+}
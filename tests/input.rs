@@ -0,0 +1,40 @@
+use ggez::event::KeyCode;
+use rust_game::input::{self, Action, Bindings};
+use serde_json::json;
+
+#[test]
+fn test_binding_a_taken_key_swaps() {
+    let mut bindings = Bindings::default();
+    assert_eq!(bindings.action_for(KeyCode::Escape), Some(Action::Pause));
+
+    bindings.bind(Action::Pause, KeyCode::U);
+    assert_eq!(bindings.key_for(Action::Pause), KeyCode::U);
+    assert_eq!(bindings.key_for(Action::UpgradeSelected), KeyCode::Escape);
+    assert_eq!(bindings.action_for(KeyCode::U), Some(Action::Pause));
+}
+
+#[test]
+fn test_bindings_from_json() {
+    assert_eq!(Bindings::from_json(&json!({})), Bindings::default());
+
+    let bindings = Bindings::from_json(&json!({"pause": "P", "speed_up": "no such key"}));
+    assert_eq!(bindings.key_for(Action::Pause), KeyCode::P);
+    assert_eq!(bindings.key_for(Action::SpeedUp), KeyCode::Space);
+
+    let mut bindings = Bindings::default();
+    bindings.bind(Action::QuickSave, KeyCode::Key1);
+    assert_eq!(Bindings::from_json(&bindings.to_json()), bindings);
+    assert_eq!(input::parse_key(input::key_name(KeyCode::Grave).unwrap()), Some(KeyCode::Grave));
+}
+
+#[test]
+fn test_reserved_keys_are_never_bound() {
+    let bindings = Bindings::default();
+    for key in input::RESERVED_KEYS.iter() {
+        assert_eq!(input::key_name(*key), None, "{:?} can be bound", key);
+        assert_eq!(bindings.action_for(*key), None, "{:?} is bound by default", key);
+    }
+
+    let json = json!({"pause": "W", "speed_up": "F2", "toggle_debug": "H"});
+    assert_eq!(Bindings::from_json(&json), Bindings::default());
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use rust_game::level::{Animation, AnimationFrame, Layer, Level};
+use rust_game::tiled;
+use std::collections::BTreeMap;
+use common::load_meadow;
+
+#[test]
+fn test_layers_and_animations_survive_saving() {
+    let mut level = load_meadow();
+    let mut sprites = BTreeMap::new();
+    sprites.insert((3, 2), "105".to_string());
+    level.layers.push(Layer { name: "canopy".to_string(), above_entities: true, sprites });
+    level.animations.push(Animation {
+        id: "water".to_string(),
+        frames: vec![
+            AnimationFrame { sprite: "80".to_string(), duration: 300 },
+            AnimationFrame { sprite: "81".to_string(), duration: 100 },
+        ],
+    });
+
+    let saved: serde_json::Value = serde_json::from_str(&level.to_json_string()).unwrap();
+    let loaded = Level::from_json(&saved, "saved.json").unwrap();
+    assert_eq!(loaded.layers, level.layers);
+    assert_eq!(loaded.animations, level.animations);
+
+    assert_eq!(loaded.frame_sprite("water", 0), "80");
+    assert_eq!(loaded.frame_sprite("water", 350), "81");
+    assert_eq!(loaded.frame_sprite("water", 420), "80");
+    assert_eq!(loaded.frame_sprite("70", 350), "70");
+}
+
+#[test]
+fn test_tiled_tile_animations_become_level_animations() {
+    let contents = std::fs::read_to_string("src/resources/levels/04_hairpin.tmx").unwrap();
+    let mut open = |path: &str| Ok(std::fs::read_to_string(format!("src/resources{}", path))?);
+    let level = tiled::load_tmx(&contents, "/levels/04_hairpin.tmx", &mut open).unwrap();
+
+    let pond = &level.tile(50, 23).unwrap().sprite;
+    let animation = level.animation(pond).unwrap();
+    let frames: Vec<&str> = animation.frames.iter().map(|frame| frame.sprite.as_str()).collect();
+    assert_eq!(frames, vec!["80", "81"]);
+}
+
+#[test]
+fn test_grid_size_out_of_range_is_an_error() {
+    let mut json: serde_json::Value = serde_json::from_str(&load_meadow().to_json_string()).unwrap();
+    json["grid_size"] = serde_json::json!([40000, 1]);
+    let error = Level::from_json(&json, "huge.json").err().unwrap();
+    assert!(error.to_string().contains("grid_size can be at most 32767 cells a side"), "{}", error);
+
+    json["grid_size"] = serde_json::json!([0, 10]);
+    assert!(Level::from_json(&json, "empty.json").is_err());
+}
+
+#[test]
+fn test_grid_size_without_the_tiles_is_an_error() {
+    let mut json: serde_json::Value = serde_json::from_str(&load_meadow().to_json_string()).unwrap();
+    json["grid_size"] = serde_json::json!([32767, 32767]);
+    let error = Level::from_json(&json, "huge.json").err().unwrap();
+    assert!(error.to_string().contains("rows, expected 32767"), "{}", error);
+}
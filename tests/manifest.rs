@@ -0,0 +1,26 @@
+use rust_game::manifest::{AssetCategory, AssetManifest};
+use serde_json::json;
+use std::path::Path;
+
+#[test]
+fn test_shipped_manifest_lists_existing_files() {
+    let contents = std::fs::read_to_string("src/resources/assets.json").unwrap();
+    let manifest = AssetManifest::from_json(&serde_json::from_str(&contents).unwrap(), "assets.json").unwrap();
+
+    let missing = manifest.missing_files(|path| Path::new("src/resources").join(path.trim_start_matches('/')).is_file());
+    assert_eq!(missing, Vec::<String>::new());
+    assert_eq!(manifest.path(AssetCategory::Enemies, "slime_green"), Some("/slime_green.json"));
+    assert_eq!(manifest.path(AssetCategory::Enemies, "slime_orange"), Some("/slime_orange.json"));
+}
+
+#[test]
+fn test_manifest_errors_name_the_problem() {
+    let defaults = json!({ "tiles": "/t.png", "decor": "/d.png", "towers": "/t.png", "enemies": "/e.png", "ui": "/u.png" });
+    let manifest = AssetManifest::from_json(&json!({ "tiles": { "70": "/70.png" }, "defaults": defaults }), "test").unwrap();
+    assert_eq!(manifest.missing_files(|path| path != "/70.png"), vec!["tiles 70: /70.png".to_string()]);
+
+    let error = AssetManifest::from_json(&json!({ "defaults": { "tiles": "/t.png" } }), "test").err().unwrap();
+    assert!(error.to_string().contains("defaults.decor is missing"), "{}", error);
+    let error = AssetManifest::from_json(&json!({ "music": {}, "defaults": defaults }), "test").err().unwrap();
+    assert!(error.to_string().contains("unknown category music"), "{}", error);
+}
@@ -0,0 +1,56 @@
+use ggez::graphics::Color;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rust_game::particles::Particles;
+
+#[test]
+fn test_numbers_add_up_and_float_away() {
+    let mut particles = Particles::new();
+    particles.float_number(10, (100.0, 100.0), Color::WHITE);
+    particles.float_number(5, (104.0, 100.0), Color::WHITE);
+    particles.float_number(7, (300.0, 100.0), Color::WHITE);
+    let texts: Vec<&str> = particles.texts().iter().map(|text| text.text.as_str()).collect();
+    assert_eq!(texts, vec!["15", "7"]);
+
+    particles.update(0.5);
+    assert!(particles.texts()[0].position.1 < 100.0);
+    // older numbers aren't added to
+    particles.float_number(3, (100.0, 100.0), Color::WHITE);
+    assert_eq!(particles.texts().len(), 3);
+
+    particles.update(0.5);
+    assert_eq!(particles.texts().len(), 1);
+}
+
+#[test]
+fn test_particles_fall_and_expire() {
+    let mut particles = Particles::new();
+    particles.burst(&mut StdRng::seed_from_u64(7), (0.0, 0.0), Color::WHITE, 20);
+    assert_eq!(particles.particles().len(), 20);
+
+    particles.update(0.1);
+    particles.update(0.1);
+    let velocity_before = particles.particles()[0].velocity.1;
+    particles.update(0.1);
+    assert!(particles.particles()[0].velocity.1 > velocity_before);
+
+    particles.update(1.0);
+    assert!(particles.particles().is_empty());
+}
+
+#[test]
+fn test_shake_and_flash_calm_down() {
+    let mut particles = Particles::new();
+    assert_eq!(particles.shake_offset(), (0.0, 0.0));
+
+    particles.shake(8.0, 0.5);
+    particles.flash(0.5);
+    particles.update(0.25);
+    let (x, y) = particles.shake_offset();
+    assert!(x.abs() <= 4.0 && y.abs() <= 4.0 && (x, y) != (0.0, 0.0));
+    assert_eq!(particles.flash_alpha(), 0.5);
+
+    particles.update(0.25);
+    assert_eq!(particles.shake_offset(), (0.0, 0.0));
+    assert_eq!(particles.flash_alpha(), 0.0);
+}
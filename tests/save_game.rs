@@ -0,0 +1,33 @@
+use rust_game::health::Health;
+use rust_game::save_game::{SaveGame, SavedEnemy, SavedTower};
+use serde_json::json;
+
+#[test]
+fn test_save_game_round_trip() {
+    let save_game = SaveGame {
+        level_path: "/levels/level_1.json".to_string(),
+        endless_seed: u64::MAX,
+        score: 1200,
+        honey: 340,
+        lives: 7,
+        ticks: 512,
+        hardness: 8,
+        spawned_enemies: 73,
+        towers: vec![SavedTower { position: (12, 30), level: 3, kills: 14, damage_dealt: 2500 }],
+        enemies: vec![SavedEnemy {
+            sprite: "slime_orange".to_string(),
+            hardness: 8,
+            health: Health::new(800).with_shield(400).with_armor(40),
+            spawn_index: 1,
+            checkpoint_index: 4,
+            position: (20.5, 16.0),
+        }],
+    };
+    assert_eq!(SaveGame::from_json(&save_game.to_json()), Some(save_game));
+}
+
+#[test]
+fn test_save_game_needs_its_fields() {
+    assert_eq!(SaveGame::from_json(&json!({})), None);
+    assert_eq!(SaveGame::from_json(&json!({"level": "/levels/level_1.json", "towers": [], "enemies": []})), None);
+}
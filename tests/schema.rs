@@ -0,0 +1,64 @@
+use rust_game::movement_helpers::GridPosition;
+use rust_game::schema;
+
+#[test]
+fn test_map_schema_traces_the_meadow_route() {
+    let bytes = std::fs::read("src/resources/map_schema.png").unwrap();
+    let level = schema::load_schema(&bytes, "map_schema.png").unwrap();
+
+    assert_eq!(level.grid_size, (64, 32));
+    assert_eq!(level.spawns.len(), 1);
+    assert_eq!(level.spawns[0].position, GridPosition::new(0., 4.));
+
+    let expected_path: Vec<GridPosition> = vec![(55, 4), (55, 27), (7, 27), (7, 10), (47, 10), (47, 18), (18, 18)]
+        .into_iter()
+        .map(|checkpoint: (i16, i16)| checkpoint.into())
+        .collect();
+    assert_eq!(level.spawns[0].path, expected_path);
+    assert_eq!(level.tower_slots.len(), 7);
+    assert_eq!(level.tile(5, 4).unwrap().sprite, "6");
+}
+
+/// A schema image drawn from rows of palette letters.
+fn schema_png(rows: &[&str]) -> Vec<u8> {
+    let mut image = image::RgbaImage::new(rows[0].len() as u32, rows.len() as u32);
+    for (y, row) in rows.iter().enumerate() {
+        for (x, letter) in row.chars().enumerate() {
+            let rgb = match letter {
+                '#' => [255, 0, 0],
+                'S' => [255, 255, 255],
+                'N' => [255, 0, 255],
+                'B' => [0, 0, 255],
+                _ => [0, 255, 0],
+            };
+            image.put_pixel(x as u32, y as u32, image::Rgba([rgb[0], rgb[1], rgb[2], 255]));
+        }
+    }
+    let mut bytes: Vec<u8> = Vec::new();
+    image::DynamicImage::ImageRgba8(image).write_to(&mut bytes, image::ImageOutputFormat::Png).unwrap();
+    bytes
+}
+
+#[test]
+fn test_explicit_spawn_and_nexus_pixels() {
+    let bytes = schema_png(&[
+        "..........",
+        "..S#####NN",
+        "..######NN",
+        "..BB......",
+        "..BB......",
+    ]);
+    let level = schema::load_schema(&bytes, "explicit.png").unwrap();
+
+    assert_eq!(level.spawns.len(), 1);
+    assert_eq!(level.spawns[0].position, GridPosition::new(2., 1.));
+    assert_eq!(level.spawns[0].path, vec![GridPosition::new(8., 1.)]);
+    assert_eq!(level.tower_slots, vec![GridPosition::new(3., 4.)]);
+}
+
+#[test]
+fn test_schema_wider_than_a_grid_is_an_error() {
+    let row = ".".repeat(i16::MAX as usize + 1);
+    let error = schema::load_schema(&schema_png(&[&row]), "wide.png").err().unwrap();
+    assert!(error.to_string().contains("at most 32767 pixels a side"), "{}", error);
+}
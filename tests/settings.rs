@@ -0,0 +1,19 @@
+use rust_game::settings::{HealthBars, Settings};
+use serde_json::json;
+
+#[test]
+fn test_settings_from_json() {
+    assert_eq!(Settings::from_json(&json!({})), Settings::default());
+
+    let settings = Settings::from_json(&json!({"master_volume": 1.5, "music_volume": -0.2, "sfx_volume": 0.3, "text_scale": 4}));
+    assert_eq!(settings.master_volume, 1.0);
+    assert_eq!(settings.music_volume, 0.0);
+    assert_eq!(settings.sfx_volume, 0.3);
+    assert_eq!(settings.text_scale, 2.0);
+}
+
+#[test]
+fn test_settings_round_trip() {
+    let settings = Settings { master_volume: 0.5, music_volume: 0.25, sfx_volume: 0.75, text_scale: 1.5, health_bars: HealthBars::Hover, effects: false };
+    assert_eq!(Settings::from_json(&settings.to_json()), settings);
+}
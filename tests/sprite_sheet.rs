@@ -0,0 +1,84 @@
+use rust_game::movement_helpers::Direction;
+use rust_game::sprite_sheet::{Action, Animator, SpriteSheet};
+use serde_json::json;
+
+fn load_slime() -> SpriteSheet {
+    let contents = std::fs::read_to_string("src/resources/slime_blue.json").unwrap();
+    SpriteSheet::from_json(&serde_json::from_str(&contents).unwrap(), "slime_blue.json", (64, 24)).unwrap()
+}
+
+#[test]
+fn test_animator_plays_one_shot_actions_and_faces() {
+    let sheet = load_slime();
+    assert_eq!(sheet.frames.len(), 4);
+    assert_eq!(sheet.frames[2], [32, 0, 16, 24]);
+
+    let mut animator = Animator::new(Action::Walk);
+    animator.update(&sheet, 250);
+    assert_eq!(animator.frame(&sheet).rect, sheet.frames[2]);
+    animator.face(Direction::Left);
+    animator.face(Direction::Up);
+    assert!(animator.frame(&sheet).flip_x);
+
+    // walking doesn't interrupt getting hit, which goes back to walking when it's over
+    animator.play(Action::Hit);
+    animator.play(Action::Walk);
+    assert_eq!(animator.action(), Action::Hit);
+    animator.update(&sheet, 200);
+    assert_eq!(animator.action(), Action::Walk);
+
+    animator.play(Action::Death);
+    animator.play(Action::Walk);
+    assert!(!animator.is_finished(&sheet));
+    animator.update(&sheet, 1000);
+    assert_eq!(animator.action(), Action::Death);
+    assert!(animator.is_finished(&sheet));
+    assert_eq!(animator.frame(&sheet).rect, sheet.frames[2]);
+}
+
+#[test]
+fn test_atlas_frames_and_fallbacks() {
+    let json = json!({
+        "image": "/tower.png",
+        "frames": [[0, 0, 32, 32], [32, 0, 32, 32]],
+        "animations": { "idle": { "frames": [0] }, "attack_up": { "frames": [1], "loop": false } },
+    });
+    let sheet = SpriteSheet::from_json(&json, "tower.json", (64, 32)).unwrap();
+
+    let mut animator = Animator::new(Action::Idle);
+    animator.face(Direction::Left);
+    animator.play(Action::Walk);
+    assert_eq!(animator.frame(&sheet).rect, [0, 0, 32, 32]);
+    animator.face(Direction::Up);
+    animator.play(Action::Attack);
+    assert_eq!(animator.frame(&sheet).rect, [32, 0, 32, 32]);
+    assert!(!animator.frame(&sheet).flip_x);
+
+    let json = json!({ "image": "/tower.png", "frames": [[48, 0, 32, 32]] });
+    assert!(SpriteSheet::from_json(&json, "tower.json", (64, 32)).is_err());
+    let json = json!({ "image": "/tower.png", "frames": [[0, 0, 0, 32]] });
+    assert!(SpriteSheet::from_json(&json, "tower.json", (64, 32)).is_err());
+    let json = json!({ "image": "/tower.png", "frames": [[4294967295u32, 0, 2, 32]] });
+    assert!(SpriteSheet::from_json(&json, "tower.json", (64, 32)).is_err());
+}
+
+#[test]
+fn test_one_shot_actions_without_an_animation_finish_right_away() {
+    let json = json!({
+        "image": "/bee.png",
+        "grid": [16, 16],
+        "animations": { "idle": { "frames": [0, 1] } },
+    });
+    let sheet = SpriteSheet::from_json(&json, "bee.json", (32, 16)).unwrap();
+    assert!(sheet.animation(Action::Death, Direction::Right).is_none());
+
+    let mut animator = Animator::new(Action::Idle);
+    animator.play(Action::Hit);
+    animator.update(&sheet, 10);
+    assert_eq!(animator.action(), Action::Idle);
+
+    // a missing death doesn't fall back to the looping idle, which would never finish
+    animator.play(Action::Death);
+    assert!(animator.is_finished(&sheet));
+    assert_eq!(animator.frame(&sheet).rect, [0, 0, 16, 16]);
+}
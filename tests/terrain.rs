@@ -0,0 +1,57 @@
+mod common;
+
+use rust_game::level::{Animation, AnimationFrame, Layer, Level};
+use rust_game::movement_helpers::GridPosition;
+use rust_game::terrain::{self, Terrain};
+use std::collections::BTreeMap;
+use common::load_meadow;
+
+#[test]
+fn test_terrain_stacks_over_the_layers_of_a_cell() {
+    let mut level = load_meadow();
+    assert_eq!(level.terrain_at(0, 0), Terrain::default());
+
+    level.tile_mut(0, 0).unwrap().sprite = terrain::MUD.to_string();
+    let mut sprites = BTreeMap::new();
+    sprites.insert((0, 0), terrain::HONEY.to_string());
+    level.layers.push(Layer { name: "spill".to_string(), above_entities: false, sprites });
+    assert_eq!(level.terrain_at(0, 0).speed, 0.5 * 0.25);
+
+    // animations fall back to their first frame
+    level.animations.push(Animation {
+        id: "water".to_string(),
+        frames: vec![AnimationFrame { sprite: "80".to_string(), duration: 600 }],
+    });
+    level.tile_mut(1, 0).unwrap().sprite = "water".to_string();
+    assert!(!level.terrain_at(1, 0).buildable);
+
+    // the level's own terrain wins over the defaults
+    level.terrain.insert("water".to_string(), Terrain { speed: 0.75, ..Terrain::default() });
+    assert_eq!(level.terrain_at(1, 0), Terrain { speed: 0.75, ..Terrain::default() });
+}
+
+#[test]
+fn test_tower_terrain_and_saving() {
+    let mut level = load_meadow();
+    level.tile_mut(15, 7).unwrap().decor = Some(terrain::HILL.to_string());
+    level.terrain.insert("101".to_string(), Terrain { range_bonus: 1.5, buildable: false, ..Terrain::default() });
+
+    let tower_terrain = level.tower_terrain(GridPosition::new(16., 8.));
+    assert_eq!(tower_terrain.range_bonus, 2.0);
+    assert!(tower_terrain.buildable);
+
+    let saved: serde_json::Value = serde_json::from_str(&level.to_json_string()).unwrap();
+    assert_eq!(saved["terrain"]["101"], serde_json::json!({ "range_bonus": 1.5, "buildable": false }));
+    let loaded = Level::from_json(&saved, "saved.json").unwrap();
+    assert_eq!(loaded.terrain, level.terrain);
+}
+
+#[test]
+fn test_terrain_that_stops_enemies_or_shrinks_range_is_an_error() {
+    let mut saved: serde_json::Value = serde_json::from_str(&load_meadow().to_json_string()).unwrap();
+    for terrain in [serde_json::json!({ "speed": 0 }), serde_json::json!({ "speed": -1 }), serde_json::json!({ "range_bonus": -0.5 })].iter() {
+        saved["terrain"] = serde_json::json!({ "101": terrain });
+        let error = Level::from_json(&saved, "saved.json").err().unwrap().to_string();
+        assert!(error.contains("terrain of sprite 101 is invalid"), "{}", error);
+    }
+}
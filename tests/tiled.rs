@@ -0,0 +1,139 @@
+use ggez::GameResult;
+use rust_game::level::Level;
+use rust_game::movement_helpers::GridPosition;
+use rust_game::terrain::Terrain;
+use rust_game::tiled;
+
+/// Loads a map from tests/fixtures/tiled as if it were in the levels folder.
+fn load_fixture(file_name: &str) -> GameResult<Level> {
+    let contents = std::fs::read_to_string(format!("tests/fixtures/tiled/{}", file_name)).unwrap();
+    let source = format!("/levels/{}", file_name);
+    let mut open = |path: &str| Ok(std::fs::read_to_string(format!("tests/fixtures/tiled/{}", path.trim_start_matches("/levels/")))?);
+    if file_name.ends_with(".tmx") {
+        tiled::load_tmx(&contents, &source, &mut open)
+    } else {
+        tiled::load_tmj(&contents, &source, &mut open)
+    }
+}
+
+fn cells(cells: &[(i16, i16)]) -> Vec<GridPosition> {
+    cells.iter().map(|cell| (*cell).into()).collect()
+}
+
+#[test]
+fn test_tmj_map_with_a_tileset_image() {
+    let level = load_fixture("sheet.tmj").unwrap();
+    assert_eq!(level.name, "Sheet");
+    assert_eq!(level.grid_size, (4, 3));
+    assert_eq!(level.starting_honey, 250);
+
+    assert_eq!(level.tile(0, 0).unwrap().sprite, "/levels/sheet.tmj:sheet:0");
+    assert_eq!(level.tile(1, 1).unwrap().sprite, "/levels/sheet.tmj:sheet:2");
+    assert_eq!(level.tile(0, 2).unwrap().sprite, "/levels/sheet.tmj:sheet:3");
+
+    // tiles cut out of the sheet skip its margin and the spacing between tiles
+    let image = level.tile_images.iter().find(|image| image.id == "/levels/sheet.tmj:sheet:3" && !image.decor).unwrap();
+    assert_eq!(image.path, "/levels/tiles.png");
+    assert_eq!(image.source_rect, Some([19, 19, 16, 16]));
+}
+
+#[test]
+fn test_flipped_gids_are_masked() {
+    let level = load_fixture("sheet.tmj").unwrap();
+    assert_eq!(level.tile(0, 1).unwrap().decor.as_deref(), Some("/levels/sheet.tmj:sheet:1"));
+    assert_eq!(level.tile(3, 2).unwrap().decor.as_deref(), Some("/levels/sheet.tmj:sheet:3"));
+    assert_eq!(level.tile(1, 0).unwrap().decor, None);
+    assert!(level.tile_images.iter().any(|image| image.id == "/levels/sheet.tmj:sheet:1" && image.decor));
+}
+
+#[test]
+fn test_object_layer_spawns_nexus_and_slots() {
+    for file_name in ["sheet.tmj", "csv.tmx", "zlib.tmx"].iter() {
+        let level = load_fixture(file_name).unwrap();
+        assert_eq!(level.spawns.len(), 1, "{}", file_name);
+        assert_eq!(level.spawns[0].position, GridPosition::new(0., 1.), "{}", file_name);
+        assert_eq!(level.spawns[0].path, cells(&[(2, 1), (2, 2)]), "{}", file_name);
+        assert_eq!(level.nexus.position, GridPosition::new(3., 2.), "{}", file_name);
+        assert_eq!(level.nexus.borders.top_left_corner(), GridPosition::new(3., 2.), "{}", file_name);
+        assert_eq!(level.nexus.borders.bot_right_corner(), GridPosition::new(3., 2.), "{}", file_name);
+    }
+
+    assert_eq!(load_fixture("sheet.tmj").unwrap().tower_slots, cells(&[(1, 0)]));
+    assert_eq!(load_fixture("csv.tmx").unwrap().tower_slots, cells(&[(1, 0)]));
+    assert!(load_fixture("zlib.tmx").unwrap().tower_slots.is_empty());
+}
+
+#[test]
+fn test_tmx_map_with_csv_data() {
+    let level = load_fixture("csv.tmx").unwrap();
+    assert_eq!(level.name, "Csv");
+    assert_eq!(level.enemy_count, Some(20));
+
+    // `sprite` properties name our own sprites, and so do our own images
+    assert_eq!(level.tile(0, 0).unwrap().sprite, "6");
+    assert_eq!(level.tile(2, 0).unwrap().sprite, "14");
+    assert_eq!(level.tile(1, 1).unwrap().sprite, "70");
+    assert!(level.tile_images.is_empty());
+}
+
+#[test]
+fn test_level_images_named_like_our_sprites_get_their_own_id() {
+    let map = r#"<map width="2" height="1" tilewidth="16" tileheight="16">
+        <tileset firstgid="1" name="art" tilewidth="16" tileheight="16" columns="0">
+            <tile id="0"><image width="16" height="16" source="art/70.png"/></tile>
+        </tileset>
+        <layer name="ground" width="2" height="1"><data encoding="csv">1,1</data></layer>
+        <objectgroup name="objects">
+            <object type="path" x="0" y="0"><polyline points="0,0 16,0"/></object>
+            <object type="nexus" x="16" y="0" width="16" height="16"/>
+        </objectgroup>
+    </map>"#;
+    let level = tiled::load_tmx(map, "/levels/art.tmx", &mut |_| unreachable!()).unwrap();
+
+    assert_eq!(level.tile(0, 0).unwrap().sprite, "/levels/art.tmx:art:0");
+    assert_eq!(level.tile_images.len(), 1);
+    assert_eq!(level.tile_images[0].id, "/levels/art.tmx:art:0");
+    assert_eq!(level.tile_images[0].path, "/levels/art/70.png");
+}
+
+#[test]
+fn test_tmx_map_with_zlib_data_and_an_external_tileset() {
+    let level = load_fixture("zlib.tmx").unwrap();
+    assert_eq!(level.name, "/levels/zlib.tmx");
+
+    let sprites: Vec<&str> = (0..4).map(|x| level.tile(x, 1).unwrap().sprite.as_str()).collect();
+    assert_eq!(sprites, vec!["6", "82", "82", "6"]);
+    assert_eq!(level.terrain.get("82"), Some(&Terrain { speed: 0.5, range_bonus: 0.0, buildable: false }));
+    assert_eq!(level.terrain.get("6"), None);
+}
+
+#[test]
+fn test_tileset_without_tile_size_is_an_error() {
+    let map = r#"<map width="1" height="1" tilewidth="16" tileheight="16">
+        <tileset firstgid="1" name="broken" tileheight="16" columns="1"><image source="tiles.png"/></tileset>
+        <layer name="ground"><data encoding="csv">1</data></layer>
+    </map>"#;
+    let mut open = |path: &str| Ok(std::fs::read_to_string(path)?);
+    let error = tiled::load_tmx(map, "/levels/broken.tmx", &mut open).err().unwrap();
+    assert!(error.to_string().contains("tileset broken has no tilewidth"), "{}", error);
+
+    let map = r#"{"type": "map", "tiledversion": "1.8.2", "width": 1, "height": 1, "tilewidth": 16, "tileheight": 16,
+        "tilesets": [{"firstgid": 1, "name": "broken", "tilewidth": 16, "columns": 1, "image": "tiles.png"}],
+        "layers": [{"type": "tilelayer", "name": "ground", "data": [1]}]}"#;
+    let error = tiled::load_tmj(map, "/levels/broken.tmj", &mut open).err().unwrap();
+    assert!(error.to_string().contains("tileset broken has no tileheight"), "{}", error);
+}
+
+#[test]
+fn test_map_size_out_of_range_is_an_error() {
+    let mut open = |path: &str| Ok(std::fs::read_to_string(path)?);
+    let map = |width: u32| format!(r#"<map width="{}" height="1" tilewidth="16" tileheight="16">
+        <layer name="ground"><data encoding="csv">1</data></layer>
+        <objectgroup><object type="nexus" x="0" y="0" width="16" height="16"/></objectgroup>
+    </map>"#, width);
+
+    let error = tiled::load_tmx(&map(40000), "/levels/huge.tmx", &mut open).err().unwrap();
+    assert!(error.to_string().contains("at most 32767 cells a side"), "{}", error);
+    let error = tiled::load_tmx(&map(0), "/levels/empty.tmx", &mut open).err().unwrap();
+    assert!(error.to_string().contains("at least one cell"), "{}", error);
+}
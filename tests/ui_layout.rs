@@ -0,0 +1,29 @@
+use ggez::graphics::Rect;
+use rust_game::ui_layout::{self, Anchor, ButtonState};
+
+#[test]
+fn test_anchor_place() {
+    let screen = Rect::new(0.0, 0.0, 800.0, 600.0);
+    assert_eq!(Anchor::TopLeft.place(screen, (100.0, 50.0), 4.0), Rect::new(4.0, 4.0, 100.0, 50.0));
+    assert_eq!(Anchor::Top.place(screen, (100.0, 50.0), 4.0), Rect::new(350.0, 4.0, 100.0, 50.0));
+    assert_eq!(Anchor::Center.place(screen, (100.0, 50.0), 4.0), Rect::new(350.0, 275.0, 100.0, 50.0));
+    assert_eq!(Anchor::BottomRight.place(screen, (100.0, 50.0), 4.0), Rect::new(696.0, 546.0, 100.0, 50.0));
+}
+
+#[test]
+fn test_button_state() {
+    let button = Rect::new(10.0, 10.0, 100.0, 30.0);
+    assert_eq!(ButtonState::of(button, true, [0.0, 0.0], None), ButtonState::Normal);
+    assert_eq!(ButtonState::of(button, true, [20.0, 20.0], None), ButtonState::Hovered);
+    assert_eq!(ButtonState::of(button, true, [20.0, 20.0], Some([30.0, 15.0])), ButtonState::Pressed);
+    // pressed somewhere else and dragged onto the button
+    assert_eq!(ButtonState::of(button, true, [20.0, 20.0], Some([300.0, 15.0])), ButtonState::Hovered);
+    assert_eq!(ButtonState::of(button, false, [20.0, 20.0], Some([30.0, 15.0])), ButtonState::Disabled);
+}
+
+#[test]
+fn test_row_and_column() {
+    let sizes = [(10.0, 5.0), (20.0, 8.0)];
+    assert_eq!(ui_layout::row([1.0, 2.0], &sizes, 4.0), vec![Rect::new(1.0, 2.0, 10.0, 5.0), Rect::new(15.0, 2.0, 20.0, 8.0)]);
+    assert_eq!(ui_layout::column([1.0, 2.0], &sizes, 4.0), vec![Rect::new(1.0, 2.0, 10.0, 5.0), Rect::new(1.0, 11.0, 20.0, 8.0)]);
+}
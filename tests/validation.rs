@@ -0,0 +1,29 @@
+mod common;
+
+use rust_game::manifest::AssetManifest;
+use rust_game::movement_helpers::GridPosition;
+use rust_game::validation;
+use common::load_meadow;
+
+fn load_manifest() -> AssetManifest {
+    let contents = std::fs::read_to_string("src/resources/assets.json").unwrap();
+    AssetManifest::from_json(&serde_json::from_str(&contents).unwrap(), "assets.json").unwrap()
+}
+
+#[test]
+fn test_validate_level_reports_problems() {
+    let manifest = load_manifest();
+    let mut level = load_meadow();
+    assert!(validation::validate_level(&level, &manifest).is_empty());
+
+    level.tile_mut(3, 2).unwrap().sprite = "999".to_string();
+    level.spawns[0].path[0] = GridPosition::new(54., 5.);
+    level.tower_slots.push(GridPosition::new(8., 20.));
+    level.tile_mut(15, 7).unwrap().sprite = "80".to_string();
+
+    let problems = validation::validate_level(&level, &manifest);
+    assert!(problems.contains(&"cell (3, 2) uses tile 999 which has no image".to_string()));
+    assert!(problems.contains(&"spawn 0 checkpoint 0 at (54, 5) is not in line with (0, 4)".to_string()));
+    assert!(problems.contains(&"tower slot (8, 20) overlaps an enemy route".to_string()));
+    assert!(problems.contains(&"tower slot (16, 8) is on terrain that can't be built on".to_string()));
+}